@@ -29,7 +29,7 @@ pub enum ApprovalStatus {
 }
 
 /// Whether a mod can be resolved by clients or not
-#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash)]
+#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum ResolvableStatus {
     Unresolvable(String),
     Resolvable,
@@ -75,7 +75,7 @@ impl ModSpecification {
 }
 
 /// Points to a specific version of a specific mod
-#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash)]
+#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct ModResolution {
     pub url: ModIdentifier,
     pub status: ResolvableStatus,
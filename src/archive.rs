@@ -0,0 +1,237 @@
+//! Archive formats mods can be distributed in, shared by integration, lints and the download
+//! checks of the HTTP provider.
+//!
+//! Formats are registered through `inventory::submit!` like mod providers and are recognized by
+//! their magic bytes rather than by file extension, since downloaded mods are stored without one.
+
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use snafu::prelude::*;
+
+use crate::providers::ReadSeek;
+
+#[derive(Debug, Snafu)]
+pub enum ArchiveError {
+    #[snafu(transparent)]
+    IoError { source: std::io::Error },
+    #[snafu(display("zip archive error: {source}"))]
+    ZipError { source: zip::result::ZipError },
+    #[snafu(display("7z archive error: {source}"))]
+    SevenZError { source: sevenz_rust::Error },
+    #[snafu(display("xz decompression error: {source}"))]
+    XzError { source: lzma_rs::error::Error },
+}
+
+/// Visitor receiving the path of every file and directory in an archive, along with the contents
+/// of files. Directories have no contents.
+pub type ArchiveVisitor<'a> =
+    dyn FnMut(&Path, Option<&mut dyn Read>) -> Result<(), ArchiveError> + 'a;
+
+pub struct ArchiveFormat {
+    pub name: &'static str,
+    /// File name extensions of the format, without the leading dot.
+    pub extensions: &'static [&'static str],
+    /// Whether data starting with `header` is in this format. `header` holds at least the first
+    /// [`HEADER_LEN`] bytes unless the data is shorter.
+    detect: fn(header: &[u8]) -> bool,
+    /// Call the visitor for every entry in the archive, in archive order.
+    visit: fn(data: &mut dyn ReadSeek, f: &mut ArchiveVisitor) -> Result<(), ArchiveError>,
+}
+
+impl std::fmt::Debug for ArchiveFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArchiveFormat")
+            .field("name", &self.name)
+            .field("extensions", &self.extensions)
+            .finish()
+    }
+}
+
+inventory::collect!(ArchiveFormat);
+
+/// Number of leading bytes needed to detect any format. The tar magic is the furthest in at 262
+/// bytes, compressed tar archives need more to decompress that far.
+pub const HEADER_LEN: usize = 1024;
+
+pub fn formats() -> impl Iterator<Item = &'static ArchiveFormat> {
+    inventory::iter::<ArchiveFormat>()
+}
+
+/// Format of data starting with `header`, or `None` if it's not a known archive.
+pub fn detect_format(header: &[u8]) -> Option<&'static ArchiveFormat> {
+    formats().find(|f| (f.detect)(header))
+}
+
+/// Whether `name` has the extension of a known archive format.
+pub fn is_archive_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    formats()
+        .flat_map(|f| f.extensions)
+        .any(|ext| name.ends_with(&format!(".{ext}")))
+}
+
+/// Contents of a mod file, which is either a pak or an archive containing paks.
+pub enum ModContents {
+    Pak(Box<dyn ReadSeek>),
+    Archive {
+        format: &'static ArchiveFormat,
+        /// Paks in archive order.
+        paks: Vec<(PathBuf, Vec<u8>)>,
+        /// Paths of all other entries, including directories.
+        other_files: Vec<PathBuf>,
+    },
+}
+
+/// Read a mod file. Data that isn't a known archive is assumed to be a pak.
+pub fn read_mod_contents(mut data: Box<dyn ReadSeek>) -> Result<ModContents, ArchiveError> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    (&mut data)
+        .take(HEADER_LEN as u64)
+        .read_to_end(&mut header)?;
+    data.rewind()?;
+
+    let Some(format) = detect_format(&header) else {
+        return Ok(ModContents::Pak(data));
+    };
+
+    let mut paks = vec![];
+    let mut other_files = vec![];
+    (format.visit)(&mut *data, &mut |path, reader| {
+        match reader {
+            Some(reader)
+                if path
+                    .extension()
+                    .is_some_and(|e| e.eq_ignore_ascii_case("pak")) =>
+            {
+                let mut buf = vec![];
+                reader.read_to_end(&mut buf)?;
+                paks.push((path.to_path_buf(), buf));
+            }
+            _ => other_files.push(path.to_path_buf()),
+        }
+        Ok(())
+    })?;
+    Ok(ModContents::Archive {
+        format,
+        paks,
+        other_files,
+    })
+}
+
+/// Visit the entries of a tar archive, skipping anything but regular files and directories.
+fn visit_tar<R: Read>(data: R, f: &mut ArchiveVisitor) -> Result<(), ArchiveError> {
+    let mut archive = tar::Archive::new(data);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_type = entry.header().entry_type();
+        let path = entry.path()?.into_owned();
+        if entry_type.is_file() {
+            f(&path, Some(&mut entry))?;
+        } else if entry_type.is_dir() {
+            f(&path, None)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_tar(header: &[u8]) -> bool {
+    header.get(257..262) == Some(&b"ustar"[..])
+}
+
+/// Whether `header` is the start of a gzip stream containing a tar archive, as opposed to e.g. a
+/// single compressed pak.
+fn is_tar_gz(header: &[u8]) -> bool {
+    if !header.starts_with(b"\x1F\x8B") {
+        return false;
+    }
+    let mut tar = vec![];
+    // the stream is cut off so decompression ends with an error, keeping what was read until then
+    let _ = flate2::read::GzDecoder::new(header)
+        .take(512)
+        .read_to_end(&mut tar);
+    is_tar(&tar)
+}
+
+inventory::submit! {
+    ArchiveFormat {
+        name: "zip",
+        extensions: &["zip"],
+        detect: |header| {
+            [b"PK\x03\x04", b"PK\x05\x06", b"PK\x07\x08"]
+                .iter()
+                .any(|magic| header.starts_with(*magic))
+        },
+        visit: |data, f| {
+            let mut archive = zip::ZipArchive::new(data).context(ZipSnafu)?;
+            for i in 0..archive.len() {
+                let mut file = archive.by_index(i).context(ZipSnafu)?;
+                if let Some(path) = file.enclosed_name().map(Path::to_path_buf) {
+                    if file.is_dir() {
+                        f(&path, None)?;
+                    } else {
+                        f(&path, Some(&mut file))?;
+                    }
+                }
+            }
+            Ok(())
+        },
+    }
+}
+
+inventory::submit! {
+    ArchiveFormat {
+        name: "7z",
+        extensions: &["7z"],
+        detect: |header| header.starts_with(b"7z\xBC\xAF\x27\x1C"),
+        visit: |data, f| {
+            let len = data.seek(SeekFrom::End(0))?;
+            data.rewind()?;
+            let mut archive =
+                sevenz_rust::SevenZReader::new(data, len, sevenz_rust::Password::empty())
+                    .context(SevenZSnafu)?;
+            // errors of the visitor can't pass through the 7z reader, so stop and return them
+            // afterwards
+            let mut result = Ok(());
+            archive
+                .for_each_entries(|entry, reader| {
+                    let reader = (!entry.is_directory()).then_some(reader);
+                    result = f(Path::new(entry.name()), reader);
+                    Ok(result.is_ok())
+                })
+                .context(SevenZSnafu)?;
+            result
+        },
+    }
+}
+
+inventory::submit! {
+    ArchiveFormat {
+        name: "tar",
+        extensions: &["tar"],
+        detect: is_tar,
+        visit: |data, f| visit_tar(data, f),
+    }
+}
+
+inventory::submit! {
+    ArchiveFormat {
+        name: "tar.gz",
+        extensions: &["tar.gz", "tgz"],
+        detect: is_tar_gz,
+        visit: |data, f| visit_tar(flate2::read::GzDecoder::new(data), f),
+    }
+}
+
+inventory::submit! {
+    ArchiveFormat {
+        name: "tar.xz",
+        extensions: &["tar.xz", "txz"],
+        detect: |header| header.starts_with(b"\xFD7zXZ\x00"),
+        visit: |data, f| {
+            let mut tar = vec![];
+            lzma_rs::xz_decompress(&mut BufReader::new(data), &mut tar).context(XzSnafu)?;
+            visit_tar(Cursor::new(tar), f)
+        },
+    }
+}
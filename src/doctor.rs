@@ -0,0 +1,322 @@
+//! Diagnostics of the game installation and the installed mod bundle, used by `mint doctor` and
+//! the diagnostics window.
+
+use std::path::Path;
+
+use fs_err as fs;
+use mint_lib::DRGInstallation;
+use serde::Serialize;
+
+use crate::integrate::{
+    backup_path, game_user_settings_path, modio_state_path, read_bundle_meta, UGC_SECTION,
+};
+use crate::is_drg_pak;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Ok => write!(f, "ok"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    /// Short name of the check that produced the finding.
+    pub check: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Suggested fix, only set if there is something to fix.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    fn ok(check: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(check: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn error(check: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Error,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check against the installation `pak_path` belongs to. Checks that depend on a valid
+/// installation are skipped if it cannot be found.
+pub fn diagnose(pak_path: Option<&Path>) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    let Some(pak_path) = pak_path else {
+        findings.push(Finding::error(
+            "game pak",
+            "DRG pak is not configured",
+            "set the path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for the Microsoft Store version) in the settings or pass --fsd-pak",
+        ));
+        return findings;
+    };
+
+    match is_drg_pak(pak_path) {
+        Ok(()) => findings.push(Finding::ok(
+            "game pak",
+            format!("{} is a DRG pak", pak_path.display()),
+        )),
+        Err(e) => findings.push(Finding::error(
+            "game pak",
+            format!("{} is not a valid DRG pak: {e}", pak_path.display()),
+            "select FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for the Microsoft Store version) in FSD/Content/Paks of the game directory, verify the game files if it already is",
+        )),
+    }
+
+    let installation = match DRGInstallation::from_pak_path(pak_path) {
+        Ok(installation) => {
+            findings.push(Finding::ok(
+                "installation",
+                format!(
+                    "{:?} installation at {}",
+                    installation.installation_type,
+                    installation.root.display()
+                ),
+            ));
+            installation
+        }
+        Err(e) => {
+            findings.push(Finding::error(
+                "installation",
+                format!("failed to detect installation type: {e}"),
+                "the game pak must be named FSD-WindowsNoEditor.pak (Steam) or FSD-WinGDK.pak (Microsoft Store) and be located in FSD/Content/Paks",
+            ));
+            return findings;
+        }
+    };
+
+    findings.push(check_hook(&installation));
+    findings.push(check_bundle(&installation));
+    findings.extend(check_modio(&installation));
+    findings.extend(check_manual_paks(&installation));
+
+    findings
+}
+
+fn check_hook(installation: &DRGInstallation) -> Finding {
+    let path = installation
+        .binaries_directory()
+        .join(installation.installation_type.hook_dll_name());
+    let installed = match fs::read(&path) {
+        Ok(installed) => installed,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Finding::warning(
+                "hook",
+                format!("hook is not installed at {}", path.display()),
+                "apply changes (`mint profile`) to install it",
+            );
+        }
+        Err(e) => {
+            return Finding::error(
+                "hook",
+                format!("failed to read {}: {e}", path.display()),
+                "check the permissions of the game directory",
+            );
+        }
+    };
+
+    #[cfg(feature = "hook")]
+    if installed != crate::integrate::HOOK_DLL {
+        return Finding::warning(
+            "hook",
+            format!(
+                "{} does not match the hook of this version of mint",
+                path.display()
+            ),
+            "apply changes (`mint profile`) to update it, if it belongs to another tool remove it first",
+        );
+    }
+    #[cfg(not(feature = "hook"))]
+    let _ = installed;
+
+    Finding::ok("hook", format!("hook is installed at {}", path.display()))
+}
+
+fn check_bundle(installation: &DRGInstallation) -> Finding {
+    let path = installation.paks_path().join("mods_P.pak");
+    if !path.exists() {
+        return Finding::warning(
+            "mod bundle",
+            format!("no mod bundle installed at {}", path.display()),
+            "apply changes (`mint profile`) to install one",
+        );
+    }
+    match read_bundle_meta(&path) {
+        Ok(meta) => {
+            let version = format!(
+                "{}.{}.{}",
+                meta.version.major, meta.version.minor, meta.version.patch
+            );
+            let current = env!("CARGO_PKG_VERSION")
+                .split('-')
+                .next()
+                .unwrap_or_default();
+            if version != current {
+                Finding::warning(
+                    "mod bundle",
+                    format!(
+                        "mod bundle with {} mods was installed by mint {version}, this is mint {current}",
+                        meta.mods.len()
+                    ),
+                    "apply changes (`mint profile`) to reinstall it with this version",
+                )
+            } else {
+                Finding::ok(
+                    "mod bundle",
+                    format!("mod bundle with {} mods is installed", meta.mods.len()),
+                )
+            }
+        }
+        Err(e) => {
+            let fix = if backup_path(&path).exists() {
+                "apply changes (`mint profile`) to rebuild it or restore the previous bundle with `mint rollback`"
+            } else {
+                "apply changes (`mint profile`) to rebuild it"
+            };
+            Finding::error(
+                "mod bundle",
+                format!("{} is not a valid mod bundle: {e}", path.display()),
+                fix,
+            )
+        }
+    }
+}
+
+/// Check the files the official integration is restored through, see
+/// [`crate::integrate::uninstall`].
+fn check_modio(installation: &DRGInstallation) -> Vec<Finding> {
+    let Some(modio_dir) = installation.modio_directory() else {
+        return vec![Finding::ok(
+            "mod.io",
+            "mod.io directory is not used for this installation type or platform",
+        )];
+    };
+
+    let mut findings = vec![];
+
+    let state_path = modio_state_path(&modio_dir);
+    match fs::read(&state_path) {
+        Ok(buf) => match serde_json::from_slice::<serde_json::Value>(&buf) {
+            Ok(_) => findings.push(Finding::ok(
+                "mod.io",
+                format!("found {}", state_path.display()),
+            )),
+            Err(e) => findings.push(Finding::warning(
+                "mod.io",
+                format!("failed to parse {}: {e}", state_path.display()),
+                "launch the game once with the official mod.io integration to regenerate it",
+            )),
+        },
+        Err(_) => findings.push(Finding::warning(
+            "mod.io",
+            format!("{} not found", state_path.display()),
+            "restoring the official mod.io integration on uninstall is not possible, ignore this if it has never been used",
+        )),
+    }
+
+    let config_path = game_user_settings_path(installation);
+    match ini::Ini::load_from_file(&config_path) {
+        Ok(config) => match config.section(Some(UGC_SECTION)) {
+            Some(section) => findings.push(Finding::ok(
+                "GameUserSettings.ini",
+                format!(
+                    "{UGC_SECTION} section of {} has {} entries",
+                    config_path.display(),
+                    section.iter().count()
+                ),
+            )),
+            None => findings.push(Finding::ok(
+                "GameUserSettings.ini",
+                format!(
+                    "{} has no {UGC_SECTION} section, it is created on uninstall",
+                    config_path.display()
+                ),
+            )),
+        },
+        Err(e) => findings.push(Finding::warning(
+            "GameUserSettings.ini",
+            format!("failed to load {}: {e}", config_path.display()),
+            "launch the game once to create it",
+        )),
+    }
+
+    findings
+}
+
+/// `*_P.pak` files other than the mod bundle override game files without going through mint.
+fn check_manual_paks(installation: &DRGInstallation) -> Vec<Finding> {
+    let paks_path = installation.paks_path();
+    let entries = match fs::read_dir(&paks_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return vec![Finding::error(
+                "manual paks",
+                format!("failed to read {}: {e}", paks_path.display()),
+                "check that the game pak is located in FSD/Content/Paks",
+            )];
+        }
+    };
+
+    let mut manual = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| {
+            let lower = name.to_ascii_lowercase();
+            lower.ends_with("_p.pak") && lower != "mods_p.pak"
+        })
+        .collect::<Vec<_>>();
+    manual.sort();
+
+    if manual.is_empty() {
+        return vec![Finding::ok(
+            "manual paks",
+            "no manually installed paks found",
+        )];
+    }
+    manual
+        .into_iter()
+        .map(|name| {
+            Finding::warning(
+                "manual paks",
+                format!("{name} was installed manually and is loaded alongside the mod bundle"),
+                format!(
+                    "remove {} and add it to a profile as a local file instead",
+                    paks_path.join(&name).display()
+                ),
+            )
+        })
+        .collect()
+}
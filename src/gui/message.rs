@@ -1,5 +1,4 @@
 use std::collections::BTreeSet;
-use std::ops::DerefMut;
 use std::time::SystemTime;
 use std::{collections::HashMap, sync::Arc};
 
@@ -13,17 +12,16 @@ use tracing::*;
 use super::SelfUpdateProgress;
 use super::{
     request_counter::{RequestCounter, RequestID},
-    App, SpecFetchProgress, WindowProviderParameters,
+    App, InstalledBundle, SpecFetchProgress, WindowLintReport, WindowModfileUpdates,
+    WindowProviderParameters,
 };
 use crate::gui::LastAction;
 use crate::integrate::*;
 use crate::mod_lints::{LintId, LintReport};
-use crate::state::{ModData_v0_1_0 as ModData, ModOrGroup};
+use crate::providers::modio::ModfileUpdate;
+use crate::providers::{FetchProgress, ModInfo, ModStore};
+use crate::subscriptions::{ProfileMod, SubscriptionDiff};
 use crate::*;
-use crate::{
-    providers::{FetchProgress, ModInfo, ModStore},
-    state::ModConfig,
-};
 use mint_lib::error::GenericError;
 use mint_lib::mod_info::MetaConfig;
 use mint_lib::update::GitHubRelease;
@@ -38,11 +36,17 @@ pub struct MessageHandle<S> {
 #[derive(Debug)]
 pub enum Message {
     ResolveMods(ResolveMods),
+    ResolveImportedProfile(ResolveImportedProfile),
     Integrate(Integrate),
+    PlanIntegration(PlanIntegration),
+    WatchIntegrate(WatchIntegrate),
     FetchModProgress(FetchModProgress),
     UpdateCache(UpdateCache),
     CheckUpdates(CheckUpdates),
     LintMods(LintMods),
+    ListArchivePaks(ListArchivePaks),
+    FetchSubscriptionDiff(FetchSubscriptionDiff),
+    PushSubscriptions(PushSubscriptions),
     SelfUpdate(SelfUpdate),
     FetchSelfUpdateProgress(FetchSelfUpdateProgress),
 }
@@ -51,11 +55,17 @@ impl Message {
     pub fn handle(self, app: &mut App) {
         match self {
             Self::ResolveMods(msg) => msg.receive(app),
+            Self::ResolveImportedProfile(msg) => msg.receive(app),
             Self::Integrate(msg) => msg.receive(app),
+            Self::PlanIntegration(msg) => msg.receive(app),
+            Self::WatchIntegrate(msg) => msg.receive(app),
             Self::FetchModProgress(msg) => msg.receive(app),
             Self::UpdateCache(msg) => msg.receive(app),
             Self::CheckUpdates(msg) => msg.receive(app),
             Self::LintMods(msg) => msg.receive(app),
+            Self::ListArchivePaks(msg) => msg.receive(app),
+            Self::FetchSubscriptionDiff(msg) => msg.receive(app),
+            Self::PushSubscriptions(msg) => msg.receive(app),
             Self::SelfUpdate(msg) => msg.receive(app),
             Self::FetchSelfUpdateProgress(msg) => msg.receive(app),
         }
@@ -105,56 +115,18 @@ impl ResolveMods {
         if Some(self.rid) == app.resolve_mod_rid.as_ref().map(|r| r.rid) {
             match self.result {
                 Ok(resolved_mods) => {
-                    let primary_mods = self
-                        .specs
-                        .into_iter()
-                        .collect::<HashSet<ModSpecification>>();
-                    for (resolved_spec, info) in resolved_mods {
-                        let is_dep = self.is_dependency || !primary_mods.contains(&resolved_spec);
-                        let add = if is_dep {
-                            // if mod is a dependency then check if there is a disabled
-                            // mod that satisfies the dependency and enable it. if it
-                            // is not a dependency then assume the user explicitly
-                            // wants to add a specific mod version.
-                            let active_profile = app.state.mod_data.active_profile.clone();
-                            !app.state.mod_data.any_mod_mut(
-                                &active_profile,
-                                |mc, mod_group_enabled| {
-                                    if mc.spec.satisfies_dependency(&resolved_spec) {
-                                        mc.enabled = true;
-                                        if let Some(mod_group_enabled) = mod_group_enabled {
-                                            *mod_group_enabled = true;
-                                        }
-                                        true
-                                    } else {
-                                        false
-                                    }
-                                },
-                            )
-                        } else {
-                            true
-                        };
-
-                        if add {
-                            let ModData {
-                                active_profile,
-                                profiles,
-                                ..
-                            } = app.state.mod_data.deref_mut().deref_mut();
-
-                            profiles.get_mut(active_profile).unwrap().mods.insert(
-                                0,
-                                ModOrGroup::Individual(ModConfig {
-                                    spec: info.spec.clone(),
-                                    required: info.suggested_require,
-                                    enabled: true,
-                                    priority: 0,
-                                }),
-                            );
-                        }
-                    }
+                    let active_profile = app.state.mod_data.active_profile.clone();
+                    app.state
+                        .mod_data
+                        .add_resolved_mods(
+                            &active_profile,
+                            &self.specs,
+                            resolved_mods,
+                            self.is_dependency,
+                        )
+                        .unwrap();
                     app.resolve_mod.clear();
-                    app.state.mod_data.save().unwrap();
+                    app.save_mod_data();
                     app.last_action = Some(LastAction::success(
                         "mods successfully resolved".to_string(),
                     ));
@@ -175,6 +147,71 @@ impl ResolveMods {
     }
 }
 
+/// Resolves the mods of a freshly imported profile so any missing providers get configured.
+#[derive(Debug)]
+pub struct ResolveImportedProfile {
+    rid: RequestID,
+    profile: String,
+    result: Result<HashMap<ModSpecification, ModInfo>, ProviderError>,
+}
+
+impl ResolveImportedProfile {
+    pub fn send(app: &mut App, ctx: &egui::Context, profile: String, specs: Vec<ModSpecification>) {
+        let rid = app.request_counter.next();
+        let store = app.state.store.clone();
+        let ctx = ctx.clone();
+        let tx = app.tx.clone();
+        let handle = tokio::spawn(async move {
+            let result = store.resolve_mods(&specs, false).await;
+            tx.send(Message::ResolveImportedProfile(Self {
+                rid,
+                profile,
+                result,
+            }))
+            .await
+            .unwrap();
+            ctx.request_repaint();
+        });
+        app.last_action = None;
+        app.resolve_mod_rid = Some(MessageHandle {
+            rid,
+            handle,
+            state: (),
+        });
+    }
+
+    fn receive(self, app: &mut App) {
+        if Some(self.rid) == app.resolve_mod_rid.as_ref().map(|r| r.rid) {
+            match self.result {
+                Ok(_) => {
+                    app.installed_bundle.update_diff(&app.state);
+                    app.last_action = Some(LastAction::success(format!(
+                        "imported profile \"{}\"",
+                        self.profile
+                    )));
+                }
+                Err(ProviderError::NoProvider { url: _, factory }) => {
+                    app.window_provider_parameters =
+                        Some(WindowProviderParameters::new(factory, &app.state));
+                    app.last_action = Some(LastAction::failure(format!(
+                        "imported profile \"{}\" but no provider",
+                        self.profile
+                    )));
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    app.problematic_mod_id = e.opt_mod_id();
+                    app.last_action = Some(LastAction::failure(format!(
+                        "imported profile \"{}\" but failed to resolve mods: {e}",
+                        self.profile
+                    )));
+                }
+            }
+            app.resolve_mod_rid = None;
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Integrate {
     rid: RequestID,
@@ -182,10 +219,12 @@ pub struct Integrate {
 }
 
 impl Integrate {
+    #[allow(clippy::too_many_arguments)]
     pub fn send(
         rc: &mut RequestCounter,
         store: Arc<ModStore>,
         mods: Vec<ModSpecification>,
+        disabled_paks: DisabledPaks,
         fsd_pak: PathBuf,
         config: MetaConfig,
         tx: Sender<Message>,
@@ -195,9 +234,17 @@ impl Integrate {
         MessageHandle {
             rid,
             handle: tokio::task::spawn(async move {
-                let res =
-                    integrate_async(store, ctx.clone(), mods, fsd_pak, config, rid, tx.clone())
-                        .await;
+                let res = integrate_async(
+                    store,
+                    ctx.clone(),
+                    mods,
+                    disabled_paks,
+                    fsd_pak,
+                    config,
+                    rid,
+                    tx.clone(),
+                )
+                .await;
                 tx.send(Message::Integrate(Integrate { rid, result: res }))
                     .await
                     .unwrap();
@@ -213,6 +260,182 @@ impl Integrate {
                 Ok(()) => {
                     info!("integration complete");
                     app.last_action = Some(LastAction::success("integration complete".to_string()));
+                    app.installed_bundle = InstalledBundle::read(&app.state);
+                }
+                Err(ref e)
+                    if let IntegrationError::ProviderError { ref source } = e
+                        && let ProviderError::NoProvider { url: _, factory } = source =>
+                {
+                    app.window_provider_parameters =
+                        Some(WindowProviderParameters::new(factory, &app.state));
+                    app.last_action = Some(LastAction::failure("no provider".to_string()));
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    app.problematic_mod_id = e.opt_mod_id();
+                    app.last_action = Some(LastAction::failure(e.to_string()));
+                }
+            }
+            app.integrate_rid = None;
+        }
+    }
+}
+
+/// Lint and integrate after local mods changed in watch mode.
+#[derive(Debug)]
+pub struct WatchIntegrate {
+    rid: RequestID,
+    result: Result<LintReport, IntegrationError>,
+}
+
+impl WatchIntegrate {
+    #[allow(clippy::too_many_arguments)]
+    pub fn send(
+        rc: &mut RequestCounter,
+        store: Arc<ModStore>,
+        mods: Vec<ModSpecification>,
+        disabled_paks: DisabledPaks,
+        fsd_pak: PathBuf,
+        config: MetaConfig,
+        enabled_lints: BTreeSet<LintId>,
+        backup: bool,
+        tx: Sender<Message>,
+        ctx: egui::Context,
+    ) -> MessageHandle<HashMap<ModSpecification, SpecFetchProgress>> {
+        let rid = rc.next();
+        MessageHandle {
+            rid,
+            handle: tokio::task::spawn(async move {
+                let res = async {
+                    let (mods, disabled_paks) = fetch_integrate_async(
+                        store,
+                        ctx.clone(),
+                        mods,
+                        &disabled_paks,
+                        rid,
+                        tx.clone(),
+                    )
+                    .await?;
+                    tokio::task::spawn_blocking(move || {
+                        let report = crate::mod_lints::run_lints(
+                            &enabled_lints,
+                            mods.iter()
+                                .map(|(info, path)| (info.spec.clone(), path.clone()))
+                                .collect(),
+                            disabled_paks.clone(),
+                            Some(fsd_pak.clone()),
+                        )?;
+                        if backup {
+                            crate::integrate::integrate(fsd_pak, config, mods, &disabled_paks)?;
+                        } else {
+                            crate::integrate::reintegrate(fsd_pak, config, mods, &disabled_paks)?;
+                        }
+                        Ok::<_, IntegrationError>(report)
+                    })
+                    .await?
+                }
+                .await;
+                tx.send(Message::WatchIntegrate(WatchIntegrate { rid, result: res }))
+                    .await
+                    .unwrap();
+                ctx.request_repaint();
+            }),
+            state: Default::default(),
+        }
+    }
+
+    fn receive(self, app: &mut App) {
+        if Some(self.rid) == app.integrate_rid.as_ref().map(|r| r.rid) {
+            match self.result {
+                Ok(report) => {
+                    let findings = report.findings().len();
+                    info!("watch integration complete with {findings} lint findings");
+                    app.last_action = Some(LastAction::success(format!(
+                        "local mods changed, integration complete ({findings} lint findings)"
+                    )));
+                    if findings > 0 {
+                        app.lint_report_window = Some(WindowLintReport);
+                    }
+                    app.lint_report = Some(report);
+                    app.installed_bundle = InstalledBundle::read(&app.state);
+                    app.watch_backed_up = true;
+                }
+                Err(ref e)
+                    if let IntegrationError::ProviderError { ref source } = e
+                        && let ProviderError::NoProvider { url: _, factory } = source =>
+                {
+                    app.window_provider_parameters =
+                        Some(WindowProviderParameters::new(factory, &app.state));
+                    app.last_action = Some(LastAction::failure("no provider".to_string()));
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    app.problematic_mod_id = e.opt_mod_id();
+                    app.last_action = Some(LastAction::failure(e.to_string()));
+                }
+            }
+            app.integrate_rid = None;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PlanIntegration {
+    rid: RequestID,
+    result: Result<IntegrationPlan, IntegrationError>,
+}
+
+impl PlanIntegration {
+    pub fn send(
+        rc: &mut RequestCounter,
+        store: Arc<ModStore>,
+        mods: Vec<ModSpecification>,
+        disabled_paks: DisabledPaks,
+        fsd_pak: PathBuf,
+        tx: Sender<Message>,
+        ctx: egui::Context,
+    ) -> MessageHandle<HashMap<ModSpecification, SpecFetchProgress>> {
+        let rid = rc.next();
+        MessageHandle {
+            rid,
+            handle: tokio::task::spawn(async move {
+                let res = async {
+                    let (mods, disabled_paks) = fetch_integrate_async(
+                        store,
+                        ctx.clone(),
+                        mods,
+                        &disabled_paks,
+                        rid,
+                        tx.clone(),
+                    )
+                    .await?;
+                    tokio::task::spawn_blocking(move || {
+                        crate::integrate::plan_integration(fsd_pak, &mods, &disabled_paks)
+                    })
+                    .await?
+                }
+                .await;
+                tx.send(Message::PlanIntegration(PlanIntegration {
+                    rid,
+                    result: res,
+                }))
+                .await
+                .unwrap();
+                ctx.request_repaint();
+            }),
+            state: Default::default(),
+        }
+    }
+
+    fn receive(self, app: &mut App) {
+        if Some(self.rid) == app.integrate_rid.as_ref().map(|r| r.rid) {
+            match self.result {
+                Ok(plan) => {
+                    info!("integration preview complete");
+                    app.integration_plan = Some(plan);
+                    app.last_action = Some(LastAction::success(
+                        "integration preview complete".to_string(),
+                    ));
                 }
                 Err(ref e)
                     if let IntegrationError::ProviderError { ref source } = e
@@ -253,7 +476,7 @@ impl FetchModProgress {
 #[derive(Debug)]
 pub struct UpdateCache {
     rid: RequestID,
-    result: Result<(), ProviderError>,
+    result: Result<Vec<ModfileUpdate>, ProviderError>,
 }
 
 impl UpdateCache {
@@ -261,8 +484,18 @@ impl UpdateCache {
         let rid = app.request_counter.next();
         let tx = app.tx.clone();
         let store = app.state.store.clone();
+        let mut specs = vec![];
+        app.state
+            .mod_data
+            .for_each_enabled_mod(&app.state.mod_data.active_profile, |mc| {
+                specs.push(mc.spec.clone());
+            });
+        let modfiles = store.modio_modfiles(&specs);
         let handle = tokio::spawn(async move {
-            let res = store.update_cache().await;
+            let res = store
+                .update_cache()
+                .await
+                .map(|()| store.modio_updates_since(&specs, &modfiles));
             tx.send(Message::UpdateCache(UpdateCache { rid, result: res }))
                 .await
                 .unwrap();
@@ -278,11 +511,15 @@ impl UpdateCache {
     fn receive(self, app: &mut App) {
         if Some(self.rid) == app.update_rid.as_ref().map(|r| r.rid) {
             match self.result {
-                Ok(()) => {
+                Ok(updates) => {
                     info!("cache update complete");
+                    app.installed_bundle.update_diff(&app.state);
                     app.last_action = Some(LastAction::success(
                         "successfully updated cache".to_string(),
                     ));
+                    if !updates.is_empty() {
+                        app.modfile_updates_window = Some(WindowModfileUpdates { updates });
+                    }
                 }
                 Err(ProviderError::NoProvider { url: _, factory }) => {
                     app.window_provider_parameters =
@@ -352,18 +589,42 @@ impl CheckUpdates {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn integrate_async(
     store: Arc<ModStore>,
     ctx: egui::Context,
     mod_specs: Vec<ModSpecification>,
+    disabled_paks: DisabledPaks,
     fsd_pak: PathBuf,
     config: MetaConfig,
     rid: RequestID,
     message_tx: Sender<Message>,
 ) -> Result<(), IntegrationError> {
+    let (mods, disabled_paks) =
+        fetch_integrate_async(store, ctx, mod_specs, &disabled_paks, rid, message_tx).await?;
+
+    tokio::task::spawn_blocking(move || {
+        crate::integrate::integrate(fsd_pak, config, mods, &disabled_paks)
+    })
+    .await??;
+
+    Ok(())
+}
+
+/// Resolve and fetch mods to integrate, reporting fetch progress. Returns them along with their
+/// disabled paks re-keyed for [`integrate`].
+async fn fetch_integrate_async(
+    store: Arc<ModStore>,
+    ctx: egui::Context,
+    mod_specs: Vec<ModSpecification>,
+    disabled_paks: &DisabledPaks,
+    rid: RequestID,
+    message_tx: Sender<Message>,
+) -> Result<(Vec<(ModInfo, PathBuf)>, DisabledPaks), IntegrationError> {
     let update = false;
 
     let mods = store.resolve_mods(&mod_specs, update).await?;
+    let disabled_paks = resolve_disabled_paks(disabled_paks, &mods);
 
     let to_integrate = mod_specs
         .iter()
@@ -398,16 +659,144 @@ async fn integrate_async(
 
     let paths = store.fetch_mods_ordered(&urls, update, Some(tx)).await?;
 
-    tokio::task::spawn_blocking(|| {
-        crate::integrate::integrate(
-            fsd_pak,
-            config,
-            to_integrate.into_iter().zip(paths).collect(),
-        )
-    })
-    .await??;
+    Ok((to_integrate.into_iter().zip(paths).collect(), disabled_paks))
+}
 
-    Ok(())
+#[derive(Debug)]
+pub struct ListArchivePaks {
+    rid: RequestID,
+    result: Result<Vec<String>, IntegrationError>,
+}
+
+impl ListArchivePaks {
+    pub fn send(app: &mut App, ctx: &egui::Context, spec: ModSpecification) -> MessageHandle<()> {
+        let rid = app.request_counter.next();
+        let store = app.state.store.clone();
+        let ctx = ctx.clone();
+        let tx = app.tx.clone();
+        let handle = tokio::spawn(async move {
+            let result = async {
+                let (_, info) = store.resolve_mod(spec, false).await?;
+                let path = store.fetch_mod(&info.resolution, false, None).await?;
+                tokio::task::spawn_blocking(move || list_archive_paks(&path)).await?
+            }
+            .await;
+            tx.send(Message::ListArchivePaks(Self { rid, result }))
+                .await
+                .unwrap();
+            ctx.request_repaint();
+        });
+        MessageHandle {
+            rid,
+            handle,
+            state: (),
+        }
+    }
+
+    fn receive(self, app: &mut App) {
+        if let Some(window) = &mut app.archive_paks_window
+            && Some(self.rid) == window.rid.as_ref().map(|r| r.rid)
+        {
+            window.paks = Some(self.result.map_err(|e| e.to_string()));
+            window.rid = None;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FetchSubscriptionDiff {
+    rid: RequestID,
+    result: Result<SubscriptionDiff, ProviderError>,
+}
+
+impl FetchSubscriptionDiff {
+    pub fn send(app: &mut App, ctx: &egui::Context, mods: Vec<ProfileMod>) -> MessageHandle<()> {
+        let rid = app.request_counter.next();
+        let store = app.state.store.clone();
+        let ctx = ctx.clone();
+        let tx = app.tx.clone();
+        let handle = tokio::spawn(async move {
+            let result = subscriptions::fetch_diff(&store, &mods).await;
+            tx.send(Message::FetchSubscriptionDiff(Self { rid, result }))
+                .await
+                .unwrap();
+            ctx.request_repaint();
+        });
+        MessageHandle {
+            rid,
+            handle,
+            state: (),
+        }
+    }
+
+    fn receive(self, app: &mut App) {
+        if let Some(window) = &mut app.subscriptions_window
+            && Some(self.rid) == window.rid.as_ref().map(|r| r.rid)
+        {
+            window.rid = None;
+            window.diff = Some(match self.result {
+                Ok(diff) => Ok(diff),
+                Err(ProviderError::NoProvider { url: _, factory }) => {
+                    app.window_provider_parameters =
+                        Some(WindowProviderParameters::new(factory, &app.state));
+                    Err("no provider".to_string())
+                }
+                Err(e) => Err(e.to_string()),
+            });
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PushSubscriptions {
+    rid: RequestID,
+    result: Result<(), ProviderError>,
+}
+
+impl PushSubscriptions {
+    pub fn send(app: &mut App, ctx: &egui::Context, diff: SubscriptionDiff) -> MessageHandle<()> {
+        let rid = app.request_counter.next();
+        let store = app.state.store.clone();
+        let ctx = ctx.clone();
+        let tx = app.tx.clone();
+        let handle = tokio::spawn(async move {
+            let result = subscriptions::push(&store, &diff).await;
+            tx.send(Message::PushSubscriptions(Self { rid, result }))
+                .await
+                .unwrap();
+            ctx.request_repaint();
+        });
+        app.last_action = None;
+        MessageHandle {
+            rid,
+            handle,
+            state: (),
+        }
+    }
+
+    fn receive(self, app: &mut App) {
+        if let Some(window) = &app.subscriptions_window
+            && Some(self.rid) == window.rid.as_ref().map(|r| r.rid)
+        {
+            match self.result {
+                Ok(()) => {
+                    app.last_action = Some(LastAction::success(
+                        "mod.io subscriptions updated".to_string(),
+                    ));
+                    app.subscriptions_window = None;
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    app.last_action = Some(LastAction::failure(e.to_string()));
+                    // the subscriptions may have changed partially, so they are fetched again
+                    if let Some(window) = &mut app.subscriptions_window {
+                        window.rid = None;
+                        window.diff = None;
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -417,10 +806,12 @@ pub struct LintMods {
 }
 
 impl LintMods {
+    #[allow(clippy::too_many_arguments)]
     pub fn send(
         rc: &mut RequestCounter,
         store: Arc<ModStore>,
         mods: Vec<ModSpecification>,
+        disabled_paks: DisabledPaks,
         enabled_lints: BTreeSet<LintId>,
         game_pak_path: Option<PathBuf>,
         tx: Sender<Message>,
@@ -439,6 +830,7 @@ impl LintMods {
                     crate::mod_lints::run_lints(
                         &enabled_lints,
                         pairs.into_iter().collect(),
+                        disabled_paks,
                         game_pak_path,
                     )
                 })
@@ -7,7 +7,7 @@ mod toggle_switch;
 //#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeSet;
 use std::ops::{Deref, RangeInclusive};
 use std::time::{Duration, Instant, SystemTime};
 use std::{
@@ -27,7 +27,7 @@ use egui::UiBuilder;
 use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
 use itertools::Itertools as _;
 use mint_lib::error::ResultExt as _;
-use mint_lib::mod_info::{ModioTags, RequiredStatus};
+use mint_lib::mod_info::{Meta, ModioTags, RequiredStatus};
 use mint_lib::update::GitHubRelease;
 use strum::{EnumIter, IntoEnumIterator};
 use tokio::{
@@ -36,13 +36,19 @@ use tokio::{
 };
 use tracing::{debug, trace};
 
+use crate::doctor::{diagnose, Finding, Severity};
 use crate::gui::find_string::searchable_text;
 use crate::mod_lints::{LintId, LintReport, SplitAssetPair};
+use crate::providers::modio::ModfileUpdate;
 use crate::providers::ProviderError;
+use crate::state::profile_export::ProfileExport_v0_0_0 as ProfileExport;
 use crate::state::SortingConfig;
+use crate::status::{compare, installed_meta, BundleDiff};
+use crate::subscriptions::{profile_modio_mods, SubscriptionDiff, SyncDirection, SyncMod};
+use crate::watch::{local_mod_paths, LocalModWatcher, POLL_INTERVAL};
 use crate::Dirs;
 use crate::{
-    integrate::uninstall,
+    integrate::{uninstall, DisabledPaks, IntegrationPlan},
     is_drg_pak,
     providers::{
         ApprovalStatus, FetchProgress, ModInfo, ModSpecification, ModStore, ProviderFactory,
@@ -149,6 +155,16 @@ pub struct App {
     lint_report: Option<LintReport>,
     lints_toggle_window: Option<WindowLintsToggle>,
     lint_options: LintOptions,
+    integration_plan_window: Option<WindowIntegrationPlan>,
+    integration_plan: Option<IntegrationPlan>,
+    doctor_window: Option<WindowDoctor>,
+    archive_paks_window: Option<WindowArchivePaks>,
+    subscriptions_window: Option<WindowSubscriptions>,
+    modfile_updates_window: Option<WindowModfileUpdates>,
+    installed_bundle: InstalledBundle,
+    local_mod_watcher: Option<LocalModWatcher>,
+    /// Whether the watch session replaced the bundle backup already, later integrations keep it.
+    watch_backed_up: bool,
     cache: CommonMarkCache,
     needs_restart: bool,
     self_update_rid: Option<MessageHandle<SelfUpdateProgress>>,
@@ -173,6 +189,32 @@ struct LintOptions {
     unmodified_game_assets: bool,
 }
 
+impl LintOptions {
+    fn enabled(&self) -> BTreeSet<LintId> {
+        [
+            (
+                LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
+                self.archive_with_multiple_paks,
+            ),
+            (
+                LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
+                self.archive_with_only_non_pak_files,
+            ),
+            (LintId::ASSET_REGISTRY_BIN, self.asset_register_bin),
+            (LintId::CONFLICTING, self.conflicting),
+            (LintId::EMPTY_ARCHIVE, self.empty_archive),
+            (LintId::OUTDATED_PAK_VERSION, self.outdated_pak_version),
+            (LintId::SHADER_FILES, self.shader_files),
+            (LintId::NON_ASSET_FILES, self.non_asset_files),
+            (LintId::SPLIT_ASSET_PAIRS, self.split_asset_pairs),
+            (LintId::UNMODIFIED_GAME_ASSETS, self.unmodified_game_assets),
+        ]
+        .into_iter()
+        .filter_map(|(lint, enabled)| enabled.then_some(lint))
+        .collect()
+    }
+}
+
 struct LastAction {
     timestamp: Instant,
     status: LastActionStatus,
@@ -208,6 +250,11 @@ enum LastActionStatus {
     Failure(String),
 }
 
+enum ProfileFileAction {
+    Export(String),
+    Import,
+}
+
 impl App {
     fn new(
         cc: &eframe::CreationContext,
@@ -216,6 +263,7 @@ impl App {
     ) -> Result<Self, MintError> {
         let (tx, rx) = mpsc::channel(10);
         let state = State::init(dirs)?;
+        let installed_bundle = InstalledBundle::read(&state);
 
         Ok(Self {
             default_visuals: cc
@@ -249,6 +297,15 @@ impl App {
             lint_report: None,
             lints_toggle_window: None,
             lint_options: LintOptions::default(),
+            integration_plan_window: None,
+            integration_plan: None,
+            doctor_window: None,
+            archive_paks_window: None,
+            subscriptions_window: None,
+            modfile_updates_window: None,
+            installed_bundle,
+            local_mod_watcher: None,
+            watch_backed_up: false,
             cache: Default::default(),
             needs_restart: false,
             self_update_rid: None,
@@ -260,6 +317,44 @@ impl App {
         })
     }
 
+    fn ui_installed_bundle(&self, ui: &mut Ui) {
+        match &self.installed_bundle {
+            InstalledBundle::Unknown => {}
+            InstalledBundle::NotInstalled => {
+                ui.label("Not installed").on_hover_text(
+                    "No mod bundle is installed, click \"Apply changes\" to install one",
+                );
+            }
+            InstalledBundle::Installed { meta, diff } => {
+                let mut hover = format!(
+                    "Installed by mint {} with {} mods:",
+                    meta.version,
+                    meta.mods.len()
+                );
+                for m in &meta.mods {
+                    hover.push_str(&format!("\n[{:?}] {}", m.approval, m.name));
+                }
+                if diff.is_up_to_date() {
+                    ui.label(RichText::new("✔ Installed").color(Color32::LIGHT_GREEN))
+                        .on_hover_text(hover);
+                } else {
+                    hover = format!(
+                        "Click \"Apply changes\" to install the active profile:\n{}\n\n{hover}",
+                        diff.describe().join("\n")
+                    );
+                    ui.label(
+                        RichText::new("⚠ Changes not applied").color(ui.visuals().warn_fg_color),
+                    )
+                    .on_hover_text(hover);
+                }
+            }
+            InstalledBundle::Invalid(e) => {
+                ui.label(RichText::new("⚠ Invalid mod bundle").color(ui.visuals().error_fg_color))
+                    .on_hover_text(format!("{e}\nOpen \"Diagnostics\" for details"));
+            }
+        }
+    }
+
     fn ui_profile(&mut self, ui: &mut Ui, profile: &str) {
         let sorting_config = self.get_sorting_config();
 
@@ -272,12 +367,14 @@ impl App {
             scroll_to_match: bool,
             btn_remove: Option<usize>,
             add_deps: Option<Vec<ModSpecification>>,
+            archive_paks: Option<(ModSpecification, String)>,
         }
         let mut ctx = Ctx {
             needs_save: false,
             scroll_to_match: self.scroll_to_match,
             btn_remove: None,
             add_deps: None,
+            archive_paks: None,
         };
 
         let ui_profile = |ui: &mut Ui, profile: &mut ModProfile| {
@@ -640,6 +737,12 @@ impl App {
                         "file" => {
                             ui.label("📁");
                         }
+                        "dir" => {
+                            ui.label("🗀");
+                        }
+                        "github" => {
+                            ui.label("🐙");
+                        }
                         _ => unimplemented!("unimplemented provider kind"),
                     }
 
@@ -655,6 +758,23 @@ impl App {
                         res.scroll_to_me(None);
                         ctx.scroll_to_match = false;
                     }
+                    res.context_menu(|ui| {
+                        if ui.button("Choose paks…").clicked() {
+                            ctx.archive_paks = Some((mc.spec.clone(), info.name.clone()));
+                            ui.close_menu();
+                        }
+                    });
+
+                    if !mc.disabled_paks.is_empty() {
+                        let mut msg = "Disabled paks:".to_string();
+                        for pak in &mc.disabled_paks {
+                            msg.push('\n');
+                            msg.push_str(pak);
+                        }
+                        if ui.button("📦").on_hover_text(msg).clicked() {
+                            ctx.archive_paks = Some((mc.spec.clone(), info.name.clone()));
+                        }
+                    }
 
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                         ui_mod_tags(ctx, ui, info);
@@ -802,10 +922,21 @@ impl App {
             self.problematic_mod_id = None;
         }
 
+        if let Some((spec, name)) = ctx.archive_paks {
+            let rid = message::ListArchivePaks::send(self, ui.ctx(), spec.clone());
+            self.archive_paks_window = Some(WindowArchivePaks {
+                profile: profile.to_string(),
+                spec,
+                name,
+                rid: Some(rid),
+                paks: None,
+            });
+        }
+
         self.scroll_to_match = ctx.scroll_to_match;
 
         if ctx.needs_save {
-            self.state.mod_data.save().unwrap();
+            self.save_mod_data();
         }
     }
 
@@ -818,6 +949,51 @@ impl App {
             .collect()
     }
 
+    fn export_profile(&mut self, profile: String) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("mint profile", &["json"])
+            .set_file_name(format!("{profile}.json"))
+            .save_file()
+        else {
+            return;
+        };
+        let result = self
+            .state
+            .mod_data
+            .export_profile(&profile)
+            .and_then(|export| export.write(&path));
+        self.last_action = Some(match result {
+            Ok(()) => LastAction::success(format!("exported profile to {}", path.display())),
+            Err(e) => LastAction::failure(format!("failed to export profile: {e}")),
+        });
+    }
+
+    fn import_profile(&mut self, ctx: &egui::Context) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("mint profile", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        let result = ProfileExport::read(&path).and_then(|export| {
+            let specs = export.specs();
+            let name = self.state.mod_data.import_profile(export, None, false)?;
+            Ok((name, specs))
+        });
+        match result {
+            Ok((name, specs)) => {
+                self.state.mod_data.active_profile = name.clone();
+                self.save_mod_data();
+                message::ResolveImportedProfile::send(self, ctx, name, specs);
+            }
+            Err(e) => {
+                self.last_action = Some(LastAction::failure(format!(
+                    "failed to import profile: {e}"
+                )));
+            }
+        }
+    }
+
     fn build_mod_string(mods: &Vec<ModConfig>) -> String {
         let mut string = String::new();
         for m in mods {
@@ -960,7 +1136,7 @@ impl App {
                                 egui::TextEdit::singleline(
                                     window.parameters.entry(p.id.to_string()).or_default(),
                                 )
-                                .password(true)
+                                .password(p.secret)
                                 .desired_width(200.0),
                             );
                             if is_committed(&res) {
@@ -1135,6 +1311,7 @@ impl App {
                         self.settings_window.take().unwrap().drg_pak_path,
                     ));
                     self.state.config.save().unwrap();
+                    self.installed_bundle = InstalledBundle::read(&self.state);
                 }
             } else if !open {
                 self.settings_window = None;
@@ -1222,38 +1399,8 @@ impl App {
                             )
                             .clicked()
                         {
-                            let lint_options = BTreeMap::from([
-                                (
-                                    LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
-                                    self.lint_options.archive_with_multiple_paks,
-                                ),
-                                (
-                                    LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
-                                    self.lint_options.archive_with_only_non_pak_files,
-                                ),
-                                (
-                                    LintId::ASSET_REGISTRY_BIN,
-                                    self.lint_options.asset_register_bin,
-                                ),
-                                (LintId::CONFLICTING, self.lint_options.conflicting),
-                                (LintId::EMPTY_ARCHIVE, self.lint_options.empty_archive),
-                                (
-                                    LintId::OUTDATED_PAK_VERSION,
-                                    self.lint_options.outdated_pak_version,
-                                ),
-                                (LintId::SHADER_FILES, self.lint_options.shader_files),
-                                (LintId::NON_ASSET_FILES, self.lint_options.non_asset_files),
-                                (
-                                    LintId::SPLIT_ASSET_PAIRS,
-                                    self.lint_options.split_asset_pairs,
-                                ),
-                                (
-                                    LintId::UNMODIFIED_GAME_ASSETS,
-                                    self.lint_options.unmodified_game_assets,
-                                ),
-                            ]);
-
-                            trace!(?lint_options);
+                            let enabled_lints = self.lint_options.enabled();
+                            trace!(?enabled_lints);
 
                             let mut mods = Vec::new();
                             self.state.mod_data.for_each_enabled_mod(
@@ -1268,11 +1415,8 @@ impl App {
                                 &mut self.request_counter,
                                 self.state.store.clone(),
                                 mods,
-                                BTreeSet::from_iter(
-                                    lint_options
-                                        .into_iter()
-                                        .filter_map(|(lint, enabled)| enabled.then_some(lint)),
-                                ),
+                                self.active_disabled_paks(),
+                                enabled_lints,
                                 self.state.config.drg_pak_path.clone(),
                                 self.tx.clone(),
                                 ctx.clone(),
@@ -1467,18 +1611,18 @@ impl App {
                                     if !archive_with_multiple_paks_mods.is_empty() {
                                         CollapsingHeader::new(
                                             RichText::new(
-                                                "⚠ Mod(s) with multiple `.pak`s detected",
+                                                "ℹ Mod(s) with multiple `.pak`s detected",
                                             )
-                                            .color(AMBER),
+                                            .color(Color32::LIGHT_BLUE),
                                         )
                                         .default_open(true)
                                         .show(ui, |ui| {
                                             archive_with_multiple_paks_mods.iter().for_each(|r#mod| {
                                                 ui.label(RichText::new(format!(
-                                                    "⚠ {} contains multiple `.pak`s, only the first encountered `.pak` will be loaded",
+                                                    "ℹ {} contains multiple `.pak`s, all of them are loaded unless disabled with the 📦 button of the mod",
                                                     r#mod.url
                                                 ))
-                                                .color(AMBER));
+                                                .color(Color32::LIGHT_BLUE));
                                             });
                                         });
                                     }
@@ -1588,6 +1732,366 @@ impl App {
         }
     }
 
+    fn show_integration_plan(&mut self, ctx: &egui::Context) {
+        if self.integration_plan_window.is_some() {
+            let mut open = true;
+
+            egui::Window::new("Integration preview")
+                .open(&mut open)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    if let Some(plan) = &self.integration_plan {
+                        let scroll_height =
+                            (ui.available_height() - 30.0).clamp(0.0, f32::INFINITY);
+                        egui::ScrollArea::vertical()
+                            .max_height(scroll_height)
+                            .show(ui, |ui| {
+                                let overridden = plan
+                                    .files
+                                    .iter()
+                                    .filter(|(_, file)| !file.overridden.is_empty())
+                                    .collect::<Vec<_>>();
+                                CollapsingHeader::new(format!(
+                                    "Files provided by more than one mod ({})",
+                                    overridden.len()
+                                ))
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    for (path, file) in overridden {
+                                        CollapsingHeader::new(format!("{path} ← {}", file.source))
+                                            .show(ui, |ui| {
+                                                for m in &file.overridden {
+                                                    ui.label(format!(
+                                                        "overrides {} <{}>",
+                                                        m.name, m.url
+                                                    ));
+                                                }
+                                            });
+                                    }
+                                });
+
+                                CollapsingHeader::new(format!(
+                                    "Patched game assets ({})",
+                                    plan.patched.len()
+                                ))
+                                .show(ui, |ui| {
+                                    for patched in &plan.patched {
+                                        ui.label(format!(
+                                            "{} ({}) ← {}",
+                                            patched.path, patched.patch, patched.source
+                                        ));
+                                        for m in &patched.overridden {
+                                            ui.label(format!(
+                                                "    overrides {} <{}>",
+                                                m.name, m.url
+                                            ));
+                                        }
+                                    }
+                                });
+
+                                CollapsingHeader::new(format!(
+                                    "AssetRegistry entries ({})",
+                                    plan.asset_registry.len()
+                                ))
+                                .show(ui, |ui| {
+                                    for entry in &plan.asset_registry {
+                                        ui.label(format!("{} ← {}", entry.path, entry.source.name));
+                                    }
+                                });
+
+                                CollapsingHeader::new(format!("All files ({})", plan.files.len()))
+                                    .show(ui, |ui| {
+                                        for (path, file) in &plan.files {
+                                            ui.label(format!("{path} ← {}", file.source));
+                                        }
+                                    });
+                            });
+                    } else {
+                        ui.spinner();
+                        ui.label("Integration preview generating...");
+                    }
+                });
+
+            if !open {
+                self.integration_plan_window = None;
+                self.integration_plan = None;
+            }
+        }
+    }
+
+    fn show_doctor(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.doctor_window else {
+            return;
+        };
+
+        let mut open = true;
+        let mut rerun = false;
+        egui::Window::new("Diagnostics")
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height((ui.available_height() - 30.0).clamp(0.0, f32::INFINITY))
+                    .show(ui, |ui| {
+                        for finding in &window.findings {
+                            let (icon, color) = match finding.severity {
+                                Severity::Ok => ("✔", ui.visuals().text_color()),
+                                Severity::Warning => ("⚠", ui.visuals().warn_fg_color),
+                                Severity::Error => ("❌", ui.visuals().error_fg_color),
+                            };
+                            ui.label(
+                                RichText::new(format!(
+                                    "{icon} {}: {}",
+                                    finding.check, finding.message
+                                ))
+                                .color(color),
+                            );
+                            if let Some(fix) = &finding.fix {
+                                ui.label(format!("    {fix}"));
+                            }
+                        }
+                    });
+                if ui.button("Run again").clicked() {
+                    rerun = true;
+                }
+            });
+
+        if rerun {
+            *window = WindowDoctor::new(&self.state);
+        }
+        if !open {
+            self.doctor_window = None;
+        }
+    }
+
+    fn show_archive_paks(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.archive_paks_window else {
+            return;
+        };
+
+        let mut open = true;
+        let mut needs_save = false;
+        egui::Window::new(format!("Paks of {}", window.name))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| match &window.paks {
+                None => {
+                    ui.spinner();
+                }
+                Some(Err(e)) => {
+                    ui.label(RichText::new(e).color(ui.visuals().error_fg_color));
+                }
+                Some(Ok(paks)) if paks.is_empty() => {
+                    ui.label("This mod is a single pak.");
+                }
+                Some(Ok(paks)) => {
+                    let Ok(mc) = self
+                        .state
+                        .mod_data
+                        .get_mod_mut(&window.profile, &window.spec.url)
+                    else {
+                        ui.label("Mod is no longer in the profile.");
+                        return;
+                    };
+                    ui.label(
+                        "Paks are integrated in this order, later paks override earlier ones.",
+                    );
+                    for pak in paks {
+                        let mut enabled = !mc.disabled_paks.contains(pak);
+                        if ui.checkbox(&mut enabled, pak).changed() {
+                            if enabled {
+                                mc.disabled_paks.remove(pak);
+                            } else {
+                                mc.disabled_paks.insert(pak.clone());
+                            }
+                            needs_save = true;
+                        }
+                    }
+                }
+            });
+
+        if needs_save {
+            self.save_mod_data();
+        }
+        if !open {
+            self.archive_paks_window = None;
+        }
+    }
+
+    fn show_subscriptions(&mut self, ctx: &egui::Context) {
+        let active_profile = self.state.mod_data.active_profile.clone();
+        let Some(window) = &mut self.subscriptions_window else {
+            return;
+        };
+        if window.profile != active_profile {
+            window.profile = active_profile;
+            window.rid = None;
+            window.diff = None;
+        }
+        if window.rid.is_none() && window.diff.is_none() {
+            let mods = profile_modio_mods(&self.state.mod_data, &window.profile);
+            let rid = message::FetchSubscriptionDiff::send(self, ctx, mods);
+            self.subscriptions_window.as_mut().unwrap().rid = Some(rid);
+        }
+        let window = self.subscriptions_window.as_mut().unwrap();
+
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new("mod.io subscriptions")
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                let diff = match &window.diff {
+                    Some(Ok(diff)) if window.rid.is_none() => diff,
+                    Some(Err(e)) if window.rid.is_none() => {
+                        ui.label(RichText::new(e).color(ui.visuals().error_fg_color));
+                        if ui.button("Retry").clicked() {
+                            window.diff = None;
+                        }
+                        return;
+                    }
+                    _ => {
+                        ui.spinner();
+                        return;
+                    }
+                };
+
+                let list = |ui: &mut Ui, mods: &[SyncMod]| {
+                    if mods.is_empty() {
+                        ui.label("none");
+                    }
+                    for m in mods {
+                        ui.hyperlink_to(m.name.as_str(), &m.spec.url);
+                    }
+                };
+                egui::ScrollArea::vertical()
+                    .max_height((ui.available_height() - 30.0).clamp(0.0, f32::INFINITY))
+                    .show(ui, |ui| {
+                        ui.strong(format!(
+                            "Subscribed but not enabled in \"{}\"",
+                            window.profile
+                        ));
+                        list(ui, &diff.only_subscribed);
+                        ui.separator();
+                        ui.strong(format!(
+                            "Enabled in \"{}\" but not subscribed",
+                            window.profile
+                        ));
+                        list(ui, &diff.only_in_profile);
+                    });
+
+                ui.horizontal(|ui| {
+                    for (direction, label) in [
+                        (SyncDirection::Pull, "Add subscriptions to profile"),
+                        (SyncDirection::Push, "Update subscriptions"),
+                    ] {
+                        let changes = diff.describe(direction, &window.profile);
+                        if ui
+                            .add_enabled(!diff.is_empty(direction), egui::Button::new(label))
+                            .on_hover_text(changes.join("\n"))
+                            .clicked()
+                        {
+                            action = Some((direction, diff.clone()));
+                        }
+                    }
+                    if ui.button("Refresh").clicked() {
+                        window.diff = None;
+                    }
+                });
+            });
+
+        match action {
+            Some((SyncDirection::Pull, diff)) => {
+                let profile = self.state.mod_data.active_profile.clone();
+                self.state
+                    .mod_data
+                    .enable_mods(&profile, &diff.specs_to_enable())
+                    .unwrap();
+                self.save_mod_data();
+                let specs = diff.specs_to_add();
+                if !specs.is_empty() {
+                    message::ResolveMods::send(self, ctx, specs, false);
+                }
+                self.subscriptions_window = None;
+            }
+            Some((SyncDirection::Push, diff)) => {
+                let rid = message::PushSubscriptions::send(self, ctx, diff);
+                self.subscriptions_window.as_mut().unwrap().rid = Some(rid);
+            }
+            None => {}
+        }
+        if !open {
+            self.subscriptions_window = None;
+        }
+    }
+
+    fn show_modfile_updates(&mut self, ctx: &egui::Context) {
+        let Some(window) = &self.modfile_updates_window else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Updated mods")
+            .open(&mut open)
+            .resizable(true)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                for update in &window.updates {
+                    CollapsingHeader::new(format!(
+                        "{}: {} → {}",
+                        update.name, update.old_version, update.new_version
+                    ))
+                    .id_salt(update.modio_id)
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.hyperlink_to("mod.io", &update.spec.url);
+                        for entry in &update.changelogs {
+                            ui.strong(&entry.version);
+                            match &entry.changelog {
+                                Some(changelog) => {
+                                    ui.label(changelog);
+                                }
+                                None => {
+                                    ui.weak("No changelog");
+                                }
+                            }
+                        }
+                    });
+                }
+            });
+
+        if !open {
+            self.modfile_updates_window = None;
+        }
+    }
+
+    fn active_disabled_paks(&self) -> DisabledPaks {
+        self.state
+            .mod_data
+            .disabled_paks(&self.state.mod_data.active_profile)
+    }
+
+    /// Enabled mods of the active profile in the order they are integrated.
+    fn enabled_mods_by_priority(&self) -> Vec<ModSpecification> {
+        let mut mod_configs = Vec::new();
+        let active_profile = self.state.mod_data.active_profile.clone();
+        self.state
+            .mod_data
+            .for_each_enabled_mod(&active_profile, |mc| {
+                mod_configs.push(mc.clone());
+            });
+
+        mod_configs.sort_by_key(|k| -k.priority);
+
+        mod_configs.into_iter().map(|config| config.spec).collect()
+    }
+
+    /// Save the mod data and compare the installed bundle with the changed profile.
+    fn save_mod_data(&mut self) {
+        self.state.mod_data.save().unwrap();
+        self.installed_bundle.update_diff(&self.state);
+    }
+
     fn get_sorting_config(&self) -> Option<SortingConfig> {
         self.state.config.sorting_config.clone()
     }
@@ -1710,6 +2214,80 @@ struct WindowLintReport;
 
 struct WindowLintsToggle;
 
+struct WindowIntegrationPlan;
+
+struct WindowDoctor {
+    findings: Vec<Finding>,
+}
+
+/// Paks inside the archive of a mod, to choose which of them are integrated.
+struct WindowArchivePaks {
+    profile: String,
+    spec: ModSpecification,
+    name: String,
+    rid: Option<MessageHandle<()>>,
+    /// Paths of the paks once listed, empty if the mod is a single pak.
+    paks: Option<Result<Vec<String>, String>>,
+}
+
+/// Mods that got a newer modfile when checking for updates, with the changelogs in between.
+struct WindowModfileUpdates {
+    updates: Vec<ModfileUpdate>,
+}
+
+/// Differences between the mod.io subscriptions and the active profile.
+struct WindowSubscriptions {
+    profile: String,
+    rid: Option<MessageHandle<()>>,
+    diff: Option<Result<SubscriptionDiff, String>>,
+}
+
+impl WindowDoctor {
+    fn new(state: &State) -> Self {
+        Self {
+            findings: diagnose(state.config.drg_pak_path.as_deref()),
+        }
+    }
+}
+
+/// Mod bundle installed in the game directory, compared with the active profile to tell whether
+/// changes still need to be applied.
+enum InstalledBundle {
+    /// DRG pak is not configured.
+    Unknown,
+    NotInstalled,
+    Installed {
+        meta: Meta,
+        /// Kept up to date by [`InstalledBundle::update_diff`] as comparing resolves every mod.
+        diff: BundleDiff,
+    },
+    Invalid(String),
+}
+
+impl InstalledBundle {
+    fn read(state: &State) -> Self {
+        let Some(pak_path) = &state.config.drg_pak_path else {
+            return Self::Unknown;
+        };
+        match installed_meta(pak_path) {
+            Ok(Some(meta)) => {
+                let diff = compare(state, &state.mod_data.active_profile, &meta);
+                Self::Installed { meta, diff }
+            }
+            Ok(None) => Self::NotInstalled,
+            Err(e) => Self::Invalid(e.to_string()),
+        }
+    }
+
+    /// Compare the installed bundle with the active profile again after the mod data or the
+    /// cached resolutions changed.
+    fn update_diff(&mut self, state: &State) {
+        if let Self::Installed { meta, diff } = self {
+            *diff = compare(state, &state.mod_data.active_profile, meta);
+        }
+    }
+}
+
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         if self.needs_restart
@@ -1747,6 +2325,37 @@ impl eframe::App for App {
             msg.handle(self);
         }
 
+        // watch mode
+        let local_mods_changed = match &mut self.local_mod_watcher {
+            Some(watcher) => {
+                ctx.request_repaint_after(POLL_INTERVAL);
+                watcher.set_paths(local_mod_paths(
+                    &self.state,
+                    &self.state.mod_data.active_profile,
+                ));
+                self.integrate_rid.is_none() && self.lint_rid.is_none() && watcher.poll()
+            }
+            None => false,
+        };
+        if local_mods_changed && let Some(fsd_pak) = self.state.config.drg_pak_path.clone() {
+            let mods = self.enabled_mods_by_priority();
+            let disabled_paks = self.active_disabled_paks();
+            self.last_action = None;
+            self.integrate_rid = Some(message::WatchIntegrate::send(
+                &mut self.request_counter,
+                self.state.store.clone(),
+                mods,
+                disabled_paks,
+                fsd_pak,
+                self.state.config.deref().into(),
+                self.lint_options.enabled(),
+                !self.watch_backed_up,
+                self.tx.clone(),
+                ctx.clone(),
+            ));
+            self.problematic_mod_id = None;
+        }
+
         // begin draw
 
         self.show_update_window(ctx);
@@ -1755,6 +2364,11 @@ impl eframe::App for App {
         self.show_settings(ctx);
         self.show_lints_toggle(ctx);
         self.show_lint_report(ctx);
+        self.show_integration_plan(ctx);
+        self.show_doctor(ctx);
+        self.show_archive_paks(ctx);
+        self.show_subscriptions(ctx);
+        self.show_modfile_updates(ctx);
 
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             ui.with_layout(egui::Layout::right_to_left(Align::TOP), |ui| {
@@ -1797,26 +2411,14 @@ impl eframe::App for App {
                             }
 
                             if button.clicked() {
-                                let mut mod_configs = Vec::new();
-                                let mut mods = Vec::new();
-                                let active_profile = self.state.mod_data.active_profile.clone();
-                                self.state
-                                    .mod_data
-                                    .for_each_enabled_mod(&active_profile, |mc| {
-                                        mod_configs.push(mc.clone());
-                                    });
-
-                                mod_configs.sort_by_key(|k| -k.priority);
-
-                                for config in mod_configs {
-                                    mods.push(config.spec.clone());
-                                }
-
+                                let mods = self.enabled_mods_by_priority();
+                                let disabled_paks = self.active_disabled_paks();
                                 self.last_action = None;
                                 self.integrate_rid = Some(message::Integrate::send(
                                     &mut self.request_counter,
                                     self.state.store.clone(),
                                     mods,
+                                    disabled_paks,
                                     self.state.config.drg_pak_path.as_ref().unwrap().clone(),
                                     self.state.config.deref().into(),
                                     self.tx.clone(),
@@ -1824,6 +2426,30 @@ impl eframe::App for App {
                                 ));
                                 self.problematic_mod_id = None;
                             }
+
+                            if ui
+                                .button("Preview changes")
+                                .on_hover_text(
+                                    "Show which mod each file of the mod bundle would come from without installing anything",
+                                )
+                                .clicked()
+                            {
+                                let mods = self.enabled_mods_by_priority();
+                                let disabled_paks = self.active_disabled_paks();
+                                self.last_action = None;
+                                self.integration_plan = None;
+                                self.integration_plan_window = Some(WindowIntegrationPlan);
+                                self.integrate_rid = Some(message::PlanIntegration::send(
+                                    &mut self.request_counter,
+                                    self.state.store.clone(),
+                                    mods,
+                                    disabled_paks,
+                                    self.state.config.drg_pak_path.as_ref().unwrap().clone(),
+                                    self.tx.clone(),
+                                    ctx.clone(),
+                                ));
+                                self.problematic_mod_id = None;
+                            }
                         });
 
                         if ui
@@ -1875,14 +2501,15 @@ impl eframe::App for App {
                                     );
 
                                     debug!("uninstalling mods: pak_path = {}", pak_path.display());
-                                    self.last_action = Some(match uninstall(pak_path, mods) {
-                                        Ok(()) => LastAction::success(
+                                    self.last_action = Some(match uninstall(pak_path, Some(mods)) {
+                                        Ok(_) => LastAction::success(
                                             "Successfully uninstalled mods".to_string(),
                                         ),
                                         Err(e) => LastAction::failure(format!(
                                             "Failed to uninstall mods: {e}"
                                         )),
-                                    })
+                                    });
+                                    self.installed_bundle = InstalledBundle::read(&self.state);
                                 }
                             }
                         });
@@ -1907,6 +2534,45 @@ impl eframe::App for App {
                 {
                     self.lints_toggle_window = Some(WindowLintsToggle);
                 }
+                let mut watching = self.local_mod_watcher.is_some();
+                if ui
+                    .add_enabled(
+                        self.state.config.drg_pak_path.is_some(),
+                        egui::Checkbox::new(&mut watching, "Watch local mods"),
+                    )
+                    .on_hover_text(
+                        "Lint and apply changes whenever the local mod files and directories of the active profile change.\nUses the lints selected under \"Lint mods\".",
+                    )
+                    .changed()
+                {
+                    self.watch_backed_up = false;
+                    self.local_mod_watcher = watching.then(|| {
+                        LocalModWatcher::new(
+                            local_mod_paths(&self.state, &self.state.mod_data.active_profile),
+                            Duration::from_secs(1),
+                        )
+                    });
+                }
+                if ui
+                    .button("Diagnostics")
+                    .on_hover_text("Check the game installation for common problems")
+                    .clicked()
+                {
+                    self.doctor_window = Some(WindowDoctor::new(&self.state));
+                }
+                if ui
+                    .button("mod.io subscriptions")
+                    .on_hover_text(
+                        "Compare the active profile with the mods subscribed to on mod.io",
+                    )
+                    .clicked()
+                {
+                    self.subscriptions_window = Some(WindowSubscriptions {
+                        profile: self.state.mod_data.active_profile.clone(),
+                        rid: None,
+                        diff: None,
+                    });
+                }
                 if ui.button("⚙").on_hover_text("Open settings").clicked() {
                     self.settings_window = Some(WindowSettings::new(&self.state));
                 }
@@ -1927,6 +2593,7 @@ impl eframe::App for App {
                         });
                     }
                 }
+                self.ui_installed_bundle(ui);
                 ui.with_layout(egui::Layout::left_to_right(Align::TOP), |ui| {
                     if let Some(last_action) = &self.last_action {
                         let msg = match &last_action.status {
@@ -1961,7 +2628,24 @@ impl eframe::App for App {
                     |ui| {
 
                         // profile selection
+                        let mut profile_file_action = None;
                         let buttons = |ui: &mut Ui, mod_data: &mut ModData| {
+                            if ui
+                                .button("⬆")
+                                .on_hover_text_at_pointer("Export profile to file")
+                                .clicked()
+                            {
+                                profile_file_action =
+                                    Some(ProfileFileAction::Export(mod_data.active_profile.clone()));
+                            }
+                            if ui
+                                .button("⬇")
+                                .on_hover_text_at_pointer("Import profile from file")
+                                .clicked()
+                            {
+                                profile_file_action = Some(ProfileFileAction::Import);
+                            }
+
                             if ui
                                 .button("📋")
                                 .on_hover_text_at_pointer("Copy profile mods")
@@ -1996,7 +2680,17 @@ impl eframe::App for App {
                             self.state.mod_data.deref_mut().deref_mut(),
                             Some(buttons),
                         ) {
-                            self.state.mod_data.save().unwrap();
+                            self.save_mod_data();
+                        }
+
+                        match profile_file_action {
+                            Some(ProfileFileAction::Export(profile)) => {
+                                self.export_profile(profile);
+                            }
+                            Some(ProfileFileAction::Import) => {
+                                self.import_profile(ctx);
+                            }
+                            None => {}
                         }
 
                         ui.separator();
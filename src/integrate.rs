@@ -1,13 +1,13 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io::{BufReader, BufWriter, Cursor, ErrorKind, Read, Seek, Write};
 use std::path::{Path, PathBuf};
 
 use fs_err as fs;
 
 use repak::PakWriter;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use snafu::{prelude::*, Whatever};
-use tracing::info;
+use tracing::{info, warn};
 use uasset_utils::asset_registry::{AssetRegistry, Readable as _, Writable as _};
 use uasset_utils::paths::{PakPath, PakPathBuf, PakPathComponentTrait};
 use uasset_utils::splice::{
@@ -16,8 +16,9 @@ use uasset_utils::splice::{
 use unreal_asset::engine_version::EngineVersion;
 use unreal_asset::AssetBuilder;
 
+use crate::archive::{read_mod_contents, ArchiveError, ModContents};
 use crate::mod_lints::LintError;
-use crate::providers::{ModInfo, ProviderError, ReadSeek};
+use crate::providers::{ModInfo, ModSpecification, ProviderError, ReadSeek};
 use mint_lib::mod_info::{ApprovalStatus, Meta, MetaConfig, MetaMod, SemverVersion};
 use mint_lib::DRGInstallation;
 
@@ -40,38 +41,95 @@ use unreal_asset::{
 /// back to the config so they will be disabled when the game is launched again. Since we have
 /// Modio IDs anyway, with just a little more effort we can make the 'uninstall' button work as an
 /// 'install' button for the official integration. Best anti-feature ever.
+///
+/// If `modio_mods` is `None` the official integration is left untouched.
 #[tracing::instrument(level = "debug", skip(path_pak))]
-pub fn uninstall<P: AsRef<Path>>(path_pak: P, modio_mods: HashSet<u32>) -> Result<(), Whatever> {
+pub fn uninstall<P: AsRef<Path>>(
+    path_pak: P,
+    modio_mods: Option<HashSet<u32>>,
+) -> Result<UninstallReport, Whatever> {
     let installation = DRGInstallation::from_pak_path(path_pak)
         .whatever_context("failed to get DRG installation")?;
+    let mut report = UninstallReport::default();
+
     let path_mods_pak = installation.paks_path().join("mods_P.pak");
-    match fs::remove_file(&path_mods_pak) {
-        Ok(()) => Ok(()),
-        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
-        Err(e) => Err(e),
-    }
-    .with_whatever_context(|_| format!("failed to remove {}", path_mods_pak.display()))?;
+    remove_if_exists(&path_mods_pak, &mut report)?;
     #[cfg(feature = "hook")]
     {
         let path_hook_dll = installation
             .binaries_directory()
             .join(installation.installation_type.hook_dll_name());
-        match fs::remove_file(&path_hook_dll) {
-            Ok(()) => Ok(()),
-            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
-            Err(e) => Err(e),
+        remove_if_exists(&path_hook_dll, &mut report)?;
+    }
+    if let Some(modio_mods) = modio_mods {
+        match uninstall_modio(&installation, modio_mods) {
+            Ok(Some((config_path, ini_changes))) => {
+                report.config_path = Some(config_path);
+                report.ini_changes = ini_changes;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("failed to restore mod.io integration: {e}");
+                report.modio_error = Some(e.to_string());
+            }
         }
-        .with_whatever_context(|_| format!("failed to remove {}", path_hook_dll.display()))?;
     }
-    uninstall_modio(&installation, modio_mods).ok();
-    Ok(())
+    Ok(report)
+}
+
+/// What [`uninstall`] changed on disk.
+#[derive(Debug, Default)]
+pub struct UninstallReport {
+    /// Files that existed and were removed.
+    pub removed_files: Vec<PathBuf>,
+    /// GameUserSettings.ini, if it was rewritten to restore the official integration.
+    pub config_path: Option<PathBuf>,
+    pub ini_changes: Vec<IniChange>,
+    /// Set if restoring the official integration failed. Removing the mod bundle still succeeded.
+    pub modio_error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IniChange {
+    pub section: String,
+    pub key: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+fn remove_if_exists(path: &Path, report: &mut UninstallReport) -> Result<(), Whatever> {
+    match fs::remove_file(path) {
+        Ok(()) => {
+            report.removed_files.push(path.to_path_buf());
+            Ok(())
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+    .with_whatever_context(|_| format!("failed to remove {}", path.display()))
+}
+
+#[cfg(feature = "hook")]
+pub(crate) const HOOK_DLL: &[u8] = include_bytes!(env!("CARGO_CDYLIB_FILE_HOOK_hook"));
+
+pub(crate) const UGC_SECTION: &str = "/Script/FSD.UserGeneratedContent";
+
+/// mod.io's record of the mods installed through the official integration.
+pub(crate) fn modio_state_path(modio_dir: &Path) -> PathBuf {
+    modio_dir.join("metadata/state.json")
+}
+
+pub(crate) fn game_user_settings_path(installation: &DRGInstallation) -> PathBuf {
+    installation
+        .root
+        .join("Saved/Config/WindowsNoEditor/GameUserSettings.ini")
 }
 
 #[tracing::instrument(level = "debug")]
 fn uninstall_modio(
     installation: &DRGInstallation,
     modio_mods: HashSet<u32>,
-) -> Result<(), Whatever> {
+) -> Result<Option<(PathBuf, Vec<IniChange>)>, Whatever> {
     #[derive(Debug, Deserialize)]
     struct ModioState {
         #[serde(rename = "Mods")]
@@ -83,25 +141,35 @@ fn uninstall_modio(
         id: u32,
     }
     let Some(modio_dir) = installation.modio_directory() else {
-        return Ok(());
+        return Ok(None);
     };
     let modio_state: ModioState = serde_json::from_reader(std::io::BufReader::new(
-        fs::File::open(modio_dir.join("metadata/state.json"))
+        fs::File::open(modio_state_path(&modio_dir))
             .whatever_context("failed to read mod.io metadata/state.json")?,
     ))
     .whatever_context("failed to parse mod.io metadata/state.json")?;
-    let config_path = installation
-        .root
-        .join("Saved/Config/WindowsNoEditor/GameUserSettings.ini");
+    let config_path = game_user_settings_path(installation);
     let mut config = ini::Ini::load_from_file(&config_path)
         .whatever_context("failed to load GameUserSettings.ini")?;
 
     let ignore_keys = HashSet::from(["CurrentModioUserId"]);
 
+    let section_entries = |config: &ini::Ini| {
+        config
+            .section(Some(UGC_SECTION))
+            .map(|s| {
+                s.iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .unwrap_or_default()
+    };
+    let before = section_entries(&config);
+
     config
-        .entry(Some("/Script/FSD.UserGeneratedContent".to_string()))
+        .entry(Some(UGC_SECTION.to_string()))
         .or_insert_with(Default::default);
-    if let Some(ugc_section) = config.section_mut(Some("/Script/FSD.UserGeneratedContent")) {
+    if let Some(ugc_section) = config.section_mut(Some(UGC_SECTION)) {
         let local_mods = installation
             .root
             .join("Mods")
@@ -138,14 +206,30 @@ fn uninstall_modio(
 
     config
         .write_to_file_opt(
-            config_path,
+            &config_path,
             ini::WriteOption {
                 line_separator: ini::LineSeparator::CRLF,
                 ..Default::default()
             },
         )
         .whatever_context("failed to write to GameUserSettings.ini")?;
-    Ok(())
+
+    let after = section_entries(&config);
+    let ini_changes = before
+        .keys()
+        .chain(after.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .map(|key| IniChange {
+            section: UGC_SECTION.to_string(),
+            key: key.clone(),
+            old: before.get(key).cloned(),
+            new: after.get(key).cloned(),
+        })
+        .collect();
+
+    Ok(Some((config_path, ini_changes)))
 }
 
 static INTEGRATION_DIR: include_dir::Dir<'_> =
@@ -161,6 +245,8 @@ pub enum IntegrationError {
     #[snafu(transparent)]
     RepakError { source: repak::Error },
     #[snafu(transparent)]
+    ArchiveError { source: ArchiveError },
+    #[snafu(transparent)]
     UnrealAssetError { source: unreal_asset::Error },
     #[snafu(display("mod {:?}: I/O error encountered during its processing", mod_info.name))]
     CtxtIoError {
@@ -197,6 +283,10 @@ pub enum IntegrationError {
     JoinError { source: tokio::task::JoinError },
     #[snafu(transparent)]
     LintError { source: LintError },
+    #[snafu(display("written mod bundle is invalid: {reason}"))]
+    InvalidBundle { reason: String },
+    #[snafu(display("no previous mod bundle to roll back to at {}", path.display()))]
+    NoBackup { path: PathBuf },
     #[snafu(display("self update failed: {source:?}"))]
     SelfUpdateFailed {
         source: Box<dyn std::error::Error + Send + Sync>,
@@ -216,11 +306,123 @@ impl IntegrationError {
     }
 }
 
+/// Mod referenced by an [`IntegrationPlan`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlanMod {
+    pub name: String,
+    pub url: String,
+}
+
+impl From<&ModInfo> for PlanMod {
+    fn from(info: &ModInfo) -> Self {
+        Self {
+            name: info.name.clone(),
+            url: info.spec.url.clone(),
+        }
+    }
+}
+
+/// Where the copy of a file that ends up in the bundle comes from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum PlanSource {
+    Mod(PlanMod),
+    /// Unmodified asset from the game pak.
+    #[default]
+    Game,
+    /// Added by the integration itself.
+    Integration,
+}
+
+impl std::fmt::Display for PlanSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanSource::Mod(m) => write!(f, "{}", m.name),
+            PlanSource::Game => write!(f, "<game>"),
+            PlanSource::Integration => write!(f, "<integration>"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedFile {
+    pub source: PlanSource,
+    /// Mods that also contain the file but whose copy is not used.
+    pub overridden: Vec<PlanMod>,
+}
+
+/// Game asset patched by the integration after all mods have been added. Unlike regular files
+/// later mods take precedence over earlier ones.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedPatch {
+    pub path: String,
+    pub patch: &'static str,
+    pub source: PlanSource,
+    pub overridden: Vec<PlanMod>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedAssetRegistryEntry {
+    pub path: String,
+    pub source: PlanMod,
+}
+
+/// What [`integrate`] would write, as returned by [`plan_integration`].
+#[derive(Debug, Default, Serialize)]
+pub struct IntegrationPlan {
+    /// Every file in the bundle except patched assets, keyed by path.
+    pub files: BTreeMap<String, PlannedFile>,
+    pub patched: Vec<PlannedPatch>,
+    /// Assets from mods added to the AssetRegistry.
+    pub asset_registry: Vec<PlannedAssetRegistryEntry>,
+}
+
+/// Paths of paks inside mod archives that are not integrated, keyed by [`ModInfo::spec`]. See
+/// [`crate::state::ModConfig::disabled_paks`].
+pub type DisabledPaks = HashMap<ModSpecification, BTreeSet<String>>;
+
+/// Re-key paks disabled per configured mod specification by the [`ModInfo::spec`] each one
+/// resolved to, as expected by [`integrate`].
+pub fn resolve_disabled_paks(
+    disabled_paks: &DisabledPaks,
+    mods: &HashMap<ModSpecification, ModInfo>,
+) -> DisabledPaks {
+    disabled_paks
+        .iter()
+        .filter_map(|(spec, paks)| Some((mods.get(spec)?.spec.clone(), paks.clone())))
+        .collect()
+}
+
+/// Install the mod bundle and hook into the installation of `path_pak`, keeping the replaced bundle
+/// at [`backup_path`].
 #[tracing::instrument(skip_all)]
 pub fn integrate<P: AsRef<Path>>(
     path_pak: P,
     config: MetaConfig,
     mods: Vec<(ModInfo, PathBuf)>,
+    disabled_paks: &DisabledPaks,
+) -> Result<(), IntegrationError> {
+    install(path_pak, config, mods, disabled_paks, true)
+}
+
+/// Like [`integrate`] but leaves the existing backup alone. Used when integrating repeatedly, e.g.
+/// while watching local mods, so the backup keeps the bundle from before the first integration.
+#[tracing::instrument(skip_all)]
+pub fn reintegrate<P: AsRef<Path>>(
+    path_pak: P,
+    config: MetaConfig,
+    mods: Vec<(ModInfo, PathBuf)>,
+    disabled_paks: &DisabledPaks,
+) -> Result<(), IntegrationError> {
+    install(path_pak, config, mods, disabled_paks, false)
+}
+
+fn install<P: AsRef<Path>>(
+    path_pak: P,
+    config: MetaConfig,
+    mods: Vec<(ModInfo, PathBuf)>,
+    disabled_paks: &DisabledPaks,
+    backup: bool,
 ) -> Result<(), IntegrationError> {
     let Ok(installation) = DRGInstallation::from_pak_path(&path_pak) else {
         return Err(IntegrationError::DrgInstallationNotFound {
@@ -229,6 +431,180 @@ pub fn integrate<P: AsRef<Path>>(
     };
     let path_mod_pak = installation.paks_path().join("mods_P.pak");
 
+    write_bundle(
+        path_pak,
+        &path_mod_pak,
+        config,
+        &mods,
+        disabled_paks,
+        backup,
+    )?;
+
+    // only update the hook once the bundle has been replaced so a failed integration leaves the
+    // previous installation intact
+    #[cfg(feature = "hook")]
+    {
+        let path_hook_dll = installation
+            .binaries_directory()
+            .join(installation.installation_type.hook_dll_name());
+        if path_hook_dll
+            .metadata()
+            .map(|m| m.len() != HOOK_DLL.len() as u64)
+            .unwrap_or(true)
+        {
+            fs::write(&path_hook_dll, HOOK_DLL)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the mod bundle to `path_mod_pak` using `path_pak` as the game pak. Unlike [`integrate`]
+/// this does not require a game installation and does not install the hook.
+#[tracing::instrument(skip_all)]
+pub fn integrate_to_path<P: AsRef<Path>, O: AsRef<Path>>(
+    path_pak: P,
+    path_mod_pak: O,
+    config: MetaConfig,
+    mods: &[(ModInfo, PathBuf)],
+    disabled_paks: &DisabledPaks,
+) -> Result<(), IntegrationError> {
+    write_bundle(
+        path_pak,
+        path_mod_pak.as_ref(),
+        config,
+        mods,
+        disabled_paks,
+        false,
+    )
+}
+
+/// Path the previous mod bundle is moved to when a new one is installed.
+pub fn backup_path(path_mod_pak: &Path) -> PathBuf {
+    path_mod_pak.with_extension("pak.bak")
+}
+
+/// Write the bundle to a temporary file next to `path_mod_pak`, check that it can be read back
+/// and only then replace `path_mod_pak`, keeping the replaced bundle if `backup` is set.
+fn write_bundle(
+    path_pak: impl AsRef<Path>,
+    path_mod_pak: &Path,
+    config: MetaConfig,
+    mods: &[(ModInfo, PathBuf)],
+    disabled_paks: &DisabledPaks,
+    backup: bool,
+) -> Result<(), IntegrationError> {
+    let dir = path_mod_pak
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut writer = BufWriter::new(tempfile::NamedTempFile::new_in(dir)?);
+    integrate_inner(path_pak, mods, disabled_paks, Some((&mut writer, config)))?;
+    let tmp = writer.into_inner().map_err(|e| e.into_error())?;
+
+    verify_bundle(tmp.path(), mods.len())?;
+
+    if backup && path_mod_pak.exists() {
+        let path_backup = backup_path(path_mod_pak);
+        // hard link so the installed bundle stays in place until it is atomically replaced
+        match fs::remove_file(&path_backup) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        if fs::hard_link(path_mod_pak, &path_backup).is_err() {
+            fs::copy(path_mod_pak, &path_backup)?;
+        }
+    }
+    tmp.persist(path_mod_pak).map_err(|e| e.error)?;
+
+    info!(
+        "{} mods installed to {}",
+        mods.len(),
+        path_mod_pak.display()
+    );
+
+    Ok(())
+}
+
+/// Reopen a written bundle and check its `meta` lists every mod.
+fn verify_bundle(path: &Path, mod_count: usize) -> Result<(), IntegrationError> {
+    let meta = read_bundle_meta(path)?;
+    ensure!(
+        meta.mods.len() == mod_count,
+        InvalidBundleSnafu {
+            reason: format!("meta lists {} mods, expected {mod_count}", meta.mods.len()),
+        }
+    );
+    Ok(())
+}
+
+/// Read the `meta` written into a mod bundle.
+pub fn read_bundle_meta(path: &Path) -> Result<Meta, IntegrationError> {
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    let pak = repak::PakBuilder::new().reader(&mut reader).map_err(|e| {
+        IntegrationError::InvalidBundle {
+            reason: e.to_string(),
+        }
+    })?;
+    let meta = pak
+        .get("meta", &mut reader)
+        .map_err(|e| IntegrationError::InvalidBundle {
+            reason: e.to_string(),
+        })?;
+    postcard::from_bytes::<Meta>(&meta).map_err(|e| IntegrationError::InvalidBundle {
+        reason: format!("failed to read meta: {e}"),
+    })
+}
+
+/// Swap the installed mod bundle with the one it replaced, so calling it again undoes the
+/// rollback. Returns the path of the restored bundle.
+pub fn rollback<P: AsRef<Path>>(path_pak: P) -> Result<PathBuf, IntegrationError> {
+    let Ok(installation) = DRGInstallation::from_pak_path(&path_pak) else {
+        return Err(IntegrationError::DrgInstallationNotFound {
+            path: path_pak.as_ref().to_path_buf(),
+        });
+    };
+    let path_mod_pak = installation.paks_path().join("mods_P.pak");
+    let path_backup = backup_path(&path_mod_pak);
+    ensure!(
+        path_backup.exists(),
+        NoBackupSnafu {
+            path: path_backup.clone()
+        }
+    );
+
+    if path_mod_pak.exists() {
+        let path_tmp = path_mod_pak.with_extension("pak.rollback");
+        fs::rename(&path_mod_pak, &path_tmp)?;
+        fs::rename(&path_backup, &path_mod_pak)?;
+        fs::rename(&path_tmp, &path_backup)?;
+    } else {
+        fs::rename(&path_backup, &path_mod_pak)?;
+    }
+    Ok(path_mod_pak)
+}
+
+/// Dry run of [`integrate`]: nothing is written, instead every file that would end up in the
+/// bundle is returned along with the mod it is taken from.
+#[tracing::instrument(skip_all)]
+pub fn plan_integration<P: AsRef<Path>>(
+    path_pak: P,
+    mods: &[(ModInfo, PathBuf)],
+    disabled_paks: &DisabledPaks,
+) -> Result<IntegrationPlan, IntegrationError> {
+    integrate_inner(path_pak, mods, disabled_paks, None::<(fs::File, _)>)
+}
+
+/// Build the mod bundle and write it to `output` if set.
+fn integrate_inner<P: AsRef<Path>, W: Write + Seek>(
+    path_pak: P,
+    mods: &[(ModInfo, PathBuf)],
+    disabled_paks: &DisabledPaks,
+    output: Option<(W, MetaConfig)>,
+) -> Result<IntegrationPlan, IntegrationError> {
+    let (writer, config) = output.unzip();
+    let dry_run = writer.is_none();
+
     let mut fsd_pak_reader = BufReader::new(fs::File::open(path_pak.as_ref())?);
     let fsd_pak = repak::PakBuilder::new().reader(&mut fsd_pak_reader)?;
 
@@ -236,6 +612,8 @@ pub fn integrate<P: AsRef<Path>>(
     struct RawAsset {
         uasset: Option<Vec<u8>>,
         uexp: Option<Vec<u8>>,
+        source: PlanSource,
+        overridden: Vec<PlanMod>,
     }
 
     impl RawAsset {
@@ -247,6 +625,15 @@ pub fn integrate<P: AsRef<Path>>(
             .bulk(Cursor::new(self.uexp.as_ref().unwrap()))
             .build()?)
         }
+
+        fn supplied_by(&mut self, mod_info: &ModInfo) {
+            let source = PlanSource::Mod(mod_info.into());
+            if self.source != source {
+                if let PlanSource::Mod(m) = std::mem::replace(&mut self.source, source) {
+                    self.overridden.push(m);
+                }
+            }
+        }
     }
 
     let ar_path = "FSD/AssetRegistry.bin";
@@ -282,56 +669,37 @@ pub fn integrate<P: AsRef<Path>>(
     );
 
     // collect assets from game pak file
-    for (path, asset) in &mut deferred_assets {
-        // TODO repak should return an option...
-        asset.uasset = match fsd_pak.get(&format!("{path}.uasset"), &mut fsd_pak_reader) {
-            Ok(file) => Ok(Some(file)),
-            Err(repak::Error::MissingEntry(_)) => Ok(None),
-            Err(e) => Err(e),
-        }?;
-        asset.uexp = match fsd_pak.get(&format!("{path}.uexp"), &mut fsd_pak_reader) {
-            Ok(file) => Ok(Some(file)),
-            Err(repak::Error::MissingEntry(_)) => Ok(None),
-            Err(e) => Err(e),
-        }?;
+    if !dry_run {
+        for (path, asset) in &mut deferred_assets {
+            // TODO repak should return an option...
+            asset.uasset = match fsd_pak.get(&format!("{path}.uasset"), &mut fsd_pak_reader) {
+                Ok(file) => Ok(Some(file)),
+                Err(repak::Error::MissingEntry(_)) => Ok(None),
+                Err(e) => Err(e),
+            }?;
+            asset.uexp = match fsd_pak.get(&format!("{path}.uexp"), &mut fsd_pak_reader) {
+                Ok(file) => Ok(Some(file)),
+                Err(repak::Error::MissingEntry(_)) => Ok(None),
+                Err(e) => Err(e),
+            }?;
+        }
     }
 
-    let mut bundle = ModBundleWriter::new(
-        BufWriter::new(
-            fs::OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&path_mod_pak)?,
-        ),
-        &fsd_pak.files(),
-    )?;
+    let mut bundle = ModBundleWriter::new(writer, &fsd_pak.files())?;
 
-    #[cfg(feature = "hook")]
-    {
-        let path_hook_dll = installation
-            .binaries_directory()
-            .join(installation.installation_type.hook_dll_name());
-        let hook_dll = include_bytes!(env!("CARGO_CDYLIB_FILE_HOOK_hook"));
-        if path_hook_dll
-            .metadata()
-            .map(|m| m.len() != hook_dll.len() as u64)
-            .unwrap_or(true)
-        {
-            fs::write(&path_hook_dll, hook_dll)?;
-        }
-    }
+    let mut plan = IntegrationPlan::default();
 
     let mut init_spacerig_assets = HashSet::new();
     let mut init_cave_assets = HashSet::new();
 
-    let mut added_paths = HashSet::new();
+    // lowercase path -> path in the bundle
+    let mut added_paths = HashMap::new();
 
-    for (mod_info, path) in &mods {
+    for (mod_info, path) in mods {
         let raw_mod_file = fs::File::open(path).with_context(|_| CtxtIoSnafu {
             mod_info: mod_info.clone(),
         })?;
-        let mut buf = get_pak_from_data(Box::new(BufReader::new(raw_mod_file))).map_err(|e| {
+        let paks = get_paks_from_data(Box::new(BufReader::new(raw_mod_file))).map_err(|e| {
             if let IntegrationError::IoError { source } = e {
                 IntegrationError::CtxtIoError {
                     source,
@@ -341,158 +709,203 @@ pub fn integrate<P: AsRef<Path>>(
                 e
             }
         })?;
-        let pak = repak::PakBuilder::new()
-            .reader(&mut buf)
-            .with_context(|_| CtxtRepakSnafu {
-                mod_info: mod_info.clone(),
-            })?;
-
-        let mount = PakPath::new(pak.mount_point());
-
-        let pak_files = pak
-            .files()
-            .into_iter()
-            .map(|p| -> Result<_, IntegrationError> {
-                let j = mount.join(&p);
-                Ok((
-                    j.strip_prefix("../../../")
-                        .map_err(|_| IntegrationError::ModfileInvalidPrefix {
-                            mod_info: mod_info.clone(),
-                            modfile_path: j.to_string(),
-                        })?
-                        .to_path_buf(),
-                    p,
-                ))
-            })
-            .collect::<Result<HashMap<_, _>, _>>()?;
-
-        for (normalized, pak_path) in &pak_files {
-            match normalized.extension() {
-                Some("uasset" | "umap")
-                    if pak_files.contains_key(&normalized.with_extension("uexp")) =>
-                {
-                    let uasset = pak
-                        .get(pak_path, &mut buf)
-                        .with_context(|_| CtxtRepakSnafu {
-                            mod_info: mod_info.clone(),
-                        })?;
-
-                    let uexp = pak
-                        .get(
-                            PakPath::new(pak_path).with_extension("uexp").as_str(),
-                            &mut buf,
-                        )
-                        .with_context(|_| CtxtRepakSnafu {
-                            mod_info: mod_info.clone(),
-                        })?;
-
-                    let asset = AssetBuilder::new(Cursor::new(uasset), EngineVersion::VER_UE4_27)
-                        .bulk(Cursor::new(uexp))
-                        .skip_data(true)
-                        .build()?;
-                    asset_registry
-                        .populate(normalized.with_extension("").as_str(), &asset)
-                        .map_err(|e| IntegrationError::CtxtGenericError {
-                            source: e.into(),
-                            mod_info: mod_info.clone(),
-                        })?;
+        for ModPak {
+            pak,
+            mut buf,
+            files: pak_files,
+        } in read_mod_paks(mod_info, paks, disabled_paks)?
+        {
+            for (normalized, pak_path) in &pak_files {
+                match normalized.extension() {
+                    Some("uasset" | "umap")
+                        if pak_files.contains_key(&normalized.with_extension("uexp")) =>
+                    {
+                        let uasset =
+                            pak.get(pak_path, &mut buf)
+                                .with_context(|_| CtxtRepakSnafu {
+                                    mod_info: mod_info.clone(),
+                                })?;
+
+                        let uexp = pak
+                            .get(
+                                PakPath::new(pak_path).with_extension("uexp").as_str(),
+                                &mut buf,
+                            )
+                            .with_context(|_| CtxtRepakSnafu {
+                                mod_info: mod_info.clone(),
+                            })?;
+
+                        let asset =
+                            AssetBuilder::new(Cursor::new(uasset), EngineVersion::VER_UE4_27)
+                                .bulk(Cursor::new(uexp))
+                                .skip_data(true)
+                                .build()?;
+                        let asset_path = normalized.with_extension("");
+                        asset_registry
+                            .populate(asset_path.as_str(), &asset)
+                            .map_err(|e| IntegrationError::CtxtGenericError {
+                                source: e.into(),
+                                mod_info: mod_info.clone(),
+                            })?;
+                        plan.asset_registry.push(PlannedAssetRegistryEntry {
+                            path: asset_path.to_string(),
+                            source: mod_info.into(),
+                        });
+                    }
+                    _ => {}
                 }
-                _ => {}
             }
-        }
 
-        for (normalized, pak_path) in pak_files {
-            let lowercase = normalized.as_str().to_ascii_lowercase();
-            if added_paths.contains(&lowercase) {
-                continue;
-            }
-
-            if let Some(filename) = normalized.file_name() {
-                if filename == "AssetRegistry.bin" {
-                    continue;
-                }
-                if normalized.extension() == Some("ushaderbytecode") {
+            for (normalized, pak_path) in pak_files {
+                let lowercase = normalized.as_str().to_ascii_lowercase();
+                if let Some(added) = added_paths.get(&lowercase) {
+                    let file: &mut PlannedFile = plan.files.get_mut(added).unwrap();
+                    if file.source != PlanSource::Mod(mod_info.into()) {
+                        file.overridden.push(mod_info.into());
+                    }
                     continue;
                 }
-                let lower = filename.to_lowercase();
-                if lower == "initspacerig.uasset" {
-                    init_spacerig_assets.insert(format_soft_class(&normalized));
-                }
-                if lower == "initcave.uasset" {
-                    init_cave_assets.insert(format_soft_class(&normalized));
+
+                if let Some(filename) = normalized.file_name() {
+                    if filename == "AssetRegistry.bin" {
+                        continue;
+                    }
+                    if normalized.extension() == Some("ushaderbytecode") {
+                        continue;
+                    }
+                    let lower = filename.to_lowercase();
+                    if lower == "initspacerig.uasset" {
+                        init_spacerig_assets.insert(format_soft_class(&normalized));
+                    }
+                    if lower == "initcave.uasset" {
+                        init_cave_assets.insert(format_soft_class(&normalized));
+                    }
                 }
-            }
 
-            let file_data = pak
-                .get(&pak_path, &mut buf)
-                .with_context(|_| CtxtRepakSnafu {
-                    mod_info: mod_info.clone(),
-                })?;
-            if let Some(raw) = normalized
-                .as_str()
-                .strip_suffix(".uasset")
-                .and_then(|path| deferred_assets.get_mut(path))
-            {
-                raw.uasset = Some(file_data);
-            } else if let Some(raw) = normalized
-                .as_str()
-                .strip_suffix(".uexp")
-                .and_then(|path| deferred_assets.get_mut(path))
-            {
-                raw.uexp = Some(file_data);
-            } else {
-                bundle.write_file(&file_data, normalized.as_str())?;
-                added_paths.insert(lowercase);
+                let mut read_file = || {
+                    pak.get(&pak_path, &mut buf)
+                        .with_context(|_| CtxtRepakSnafu {
+                            mod_info: mod_info.clone(),
+                        })
+                };
+                let path = normalized.as_str();
+                if let Some(raw) = path
+                    .strip_suffix(".uasset")
+                    .and_then(|path| deferred_assets.get_mut(path))
+                {
+                    raw.supplied_by(mod_info);
+                    if !dry_run {
+                        raw.uasset = Some(read_file()?);
+                    }
+                } else if let Some(raw) = path
+                    .strip_suffix(".uexp")
+                    .and_then(|path| deferred_assets.get_mut(path))
+                {
+                    raw.supplied_by(mod_info);
+                    if !dry_run {
+                        raw.uexp = Some(read_file()?);
+                    }
+                } else {
+                    if !dry_run {
+                        bundle.write_file(&read_file()?, path)?;
+                    }
+                    let bundle_path = bundle.normalize_path(path).to_string();
+                    plan.files.insert(
+                        bundle_path.clone(),
+                        PlannedFile {
+                            source: PlanSource::Mod(mod_info.into()),
+                            overridden: vec![],
+                        },
+                    );
+                    added_paths.insert(lowercase, bundle_path);
+                }
             }
         }
     }
 
-    {
-        let mut pcb_asset = deferred_assets[&pcb_path].parse()?;
-        hook_pcb(&mut pcb_asset);
-        bundle.write_asset(pcb_asset, pcb_path)?;
-    }
-
     let mut patch_deferred = |path_str: &str,
+                              patch: &'static str,
                               f: fn(&mut _) -> Result<(), IntegrationError>|
      -> Result<(), IntegrationError> {
-        let mut asset = deferred_assets[path_str].parse()?;
+        let raw = &deferred_assets[path_str];
+        plan.patched.push(PlannedPatch {
+            path: path_str.to_string(),
+            patch,
+            source: raw.source.clone(),
+            overridden: raw.overridden.clone(),
+        });
+        if dry_run {
+            return Ok(());
+        }
+        let mut asset = raw.parse()?;
         f(&mut asset)?;
         bundle.write_asset(asset, path_str)
     };
 
+    patch_deferred(pcb_path, "hook", |asset| {
+        hook_pcb(asset);
+        Ok(())
+    })?;
+
     // apply patches to base assets
     for patch_path in patch_paths {
-        patch_deferred(patch_path, patch)?;
+        patch_deferred(patch_path, "modded server check", patch)?;
     }
-    patch_deferred(escape_menu_path, patch_modding_tab)?;
-    patch_deferred(modding_tab_path, patch_modding_tab_item)?;
-    patch_deferred(server_list_entry_path, patch_server_list_entry)?;
+    patch_deferred(escape_menu_path, "modding tab", patch_modding_tab)?;
+    patch_deferred(modding_tab_path, "modding tab item", patch_modding_tab_item)?;
+    patch_deferred(
+        server_list_entry_path,
+        "server list mods",
+        patch_server_list_entry,
+    )?;
 
     let mut int_files = HashMap::new();
     collect_dir_files(&INTEGRATION_DIR, &mut int_files);
 
-    for (path, data) in &int_files {
-        bundle.write_file(data, path)?;
+    let mut add_integration_file = |path: &str| {
+        let lowercase = path.to_ascii_lowercase();
+        let overridden = added_paths
+            .remove(&lowercase)
+            .and_then(|added| plan.files.remove(&added))
+            .map(|file| {
+                let mut overridden = file.overridden;
+                if let PlanSource::Mod(m) = file.source {
+                    overridden.insert(0, m);
+                }
+                overridden
+            })
+            .unwrap_or_default();
+        plan.files.insert(
+            bundle.normalize_path(path).to_string(),
+            PlannedFile {
+                source: PlanSource::Integration,
+                overridden,
+            },
+        );
+    };
+    for path in int_files.keys() {
+        add_integration_file(path);
     }
+    add_integration_file("meta");
+    add_integration_file(ar_path);
 
-    bundle.write_meta(config, &mods)?;
+    if let Some(config) = config {
+        for (path, data) in &int_files {
+            bundle.write_file(data, path)?;
+        }
 
-    let mut buf = vec![];
-    asset_registry
-        .write(&mut buf)
-        .map_err(|e| IntegrationError::GenericError { msg: e.to_string() })?;
-    bundle.write_file(&buf, ar_path)?;
+        bundle.write_meta(config, mods)?;
 
-    bundle.finish()?;
+        let mut buf = vec![];
+        asset_registry
+            .write(&mut buf)
+            .map_err(|e| IntegrationError::GenericError { msg: e.to_string() })?;
+        bundle.write_file(&buf, ar_path)?;
 
-    info!(
-        "{} mods installed to {}",
-        mods.len(),
-        path_mod_pak.display()
-    );
+        bundle.finish()?;
+    }
 
-    Ok(())
+    Ok(plan)
 }
 
 fn collect_dir_files(dir: &'static include_dir::Dir, collect: &mut HashMap<String, &[u8]>) {
@@ -526,12 +939,13 @@ fn format_soft_class<P: AsRef<PakPath>>(path: P) -> String {
 }
 
 struct ModBundleWriter<W: Write + Seek> {
-    pak_writer: PakWriter<W>,
+    /// `None` for dry runs, in which case nothing is written.
+    pak_writer: Option<PakWriter<W>>,
     directories: HashMap<String, Dir>,
 }
 
 impl<W: Write + Seek> ModBundleWriter<W> {
-    fn new(writer: W, fsd_paths: &[String]) -> Result<Self, IntegrationError> {
+    fn new(writer: Option<W>, fsd_paths: &[String]) -> Result<Self, IntegrationError> {
         let mut directories: HashMap<String, Dir> = HashMap::new();
         for f in fsd_paths {
             let mut dir = &mut directories;
@@ -547,9 +961,11 @@ impl<W: Write + Seek> ModBundleWriter<W> {
         }
 
         Ok(Self {
-            pak_writer: repak::PakBuilder::new()
-                .compression([repak::Compression::Zlib])
-                .writer(writer, repak::Version::V11, "../../../".to_string(), None),
+            pak_writer: writer.map(|writer| {
+                repak::PakBuilder::new()
+                    .compression([repak::Compression::Zlib])
+                    .writer(writer, repak::Version::V11, "../../../".to_string(), None)
+            }),
             directories,
         })
     }
@@ -570,8 +986,10 @@ impl<W: Write + Seek> ModBundleWriter<W> {
     }
 
     fn write_file(&mut self, data: &[u8], path: &str) -> Result<(), IntegrationError> {
-        self.pak_writer
-            .write_file(self.normalize_path(path).as_str(), data)?;
+        let path = self.normalize_path(path);
+        if let Some(pak_writer) = &mut self.pak_writer {
+            pak_writer.write_file(path.as_str(), data)?;
+        }
         Ok(())
     }
 
@@ -628,7 +1046,9 @@ impl<W: Write + Seek> ModBundleWriter<W> {
     }
 
     fn finish(self) -> Result<(), IntegrationError> {
-        self.pak_writer.write_index()?;
+        if let Some(pak_writer) = self.pak_writer {
+            pak_writer.write_index()?;
+        }
         Ok(())
     }
 }
@@ -639,38 +1059,116 @@ struct Dir {
     children: HashMap<String, Dir>,
 }
 
-pub(crate) fn get_pak_from_data(
-    mut data: Box<dyn ReadSeek>,
-) -> Result<Box<dyn ReadSeek>, IntegrationError> {
-    if let Ok(mut archive) = zip::ZipArchive::new(&mut data) {
-        (0..archive.len())
-            .map(|i| -> Result<Option<Box<dyn ReadSeek>>, IntegrationError> {
-                let mut file = archive
-                    .by_index(i)
-                    .map_err(|_| IntegrationError::GenericError {
-                        msg: "failed to extract file in zip archive".to_string(),
-                    })?;
-                match file.enclosed_name() {
-                    Some(p) => {
-                        if file.is_file() && p.extension() == Some(std::ffi::OsStr::new("pak")) {
-                            let mut buf = vec![];
-                            file.read_to_end(&mut buf)?;
-                            Ok(Some(Box::new(Cursor::new(buf))))
-                        } else {
-                            Ok(None)
-                        }
-                    }
-                    None => Ok(None),
+/// Paks of a mod file in the order they are integrated, each with its path inside the archive if
+/// the mod is one. Paks of an archive are sorted by path, a later pak overriding files of earlier
+/// ones.
+pub(crate) fn get_paks_from_data(
+    data: Box<dyn ReadSeek>,
+) -> Result<Vec<(Option<String>, Box<dyn ReadSeek>)>, IntegrationError> {
+    let contents = read_mod_contents(data).map_err(|e| match e {
+        ArchiveError::IoError { source } => IntegrationError::IoError { source },
+        e => IntegrationError::ArchiveError { source: e },
+    })?;
+    match contents {
+        ModContents::Pak(data) => Ok(vec![(None, data)]),
+        ModContents::Archive { format, paks, .. } => {
+            ensure!(
+                !paks.is_empty(),
+                GenericSnafu {
+                    msg: format!("{} archive does not contain pak", format.name),
                 }
-            })
-            .find_map(Result::transpose)
-            .context(GenericSnafu {
-                msg: "zip archive does not contain pak",
-            })?
-    } else {
-        data.rewind()?;
-        Ok(data)
+            );
+            let mut paks = paks
+                .into_iter()
+                .map(|(path, buf)| -> (_, Box<dyn ReadSeek>) {
+                    (Some(pak_name(&path)), Box::new(Cursor::new(buf)))
+                })
+                .collect::<Vec<_>>();
+            paks.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Ok(paks)
+        }
+    }
+}
+
+/// Enabled paks of a mod from [`get_paks_from_data`] in the order files are taken from them. Files
+/// are taken from the first pak that provides them, so the paks of an archive are returned last to
+/// first to let each pak override the ones before it.
+fn paks_by_precedence(
+    mod_info: &ModInfo,
+    paks: Vec<(Option<String>, Box<dyn ReadSeek>)>,
+    disabled_paks: &DisabledPaks,
+) -> Vec<(Option<String>, Box<dyn ReadSeek>)> {
+    let disabled = disabled_paks.get(&mod_info.spec);
+    paks.into_iter()
+        .rev()
+        .filter(|(name, _)| match (name, disabled) {
+            (Some(name), Some(disabled)) if disabled.contains(name) => {
+                info!("skipping disabled pak {name} of {}", mod_info.name);
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+/// An enabled pak of a mod and the files taken from it.
+struct ModPak {
+    pak: repak::PakReader,
+    buf: Box<dyn ReadSeek>,
+    /// Normalized paths of the files taken from the pak and their paths inside it.
+    files: HashMap<PakPathBuf, String>,
+}
+
+/// Open the enabled paks of a mod in [`paks_by_precedence`] order. Each file is only taken from
+/// the first pak providing it, so later paks of an archive override plain files and patched
+/// assets alike.
+fn read_mod_paks(
+    mod_info: &ModInfo,
+    paks: Vec<(Option<String>, Box<dyn ReadSeek>)>,
+    disabled_paks: &DisabledPaks,
+) -> Result<Vec<ModPak>, IntegrationError> {
+    let mut taken = HashSet::new();
+    let mut mod_paks = vec![];
+    for (_, mut buf) in paks_by_precedence(mod_info, paks, disabled_paks) {
+        let pak = repak::PakBuilder::new()
+            .reader(&mut buf)
+            .with_context(|_| CtxtRepakSnafu {
+                mod_info: mod_info.clone(),
+            })?;
+
+        let mount = PakPath::new(pak.mount_point());
+        let mut files = HashMap::new();
+        for p in pak.files() {
+            let j = mount.join(&p);
+            let normalized = j
+                .strip_prefix("../../../")
+                .map_err(|_| IntegrationError::ModfileInvalidPrefix {
+                    mod_info: mod_info.clone(),
+                    modfile_path: j.to_string(),
+                })?
+                .to_path_buf();
+            if taken.insert(normalized.as_str().to_ascii_lowercase()) {
+                files.insert(normalized, p);
+            }
+        }
+        mod_paks.push(ModPak { pak, buf, files });
     }
+    Ok(mod_paks)
+}
+
+/// Paths of the paks inside a mod archive in the order they are integrated, or nothing if the mod
+/// is a single pak.
+pub fn list_archive_paks(path: &Path) -> Result<Vec<String>, IntegrationError> {
+    let data = Box::new(BufReader::new(fs::File::open(path)?));
+    Ok(get_paks_from_data(data)?
+        .into_iter()
+        .filter_map(|(name, _)| name)
+        .collect())
+}
+
+/// Path of a pak inside an archive as stored in [`crate::state::ModConfig::disabled_paks`].
+pub(crate) fn pak_name(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
 }
 
 type ImportChain<'a> = Vec<Import<'a>>;
@@ -1265,3 +1763,124 @@ fn patch_server_list_entry<C: Seek + Read>(asset: &mut Asset<C>) -> Result<(), I
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::providers::ModResolution;
+
+    fn build_pak(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut buf = Cursor::new(vec![]);
+        let mut pak = repak::PakBuilder::new().writer(
+            &mut buf,
+            repak::Version::V11,
+            "../../../".to_string(),
+            None,
+        );
+        for (path, data) in files {
+            pak.write_file(path, data.to_vec()).unwrap();
+        }
+        pak.write_index().unwrap();
+        buf.into_inner()
+    }
+
+    // deferred assets, patched after every mod is added
+    const ESCAPE_MENU_UASSET: &str = "FSD/Content/UI/Menu_EscapeMenu/MENU_EscapeMenu.uasset";
+    const ESCAPE_MENU_UEXP: &str = "FSD/Content/UI/Menu_EscapeMenu/MENU_EscapeMenu.uexp";
+
+    /// Contents of `path` in the pak of a mod it is taken from, checking no other pak supplies it.
+    fn taken(mod_paks: &mut [ModPak], path: &str) -> Option<Vec<u8>> {
+        let mut suppliers = mod_paks
+            .iter_mut()
+            .filter_map(|p| {
+                let (_, pak_path) = p.files.iter().find(|(n, _)| n.as_str() == path)?;
+                Some(p.pak.get(pak_path, &mut p.buf).unwrap())
+            })
+            .collect::<Vec<_>>();
+        assert!(suppliers.len() <= 1, "{path} taken from multiple paks");
+        suppliers.pop()
+    }
+
+    #[test]
+    fn test_later_pak_overrides_and_disabled_pak_is_skipped() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(vec![]));
+        for (name, pak) in [
+            (
+                "Mod/A.pak",
+                build_pak(&[
+                    ("FSD/Content/Shared.uexp", b"a".as_slice()),
+                    ("FSD/Content/OnlyA.uexp", b"a".as_slice()),
+                    (ESCAPE_MENU_UASSET, b"a".as_slice()),
+                    (ESCAPE_MENU_UEXP, b"a".as_slice()),
+                ]),
+            ),
+            (
+                "Mod/B.pak",
+                build_pak(&[
+                    ("FSD/Content/Shared.uexp", b"b".as_slice()),
+                    (ESCAPE_MENU_UASSET, b"b".as_slice()),
+                    (ESCAPE_MENU_UEXP, b"b".as_slice()),
+                ]),
+            ),
+        ] {
+            zip.start_file(name, Default::default()).unwrap();
+            zip.write_all(&pak).unwrap();
+        }
+        let zip = zip.finish().unwrap().into_inner();
+
+        let spec = ModSpecification::new("multi-pak.zip".to_string());
+        let mod_info = ModInfo {
+            provider: "file",
+            name: "multi-pak".to_string(),
+            spec: spec.clone(),
+            versions: vec![],
+            resolution: ModResolution::unresolvable(
+                spec.url.clone().into(),
+                "multi-pak".to_string(),
+            ),
+            suggested_require: false,
+            suggested_dependencies: vec![],
+            modio_tags: None,
+            modio_id: None,
+        };
+        let paks = || get_paks_from_data(Box::new(Cursor::new(zip.clone()))).unwrap();
+
+        let all = paks_by_precedence(&mod_info, paks(), &DisabledPaks::new());
+        assert_eq!(
+            vec![Some("Mod/B.pak".to_string()), Some("Mod/A.pak".to_string())],
+            all.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>()
+        );
+
+        // plain files and deferred assets are both taken from the later pak
+        let mut all = read_mod_paks(&mod_info, paks(), &DisabledPaks::new()).unwrap();
+        for path in [
+            "FSD/Content/Shared.uexp",
+            ESCAPE_MENU_UASSET,
+            ESCAPE_MENU_UEXP,
+        ] {
+            assert_eq!(Some(b"b".to_vec()), taken(&mut all, path), "{path}");
+        }
+        assert_eq!(
+            Some(b"a".to_vec()),
+            taken(&mut all, "FSD/Content/OnlyA.uexp")
+        );
+
+        let disabled = DisabledPaks::from([(spec, BTreeSet::from(["Mod/B.pak".to_string()]))]);
+        let enabled = paks_by_precedence(&mod_info, paks(), &disabled);
+        assert_eq!(
+            vec![Some("Mod/A.pak".to_string())],
+            enabled
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>()
+        );
+        let mut enabled = read_mod_paks(&mod_info, paks(), &disabled).unwrap();
+        for path in [
+            "FSD/Content/Shared.uexp",
+            ESCAPE_MENU_UASSET,
+            ESCAPE_MENU_UEXP,
+        ] {
+            assert_eq!(Some(b"a".to_vec()), taken(&mut enabled, path), "{path}");
+        }
+    }
+}
@@ -1,24 +1,36 @@
 #![feature(let_chains)]
 #![feature(if_let_guard)]
 
+pub mod archive;
+pub mod doctor;
 pub mod gui;
 pub mod integrate;
 pub mod mod_lints;
+pub mod modpack;
 pub mod providers;
 pub mod state;
+pub mod status;
+pub mod subscriptions;
+pub mod watch;
 
 use std::ops::Deref;
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
 use directories::ProjectDirs;
 use fs_err as fs;
-use integrate::IntegrationError;
-use providers::{ModResolution, ModSpecification, ProviderError, ProviderFactory};
+use integrate::{DisabledPaks, IntegrationError, IntegrationPlan};
+use mint_lib::mod_info::MetaConfig;
+use mod_lints::{run_lints, LintId, LintReport};
+use modpack::ModpackError;
+use providers::modio::{ModioSearch, ModioSearchResult};
+use providers::{ModInfo, ModResolution, ModSpecification, ProviderError, ProviderFactory};
 use snafu::prelude::*;
+use state::lockfile::{verify_locked, LockedMod, Lockfile_v0_0_0 as Lockfile};
 use state::{State, StateError};
+use subscriptions::{SubscriptionDiff, SyncDirection};
 use tracing::*;
 
 #[derive(Debug, Snafu)]
@@ -37,6 +49,8 @@ pub enum MintError {
     },
     #[snafu(transparent)]
     StateError { source: StateError },
+    #[snafu(transparent)]
+    ModpackError { source: ModpackError },
     #[snafu(display("invalid DRG pak path: {path}"))]
     InvalidDrgPak { path: String },
 }
@@ -101,10 +115,29 @@ pub fn is_drg_pak<P: AsRef<Path>>(path: P) -> Result<(), MintError> {
     Ok(())
 }
 
+/// Install the mod bundle into the game installation of `game_path`, or only write it to `output`
+/// if set.
+fn integrate_mods<P: AsRef<Path>>(
+    game_path: P,
+    output: Option<&Path>,
+    config: MetaConfig,
+    mods: Vec<(ModInfo, PathBuf)>,
+    disabled_paks: &DisabledPaks,
+) -> Result<(), IntegrationError> {
+    match output {
+        Some(output) => {
+            integrate::integrate_to_path(game_path, output, config, &mods, disabled_paks)
+        }
+        None => integrate::integrate(game_path, config, mods, disabled_paks),
+    }
+}
+
 pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
     game_path: P,
+    output: Option<&Path>,
     state: &State,
     mod_specs: &[ModSpecification],
+    disabled_paks: &DisabledPaks,
     update: bool,
 ) -> Result<(), IntegrationError> {
     let mods = state.store.resolve_mods(mod_specs, update).await?;
@@ -143,10 +176,12 @@ pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
     info!("fetching mods...");
     let paths = state.store.fetch_mods(&urls, update, None).await?;
 
-    integrate::integrate(
+    integrate_mods(
         game_path,
+        output,
         state.config.deref().into(),
         to_integrate.into_iter().zip(paths).collect(),
+        &integrate::resolve_disabled_paks(disabled_paks, &mods),
     )
 }
 
@@ -200,8 +235,10 @@ pub async fn resolve_ordered(
 
 pub async fn resolve_unordered_and_integrate_with_provider_init<P, F>(
     game_path: P,
+    output: Option<&Path>,
     state: &mut State,
     mod_specs: &[ModSpecification],
+    disabled_paks: &DisabledPaks,
     update: bool,
     init: F,
 ) -> Result<(), MintError>
@@ -210,7 +247,16 @@ where
     F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
 {
     loop {
-        match resolve_unordered_and_integrate(&game_path, state, mod_specs, update).await {
+        match resolve_unordered_and_integrate(
+            &game_path,
+            output,
+            state,
+            mod_specs,
+            disabled_paks,
+            update,
+        )
+        .await
+        {
             Ok(()) => return Ok(()),
             Err(ref e)
                 if let IntegrationError::ProviderError { ref source } = e
@@ -246,3 +292,363 @@ where
         }
     }
 }
+
+pub async fn resolve_mods_with_provider_init<F>(
+    state: &mut State,
+    mod_specs: &[ModSpecification],
+    update: bool,
+    init: F,
+) -> Result<HashMap<ModSpecification, ModInfo>, MintError>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    loop {
+        match state.store.resolve_mods(mod_specs, update).await {
+            Ok(mods) => return Ok(mods),
+            Err(ProviderError::NoProvider { url, factory }) => init(state, url, factory)?,
+            Err(e) => Err(e)?,
+        }
+    }
+}
+
+pub async fn search_with_provider_init<F>(
+    state: &mut State,
+    query: &ModioSearch,
+    init: F,
+) -> Result<Vec<ModioSearchResult>, MintError>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    loop {
+        match state.store.search(query).await {
+            Ok(results) => return Ok(results),
+            Err(ProviderError::NoProvider { url, factory }) => init(state, url, factory)?,
+            Err(e) => Err(e)?,
+        }
+    }
+}
+
+/// Compare the mod.io subscriptions with the enabled mod.io mods of `profile`, see
+/// [`subscriptions::fetch_diff`].
+pub async fn subscription_diff_with_provider_init<F>(
+    state: &mut State,
+    profile: &str,
+    init: F,
+) -> Result<SubscriptionDiff, MintError>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    state.mod_data.get_profile(profile)?;
+    let mods = subscriptions::profile_modio_mods(&state.mod_data, profile);
+    loop {
+        match subscriptions::fetch_diff(&state.store, &mods).await {
+            Ok(diff) => return Ok(diff),
+            Err(ProviderError::NoProvider { url, factory }) => init(state, url, factory)?,
+            Err(e) => Err(e)?,
+        }
+    }
+}
+
+/// Apply a diff returned by [`subscription_diff_with_provider_init`]. Pulling enables the
+/// subscribed mods `profile` contains disabled and adds the others and their dependencies,
+/// pushing subscribes to and unsubscribes from mods on mod.io.
+pub async fn sync_subscriptions_with_provider_init<F>(
+    state: &mut State,
+    profile: &str,
+    diff: &SubscriptionDiff,
+    direction: SyncDirection,
+    init: F,
+) -> Result<(), MintError>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    match direction {
+        SyncDirection::Pull => {
+            let specs = diff.specs_to_add();
+            if !specs.is_empty() {
+                let resolved = resolve_mods_with_provider_init(state, &specs, false, init).await?;
+                state
+                    .mod_data
+                    .add_resolved_mods(profile, &specs, resolved, false)?;
+            }
+            state
+                .mod_data
+                .enable_mods(profile, &diff.specs_to_enable())?;
+            state.mod_data.save()?;
+        }
+        SyncDirection::Push => loop {
+            match subscriptions::push(&state.store, diff).await {
+                Ok(()) => break,
+                Err(ProviderError::NoProvider { url, factory }) => init(state, url, factory)?,
+                Err(e) => Err(e)?,
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Resolve and fetch mods like [`resolve_unordered_and_integrate_with_provider_init`] but only
+/// return what integrating them would write, see [`integrate::plan_integration`].
+pub async fn resolve_and_plan_with_provider_init<P, F>(
+    game_path: P,
+    state: &mut State,
+    mod_specs: &[ModSpecification],
+    disabled_paks: &DisabledPaks,
+    update: bool,
+    init: F,
+) -> Result<IntegrationPlan, MintError>
+where
+    P: AsRef<Path>,
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    let mods = resolve_mods_with_provider_init(state, mod_specs, update, init).await?;
+    let to_integrate = mod_specs
+        .iter()
+        .map(|spec| mods[spec].clone())
+        .collect::<Vec<_>>();
+    let resolutions = to_integrate
+        .iter()
+        .map(|m| &m.resolution)
+        .collect::<Vec<_>>();
+
+    info!("fetching mods...");
+    let paths = state.store.fetch_mods(&resolutions, update, None).await?;
+
+    Ok(integrate::plan_integration(
+        game_path,
+        &to_integrate.into_iter().zip(paths).collect::<Vec<_>>(),
+        &integrate::resolve_disabled_paks(disabled_paks, &mods),
+    )?)
+}
+
+/// Resolve and fetch mods like [`resolve_unordered_and_integrate_with_provider_init`], run
+/// `lints` on them and integrate them. Lint findings are returned instead of preventing the
+/// integration. Mods are not updated. Unless `backup` is set the backup of the previous bundle is
+/// left alone, see [`integrate::reintegrate`].
+pub async fn resolve_lint_and_integrate_with_provider_init<P, F>(
+    game_path: P,
+    state: &mut State,
+    mod_specs: &[ModSpecification],
+    disabled_paks: &DisabledPaks,
+    lints: &BTreeSet<LintId>,
+    backup: bool,
+    init: F,
+) -> Result<LintReport, MintError>
+where
+    P: AsRef<Path>,
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    let mods = resolve_mods_with_provider_init(state, mod_specs, false, init).await?;
+    let to_integrate = mod_specs
+        .iter()
+        .map(|spec| mods[spec].clone())
+        .collect::<Vec<_>>();
+    let resolutions = to_integrate
+        .iter()
+        .map(|m| &m.resolution)
+        .collect::<Vec<_>>();
+
+    info!("fetching mods...");
+    let paths = state.store.fetch_mods(&resolutions, false, None).await?;
+
+    let game_path = game_path.as_ref().to_path_buf();
+    let config: MetaConfig = state.config.deref().into();
+    let lint_mods = mod_specs
+        .iter()
+        .cloned()
+        .zip(paths.iter().cloned())
+        .collect();
+    let lints = lints.clone();
+    // lint mods are keyed by the given specifications, integrated ones by the resolved ones
+    let lint_disabled_paks = disabled_paks.clone();
+    let disabled_paks = integrate::resolve_disabled_paks(disabled_paks, &mods);
+    let report = tokio::task::spawn_blocking(move || {
+        let report = run_lints(
+            &lints,
+            lint_mods,
+            lint_disabled_paks,
+            Some(game_path.clone()),
+        )?;
+        let mods = to_integrate.into_iter().zip(paths).collect();
+        if backup {
+            integrate::integrate(game_path, config, mods, &disabled_paks)?;
+        } else {
+            integrate::reintegrate(game_path, config, mods, &disabled_paks)?;
+        }
+        Ok::<_, IntegrationError>(report)
+    })
+    .await
+    .map_err(IntegrationError::from)??;
+    Ok(report)
+}
+
+/// Resolve and fetch the enabled mods of a profile and record their exact versions in its
+/// lockfile. Mods already present in the existing lockfile are kept as-is unless `update` is set.
+pub async fn update_lockfile_with_provider_init<F>(
+    state: &mut State,
+    profile: &str,
+    update: bool,
+    init: F,
+) -> Result<(PathBuf, Lockfile), MintError>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    state.mod_data.get_profile(profile)?;
+
+    let mut mod_specs = Vec::new();
+    state.mod_data.for_each_enabled_mod(profile, |mc| {
+        mod_specs.push(mc.spec.clone());
+    });
+
+    let existing = if update {
+        None
+    } else {
+        state.read_lockfile(profile)?
+    };
+    let mut locked = BTreeMap::new();
+    let mut to_lock = Vec::new();
+    for spec in mod_specs {
+        match existing.as_ref().and_then(|l| l.get(&spec)) {
+            Some(entry) => {
+                locked.insert(spec.url, entry.clone());
+            }
+            None => to_lock.push(spec),
+        }
+    }
+
+    let mods = resolve_mods_with_provider_init(state, &to_lock, update, &init).await?;
+    let resolutions = to_lock
+        .iter()
+        .map(|spec| &mods[spec].resolution)
+        .collect::<Vec<_>>();
+
+    info!("fetching mods...");
+    let paths = state.store.fetch_mods(&resolutions, update, None).await?;
+
+    for ((spec, resolution), path) in to_lock.iter().zip(resolutions).zip(paths) {
+        locked.insert(
+            spec.url.clone(),
+            LockedMod {
+                resolution: resolution.clone(),
+                modfile_id: providers::modio::modfile_id(&resolution.url.0),
+                sha256: providers::hash_file(&path)?,
+            },
+        );
+    }
+
+    let lockfile = Lockfile {
+        profile: profile.to_string(),
+        mods: locked,
+    };
+    let path = state.write_lockfile(lockfile.clone())?;
+    Ok((path, lockfile))
+}
+
+/// Fetch the enabled mods of a profile at exactly the versions recorded in its lockfile, returning
+/// them with their paks disabled as expected by [`integrate::integrate`]. Mod info is read from
+/// the provider cache, so mods unpacked from a modpack do not require their provider to be
+/// configured, and only mods missing from the cache are resolved.
+pub async fn fetch_locked_mods(
+    state: &State,
+    profile: &str,
+) -> Result<(Vec<(ModInfo, PathBuf)>, DisabledPaks), MintError> {
+    state.mod_data.get_profile(profile)?;
+    let lockfile = state
+        .read_lockfile(profile)?
+        .ok_or_else(|| StateError::NoLockfile {
+            profile: profile.to_string(),
+        })?;
+
+    let mut mod_specs = Vec::new();
+    state.mod_data.for_each_enabled_mod(profile, |mc| {
+        mod_specs.push(mc.spec.clone());
+    });
+
+    let locked = mod_specs
+        .iter()
+        .map(|spec| {
+            lockfile.get(spec).ok_or_else(|| StateError::NotLocked {
+                profile: profile.to_string(),
+                url: spec.url.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // mod info is only used for metadata, the resolution is overridden by the locked one
+    let mut mods = mod_specs
+        .iter()
+        .filter_map(|spec| Some((spec.clone(), state.store.get_cached_mod_info(spec)?)))
+        .collect::<HashMap<_, _>>();
+    let uncached = mod_specs
+        .iter()
+        .filter(|spec| !mods.contains_key(spec))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !uncached.is_empty() {
+        mods.extend(state.store.resolve_mods(&uncached, false).await?);
+    }
+
+    let mut to_integrate = Vec::with_capacity(mod_specs.len());
+    for (spec, locked) in mod_specs.iter().zip(locked) {
+        let path = match state.store.get_blob_path(&locked.sha256) {
+            Some(path) => path,
+            None => {
+                info!("fetching locked mod {}", spec.url);
+                let path = state
+                    .store
+                    .fetch_mod(&locked.resolution, false, None)
+                    .await?;
+                verify_locked(spec, locked, &path)?;
+                path
+            }
+        };
+        let mut info = mods[spec].clone();
+        info.resolution = locked.resolution.clone();
+        to_integrate.push((info, path));
+    }
+
+    let disabled_paks =
+        integrate::resolve_disabled_paks(&state.mod_data.disabled_paks(profile), &mods);
+    Ok((to_integrate, disabled_paks))
+}
+
+/// Integrate the enabled mods of a profile using exactly the versions recorded in its lockfile,
+/// see [`fetch_locked_mods`].
+pub async fn resolve_locked_and_integrate<P: AsRef<Path>>(
+    game_path: P,
+    output: Option<&Path>,
+    state: &State,
+    profile: &str,
+) -> Result<(), MintError> {
+    let (to_integrate, disabled_paks) = fetch_locked_mods(state, profile).await?;
+    integrate_mods(
+        game_path,
+        output,
+        state.config.deref().into(),
+        to_integrate,
+        &disabled_paks,
+    )?;
+    Ok(())
+}
+
+pub async fn resolve_locked_and_integrate_with_provider_init<P, F>(
+    game_path: P,
+    output: Option<&Path>,
+    state: &mut State,
+    profile: &str,
+    init: F,
+) -> Result<(), MintError>
+where
+    P: AsRef<Path>,
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    loop {
+        match resolve_locked_and_integrate(&game_path, output, state, profile).await {
+            Ok(()) => return Ok(()),
+            Err(MintError::ProviderError {
+                source: ProviderError::NoProvider { url, factory },
+            }) => init(state, url, factory)?,
+            Err(e) => Err(e)?,
+        }
+    }
+}
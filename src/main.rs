@@ -1,16 +1,32 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
-use anyhow::{anyhow, Context, Result};
-use clap::{Parser, Subcommand};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use mint_lib::error::ResultExt as _;
 use tracing::{debug, info};
 
-use mint::mod_lints::{run_lints, LintId};
-use mint::providers::ProviderFactory;
+use mint::doctor::{diagnose, Severity};
+use mint::integrate::{list_archive_paks, rollback, uninstall, IntegrationPlan};
+use mint::mod_lints::{run_lints, LintFinding, LintId};
+use mint::modpack::{pack_profile_with_provider_init, unpack_profile};
+use mint::providers::modio::{ModfileUpdate, ModioSearch, ModioSort};
+use mint::providers::{ModStore, ProviderError, ProviderFactory, ProviderParameter};
+use mint::state::profile_export::ProfileExport_v0_0_0 as ProfileExport;
+use mint::state::{ModConfig, ModOrGroup};
+use mint::status::{compare, installed_meta};
+use mint::subscriptions::SyncDirection;
+use mint::watch::{local_mod_paths, LocalModWatcher, POLL_INTERVAL};
 use mint::{gui::gui, providers::ModSpecification, state::State};
 use mint::{
-    resolve_ordered_with_provider_init, resolve_unordered_and_integrate_with_provider_init, Dirs,
-    MintError,
+    resolve_and_plan_with_provider_init, resolve_lint_and_integrate_with_provider_init,
+    resolve_locked_and_integrate_with_provider_init, resolve_mods_with_provider_init,
+    resolve_ordered_with_provider_init, resolve_unordered_and_integrate_with_provider_init,
+    search_with_provider_init, subscription_diff_with_provider_init,
+    sync_subscriptions_with_provider_init, update_lockfile_with_provider_init, Dirs, MintError,
 };
 
 /// Command line integration tool.
@@ -29,13 +45,28 @@ struct ActionIntegrate {
 
     /// Paths of mods to integrate
     ///
-    /// Can be a file path or URL to a .pak or .zip file or a URL to a mod on https://mod.io/g/drg
+    /// Can be a file path or URL to a .pak or an archive (.zip, .7z, .tar, .tar.gz, .tar.xz)
+    /// containing one or a URL to a mod on https://mod.io/g/drg
     /// Examples:
     ///     ./local/path/test-mod.pak
     ///     https://mod.io/g/drg/m/custom-difficulty
     ///     https://example.org/some-online-mod-repository/public-mod.zip
     #[arg(short, long, num_args=0.., verbatim_doc_comment)]
     mods: Vec<String>,
+
+    /// Write the mod bundle to this file instead of installing it into the game directory of
+    /// --fsd-pak. The hook is not installed, so the game pak can be a copy outside of an
+    /// installation.
+    #[arg(short, long, conflicts_with = "dry_run")]
+    output: Option<PathBuf>,
+
+    /// Print which mod each file of the mod bundle would come from instead of integrating.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Print the --dry-run plan as JSON.
+    #[arg(long, requires = "dry_run")]
+    json: bool,
 }
 
 /// Integrate a profile
@@ -49,13 +80,73 @@ struct ActionIntegrateProfile {
 
     /// Update mods. By default all mods and metadata are cached offline so this is necessary to
     /// check for updates.
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with = "locked")]
     update: bool,
 
+    /// Integrate exactly the mod versions recorded in the profile's lockfile (see `mint lock`).
+    #[arg(long)]
+    locked: bool,
+
+    /// Write the mod bundle to this file instead of installing it into the game directory of
+    /// --fsd-pak. The hook is not installed, so the game pak can be a copy outside of an
+    /// installation.
+    #[arg(short, long, conflicts_with = "dry_run")]
+    output: Option<PathBuf>,
+
+    /// Print which mod each file of the mod bundle would come from instead of integrating.
+    #[arg(long, conflicts_with = "locked")]
+    dry_run: bool,
+
+    /// Print the --dry-run plan as JSON.
+    #[arg(long, requires = "dry_run")]
+    json: bool,
+
     /// Profile to integrate.
     profile: String,
 }
 
+/// Create or refresh the lockfile of a profile
+///
+/// The lockfile records the exact version and SHA-256 of every enabled mod in the profile so
+/// `mint profile --locked` integrates the same bytes on every machine. Mods already in the
+/// lockfile are kept unless --update is passed.
+#[derive(Parser, Debug)]
+struct ActionLock {
+    /// Re-resolve all mods, picking up new versions of unpinned mods.
+    #[arg(short, long)]
+    update: bool,
+
+    /// Profile to lock.
+    profile: String,
+}
+
+/// Lock a profile and bundle it with its enabled mods and their metadata into a single archive
+/// that can be unpacked and integrated on a machine without network access.
+#[derive(Parser, Debug)]
+struct ActionPackProfile {
+    /// Profile to pack.
+    profile: String,
+
+    /// Path of the archive to write.
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+/// Import a profile packed with `pack-profile`, adding its mods to the cache. The imported profile
+/// can then be integrated offline with `mint profile --locked`.
+#[derive(Parser, Debug)]
+struct ActionUnpackProfile {
+    file: PathBuf,
+
+    /// Name to import the profile as instead of the name stored in the archive.
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Replace an existing profile of the same name.
+    #[arg(long)]
+    replace: bool,
+}
+
 /// Launch via steam
 #[derive(Parser, Debug)]
 struct ActionLaunch {
@@ -71,16 +162,409 @@ struct ActionLint {
     #[arg(short, long)]
     fsd_pak: Option<PathBuf>,
 
+    /// Output format of the lint report.
+    #[arg(long, value_enum, default_value_t = LintFormat::Human)]
+    format: LintFormat,
+
+    /// Only run the given lint. Can be specified multiple times. By default all lints except
+    /// `unmodified_game_assets` are run.
+    #[arg(long = "lint", value_name = "LINT")]
+    lints: Vec<LintId>,
+
+    /// Skip the given lint. Can be specified multiple times.
+    #[arg(long = "skip-lint", value_name = "LINT")]
+    skip_lints: Vec<LintId>,
+
     /// Profile to lint.
     profile: String,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LintFormat {
+    /// Human readable list of findings
+    Human,
+    /// The full lint report as JSON
+    Json,
+    /// SARIF 2.1.0 log
+    Sarif,
+}
+
+/// Watch the local files and directories of a profile and lint and integrate it whenever they
+/// change
+///
+/// The profile is integrated once on start. Only the mods that are local files or directories
+/// when watching starts are watched, restart to pick up mods added to the profile later.
+#[derive(Parser, Debug)]
+struct ActionWatch {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Milliseconds the files have to stay unchanged before the profile is integrated again.
+    #[arg(long, default_value_t = 1000)]
+    debounce: u64,
+
+    /// Only run the given lint. Can be specified multiple times. By default all lints except
+    /// `unmodified_game_assets` are run.
+    #[arg(long = "lint", value_name = "LINT")]
+    lints: Vec<LintId>,
+
+    /// Skip the given lint. Can be specified multiple times.
+    #[arg(long = "skip-lint", value_name = "LINT")]
+    skip_lints: Vec<LintId>,
+
+    /// Profile to watch. Defaults to the active profile.
+    profile: Option<String>,
+}
+
+/// Check the game installation and the installed mod bundle for common problems
+#[derive(Parser, Debug)]
+struct ActionDoctor {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Print the findings as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+/// Show the installed mod bundle and whether it matches a profile, i.e. whether changes to the
+/// profile still need to be applied
+#[derive(Parser, Debug)]
+struct ActionStatus {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Profile to compare the installed mod bundle with. Defaults to the active profile.
+    profile: Option<String>,
+}
+
+/// Restore the mod bundle that was installed before the last integration. Running it again
+/// undoes the rollback.
+#[derive(Parser, Debug)]
+struct ActionRollback {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+}
+
+/// Remove mods_P.pak and the hook from the game installation, optionally restoring the official
+/// mod.io integration
+#[derive(Parser, Debug)]
+struct ActionUninstall {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Re-enable the official mod.io integration. All installed mod.io mods are disabled except
+    /// those given by --modio-id or --profile.
+    #[arg(long)]
+    restore_modio: bool,
+
+    /// mod.io ID of a mod to enable in the official integration. Can be specified multiple times.
+    /// Implies --restore-modio.
+    #[arg(long = "modio-id", value_name = "ID")]
+    modio_ids: Vec<u32>,
+
+    /// Enable the mod.io mods enabled in this profile in the official integration. Implies
+    /// --restore-modio.
+    #[arg(long)]
+    profile: Option<String>,
+}
+
+/// Manage provider parameters such as the mod.io OAuth token
+#[derive(Parser, Debug)]
+struct ActionProvider {
+    #[command(subcommand)]
+    action: ProviderAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum ProviderAction {
+    List(ProviderList),
+    Set(ProviderSet),
+}
+
+/// List providers, their parameters and the environment variables they can be supplied through
+#[derive(Parser, Debug)]
+struct ProviderList {}
+
+/// Store a provider parameter in the config
+#[derive(Parser, Debug)]
+struct ProviderSet {
+    /// Provider ID, as shown by `provider list`
+    provider: String,
+
+    /// Parameter ID, as shown by `provider list`
+    parameter: String,
+
+    /// Value of the parameter. Prompted for if omitted so it does not end up in the shell
+    /// history, use - to read it from stdin instead.
+    value: Option<String>,
+}
+
+/// Manage profiles
+#[derive(Parser, Debug)]
+struct ActionProfiles {
+    #[command(subcommand)]
+    action: ProfilesAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfilesAction {
+    List(ProfilesList),
+    Create(ProfilesCreate),
+    Rename(ProfilesRename),
+    Copy(ProfilesCopy),
+    Delete(ProfilesDelete),
+    AddMod(ProfilesAddMod),
+    RemoveMod(ProfilesRemoveMod),
+    /// Enable mods in a profile
+    Enable(ProfilesSetEnabled),
+    /// Disable mods in a profile
+    Disable(ProfilesSetEnabled),
+    SetPriority(ProfilesSetPriority),
+    Paks(ProfilesPaks),
+    Export(ProfilesExport),
+    Import(ProfilesImport),
+}
+
+/// List all profiles, or the mods of a profile if one is specified
+#[derive(Parser, Debug)]
+struct ProfilesList {
+    profile: Option<String>,
+}
+
+/// Create a new empty profile
+#[derive(Parser, Debug)]
+struct ProfilesCreate {
+    profile: String,
+}
+
+/// Rename a profile
+#[derive(Parser, Debug)]
+struct ProfilesRename {
+    profile: String,
+    new_name: String,
+}
+
+/// Copy a profile under a new name
+#[derive(Parser, Debug)]
+struct ProfilesCopy {
+    profile: String,
+    new_name: String,
+}
+
+/// Delete a profile
+#[derive(Parser, Debug)]
+struct ProfilesDelete {
+    profile: String,
+}
+
+/// Export a profile including its groups to a portable JSON file
+#[derive(Parser, Debug)]
+struct ProfilesExport {
+    profile: String,
+
+    /// File to write the profile to. Printed to stdout if not specified.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Import a profile previously exported with `profiles export`
+///
+/// If a profile with the same name already exists the imported profile is renamed unless
+/// --replace is passed. Providers required by the imported mods are set up if missing.
+#[derive(Parser, Debug)]
+struct ProfilesImport {
+    file: PathBuf,
+
+    /// Name to import the profile as instead of the name stored in the file.
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Replace an existing profile of the same name.
+    #[arg(long)]
+    replace: bool,
+}
+
+/// Resolve and add mods (and any missing dependencies) to a profile
+#[derive(Parser, Debug)]
+struct ProfilesAddMod {
+    profile: String,
+
+    /// Mods to add, see `integrate --help` for accepted formats
+    #[arg(required = true)]
+    mods: Vec<String>,
+}
+
+/// Remove mods from a profile
+#[derive(Parser, Debug)]
+struct ProfilesRemoveMod {
+    profile: String,
+
+    /// URLs of the mods to remove, as shown by `profiles list <profile>`
+    #[arg(required = true)]
+    mods: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ProfilesSetEnabled {
+    profile: String,
+
+    /// URLs of the mods, as shown by `profiles list <profile>`
+    #[arg(required = true)]
+    mods: Vec<String>,
+}
+
+/// Set load priority of a mod. In case of asset conflict, mods with higher priority take precedent.
+#[derive(Parser, Debug)]
+struct ProfilesSetPriority {
+    profile: String,
+
+    /// URL of the mod, as shown by `profiles list <profile>`
+    r#mod: String,
+
+    #[arg(allow_negative_numbers = true)]
+    priority: i32,
+}
+
+/// List the paks inside a mod's archive and choose which of them are integrated
+///
+/// Every pak of an archive is integrated by default, ordered by their paths with later paks
+/// overriding files of earlier ones.
+#[derive(Parser, Debug)]
+struct ProfilesPaks {
+    profile: String,
+
+    /// URL of the mod, as shown by `profiles list <profile>`
+    r#mod: String,
+
+    /// Paths of paks inside the archive to integrate again
+    #[arg(long, num_args = 1..)]
+    enable: Vec<String>,
+
+    /// Paths of paks inside the archive to leave out of the integration
+    #[arg(long, num_args = 1..)]
+    disable: Vec<String>,
+}
+
+/// Synchronize a profile with the mods subscribed to on mod.io for the official integration
+#[derive(Parser, Debug)]
+struct ActionSubscriptions {
+    #[command(subcommand)]
+    action: SubscriptionsAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum SubscriptionsAction {
+    /// Add the subscribed mods and their dependencies to a profile
+    Pull(SubscriptionsSync),
+    /// Subscribe to the enabled mod.io mods of a profile and unsubscribe from all other mods
+    Push(SubscriptionsSync),
+}
+
+/// The changes are listed and confirmed before they are applied.
+#[derive(Parser, Debug)]
+struct SubscriptionsSync {
+    profile: String,
+
+    /// Apply the changes without asking for confirmation
+    #[arg(short, long, conflicts_with = "dry_run")]
+    yes: bool,
+
+    /// Only list the changes
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Search mod.io for mods and optionally add them to a profile
+#[derive(Parser, Debug)]
+struct ActionSearch {
+    /// Text to search the mod names, summaries and descriptions for
+    text: Option<String>,
+
+    /// Only list mods with this tag, e.g. QoL, Gameplay, Audio, Visual, Framework or Verified.
+    /// Can be specified multiple times.
+    #[arg(short, long = "tag")]
+    tags: Vec<String>,
+
+    #[arg(short, long, value_enum, default_value_t)]
+    sort: SearchSort,
+
+    /// Page of results to show, starting at 1
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    page: u64,
+
+    /// Number of results per page
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u64).range(1..=100))]
+    page_size: u64,
+
+    /// Add results to this profile. Which ones is asked interactively unless --pick is given.
+    #[arg(long, value_name = "PROFILE")]
+    add: Option<String>,
+
+    /// Numbers of the results to add, as printed in front of them
+    #[arg(long, requires = "add", num_args = 1..)]
+    pick: Vec<usize>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+enum SearchSort {
+    #[default]
+    Popular,
+    Downloads,
+    Rating,
+    Subscribers,
+    Newest,
+    Updated,
+    Name,
+}
+
+impl From<SearchSort> for ModioSort {
+    fn from(value: SearchSort) -> Self {
+        match value {
+            SearchSort::Popular => ModioSort::Popular,
+            SearchSort::Downloads => ModioSort::Downloads,
+            SearchSort::Rating => ModioSort::Rating,
+            SearchSort::Subscribers => ModioSort::Subscribers,
+            SearchSort::Newest => ModioSort::Newest,
+            SearchSort::Updated => ModioSort::Updated,
+            SearchSort::Name => ModioSort::Name,
+        }
+    }
+}
+
 #[derive(Subcommand, Debug)]
 enum Action {
+    Search(ActionSearch),
+    Subscriptions(ActionSubscriptions),
     Integrate(ActionIntegrate),
     Profile(ActionIntegrateProfile),
     Launch(ActionLaunch),
     Lint(ActionLint),
+    Profiles(ActionProfiles),
+    Uninstall(ActionUninstall),
+    Rollback(ActionRollback),
+    Doctor(ActionDoctor),
+    Status(ActionStatus),
+    Watch(ActionWatch),
+    Provider(ActionProvider),
+    Lock(ActionLock),
+    PackProfile(ActionPackProfile),
+    UnpackProfile(ActionUnpackProfile),
 }
 
 #[derive(Parser, Debug)]
@@ -92,9 +576,19 @@ struct Args {
     /// Location to store configs and data
     #[arg(long)]
     appdata: Option<PathBuf>,
+
+    /// Provider parameter to use for this invocation without storing it, e.g.
+    /// `modio.oauth=<token>`. Equivalent to setting the environment variable listed by
+    /// `provider list`. Can be specified multiple times.
+    #[arg(
+        long = "provider-param",
+        value_name = "PROVIDER.PARAMETER=VALUE",
+        global = true
+    )]
+    provider_params: Vec<String>,
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     #[cfg(target_os = "windows")]
     {
         // Try to enable ANSI code support on Windows 10 for console. If it fails, then whatever
@@ -102,7 +596,7 @@ fn main() -> Result<()> {
         let _res = ansi_term::enable_ansi_support();
     }
 
-    let args = Args::parse();
+    let mut args = Args::parse();
 
     let dirs = args
         .appdata
@@ -111,6 +605,8 @@ fn main() -> Result<()> {
         .unwrap_or_else(Dirs::default_xdg)?;
 
     std::env::set_var("RUST_BACKTRACE", "1");
+    // taken out of args so the values are not logged
+    set_provider_params(std::mem::take(&mut args.provider_params))?;
 
     let _guard = mint_lib::setup_logging(dirs.data_dir.join("mint.log"), "mint")?;
     debug!("logging setup complete");
@@ -128,29 +624,68 @@ fn main() -> Result<()> {
     match args.action {
         Some(Action::Integrate(action)) => rt.block_on(async {
             action_integrate(dirs, action).await?;
-            Ok(())
+            Ok(ExitCode::SUCCESS)
         }),
         Some(Action::Profile(action)) => rt.block_on(async {
             action_integrate_profile(dirs, action).await?;
-            Ok(())
+            Ok(ExitCode::SUCCESS)
         }),
         Some(Action::Launch(action)) => {
             std::thread::spawn(move || {
                 rt.block_on(std::future::pending::<()>());
             });
             gui(dirs, Some(action.args))?;
-            Ok(())
+            Ok(ExitCode::SUCCESS)
         }
-        Some(Action::Lint(action)) => rt.block_on(async {
-            action_lint(dirs, action).await?;
-            Ok(())
+        Some(Action::Lint(action)) => rt.block_on(action_lint(dirs, action)),
+        Some(Action::Profiles(action)) => rt.block_on(async {
+            action_profiles(dirs, action).await?;
+            Ok(ExitCode::SUCCESS)
+        }),
+        Some(Action::Lock(action)) => rt.block_on(async {
+            action_lock(dirs, action).await?;
+            Ok(ExitCode::SUCCESS)
+        }),
+        Some(Action::PackProfile(action)) => rt.block_on(async {
+            action_pack_profile(dirs, action).await?;
+            Ok(ExitCode::SUCCESS)
+        }),
+        Some(Action::UnpackProfile(action)) => {
+            action_unpack_profile(dirs, action)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Action::Uninstall(action)) => {
+            action_uninstall(dirs, action)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Action::Rollback(action)) => {
+            action_rollback(dirs, action)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Action::Doctor(action)) => action_doctor(dirs, action),
+        Some(Action::Status(action)) => {
+            action_status(dirs, action)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Action::Watch(action)) => rt.block_on(action_watch(dirs, action)),
+        Some(Action::Search(action)) => rt.block_on(async {
+            action_search(dirs, action).await?;
+            Ok(ExitCode::SUCCESS)
+        }),
+        Some(Action::Subscriptions(action)) => rt.block_on(async {
+            action_subscriptions(dirs, action).await?;
+            Ok(ExitCode::SUCCESS)
         }),
+        Some(Action::Provider(action)) => {
+            action_provider(dirs, action)?;
+            Ok(ExitCode::SUCCESS)
+        }
         None => {
             std::thread::spawn(move || {
                 rt.block_on(std::future::pending::<()>());
             });
             gui(dirs, None)?;
-            Ok(())
+            Ok(ExitCode::SUCCESS)
         }
     }
 }
@@ -163,23 +698,83 @@ fn init_provider(
 ) -> Result<(), MintError> {
     info!("initializing provider for {:?}", url);
 
-    let params = state
+    let mut params = state
         .config
         .provider_parameters
-        .entry(factory.id.to_owned())
-        .or_default();
+        .get(factory.id)
+        .cloned()
+        .unwrap_or_default();
+    params.extend(factory.parameters_from_env());
     for p in factory.parameters {
-        if !params.contains_key(p.name) {
+        if !p.optional && !params.contains_key(p.id) {
+            if !std::io::stdin().is_terminal() {
+                return Err(ProviderError::MissingParameter {
+                    id: factory.id,
+                    parameter: p.id,
+                    description: p.description,
+                    env_var: factory.parameter_env_var(p),
+                }
+                .into());
+            }
             // this blocks but since we're calling it on the main thread it'll be fine
-            let value =
-                dialoguer::Password::with_theme(&dialoguer::theme::ColorfulTheme::default())
-                    .with_prompt(p.description)
-                    .interact()
-                    .unwrap();
+            let value = prompt_parameter(p)?;
+            state
+                .config
+                .provider_parameters
+                .entry(factory.id.to_owned())
+                .or_default()
+                .insert(p.id.to_owned(), value.clone());
             params.insert(p.id.to_owned(), value);
         }
     }
-    Ok(state.store.add_provider(factory, params)?)
+    Ok(state.store.add_provider(factory, &params)?)
+}
+
+fn prompt_parameter(parameter: &ProviderParameter) -> Result<String, MintError> {
+    let theme = dialoguer::theme::ColorfulTheme::default();
+    let value = if parameter.secret {
+        dialoguer::Password::with_theme(&theme)
+            .with_prompt(parameter.description)
+            .interact()
+    } else {
+        dialoguer::Input::<String>::with_theme(&theme)
+            .with_prompt(parameter.description)
+            .interact_text()
+    };
+    Ok(value.with_generic(|e| format!("failed to read parameter: {e}"))?)
+}
+
+fn find_provider_parameter(
+    id: &str,
+    parameter: &str,
+) -> Result<(
+    &'static ProviderFactory,
+    &'static ProviderParameter<'static>,
+)> {
+    let factory = ProviderFactory::find(id).with_context(|| format!("unknown provider {id:?}"))?;
+    let parameter = factory
+        .parameters
+        .iter()
+        .find(|p| p.id == parameter)
+        .with_context(|| format!("provider {id:?} has no parameter {parameter:?}"))?;
+    Ok((factory, parameter))
+}
+
+/// Export `--provider-param PROVIDER.PARAMETER=VALUE` as the environment variable the provider
+/// reads the parameter from.
+fn set_provider_params(params: Vec<String>) -> Result<()> {
+    for param in params {
+        let (key, value) = param
+            .split_once('=')
+            // the value is not included as it is usually a secret
+            .context("expected --provider-param PROVIDER.PARAMETER=VALUE")?;
+        let (id, parameter) = key
+            .split_once('.')
+            .with_context(|| format!("expected PROVIDER.PARAMETER, found {key:?}"))?;
+        let (factory, parameter) = find_provider_parameter(id, parameter)?;
+        std::env::set_var(factory.parameter_env_var(parameter), value);
+    }
+    Ok(())
 }
 
 fn get_pak_path(state: &State, arg: &Option<PathBuf>) -> Result<PathBuf> {
@@ -200,15 +795,41 @@ async fn action_integrate(dirs: Dirs, action: ActionIntegrate) -> Result<()> {
         .map(ModSpecification::new)
         .collect::<Vec<_>>();
 
-    resolve_unordered_and_integrate_with_provider_init(
+    let modfiles = state.store.modio_modfiles(&mod_specs);
+
+    if action.dry_run {
+        let plan = resolve_and_plan_with_provider_init(
+            game_pak_path,
+            &mut state,
+            &mod_specs,
+            &Default::default(),
+            action.update,
+            init_provider,
+        )
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+        print_plan(&plan, action.json)?;
+        if action.update && !action.json {
+            print_modfile_updates(&state.store.modio_updates_since(&mod_specs, &modfiles));
+        }
+        return Ok(());
+    }
+
+    let result = resolve_unordered_and_integrate_with_provider_init(
         game_pak_path,
+        action.output.as_deref(),
         &mut state,
         &mod_specs,
+        &Default::default(),
         action.update,
         init_provider,
     )
     .await
-    .map_err(|e| anyhow!("{}", e))
+    .map_err(|e| anyhow!("{}", e));
+    if action.update {
+        print_modfile_updates(&state.store.modio_updates_since(&mod_specs, &modfiles));
+    }
+    result
 }
 
 async fn action_integrate_profile(dirs: Dirs, action: ActionIntegrateProfile) -> Result<()> {
@@ -216,23 +837,202 @@ async fn action_integrate_profile(dirs: Dirs, action: ActionIntegrateProfile) ->
     let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
     debug!(?game_pak_path);
 
+    if action.locked {
+        return resolve_locked_and_integrate_with_provider_init(
+            game_pak_path,
+            action.output.as_deref(),
+            &mut state,
+            &action.profile,
+            init_provider,
+        )
+        .await
+        .map_err(|e| anyhow!("{}", e));
+    }
+
     let mut mods = Vec::new();
     state.mod_data.for_each_enabled_mod(&action.profile, |mc| {
         mods.push(mc.spec.clone());
     });
+    let disabled_paks = state.mod_data.disabled_paks(&action.profile);
+
+    let modfiles = state.store.modio_modfiles(&mods);
 
-    resolve_unordered_and_integrate_with_provider_init(
+    if action.dry_run {
+        let plan = resolve_and_plan_with_provider_init(
+            game_pak_path,
+            &mut state,
+            &mods,
+            &disabled_paks,
+            action.update,
+            init_provider,
+        )
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+        print_plan(&plan, action.json)?;
+        if action.update && !action.json {
+            print_modfile_updates(&state.store.modio_updates_since(&mods, &modfiles));
+        }
+        return Ok(());
+    }
+
+    let result = resolve_unordered_and_integrate_with_provider_init(
         game_pak_path,
+        action.output.as_deref(),
         &mut state,
         &mods,
+        &disabled_paks,
+        action.update,
+        init_provider,
+    )
+    .await
+    .map_err(|e| anyhow!("{}", e));
+    if action.update {
+        print_modfile_updates(&state.store.modio_updates_since(&mods, &modfiles));
+    }
+    result
+}
+
+/// Print the mods that got a newer modfile with the changelogs of the new modfiles.
+fn print_modfile_updates(updates: &[ModfileUpdate]) {
+    if updates.is_empty() {
+        return;
+    }
+    println!("updated mods:");
+    for update in updates {
+        println!(
+            "  {} ({}): {} -> {}",
+            update.name, update.spec.url, update.old_version, update.new_version
+        );
+        for entry in &update.changelogs {
+            println!("    {}:", entry.version);
+            match &entry.changelog {
+                Some(changelog) => {
+                    for line in changelog.lines() {
+                        println!("      {line}");
+                    }
+                }
+                None => println!("      (no changelog)"),
+            }
+        }
+    }
+}
+
+fn print_plan(plan: &IntegrationPlan, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(plan)?);
+        return Ok(());
+    }
+
+    println!("files:");
+    for (path, file) in &plan.files {
+        println!("  {path} <- {}", file.source);
+        for m in &file.overridden {
+            println!("    overrides {} <{}>", m.name, m.url);
+        }
+    }
+    println!("patched assets:");
+    for patched in &plan.patched {
+        println!(
+            "  {} ({}) <- {}",
+            patched.path, patched.patch, patched.source
+        );
+        for m in &patched.overridden {
+            println!("    overrides {} <{}>", m.name, m.url);
+        }
+    }
+    println!("asset registry entries:");
+    for entry in &plan.asset_registry {
+        println!("  {} <- {}", entry.path, entry.source.name);
+    }
+    Ok(())
+}
+
+async fn action_lock(dirs: Dirs, action: ActionLock) -> Result<()> {
+    let mut state = State::init(dirs)?;
+
+    let (path, lockfile) = update_lockfile_with_provider_init(
+        &mut state,
+        &action.profile,
         action.update,
         init_provider,
     )
     .await
-    .map_err(|e| anyhow!("{}", e))
+    .map_err(|e| anyhow!("{}", e))?;
+
+    for (url, locked) in &lockfile.mods {
+        println!("{} {url} -> {}", locked.sha256, locked.resolution.url.0);
+    }
+    println!("wrote {}", path.display());
+    Ok(())
 }
 
-async fn action_lint(dirs: Dirs, action: ActionLint) -> Result<()> {
+async fn action_pack_profile(dirs: Dirs, action: ActionPackProfile) -> Result<()> {
+    let mut state = State::init(dirs)?;
+
+    let manifest =
+        pack_profile_with_provider_init(&mut state, &action.profile, &action.output, init_provider)
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+
+    println!(
+        "packed {} mods of profile \"{}\" into {}",
+        manifest.mods.len(),
+        action.profile,
+        action.output.display()
+    );
+    Ok(())
+}
+
+fn action_unpack_profile(dirs: Dirs, action: ActionUnpackProfile) -> Result<()> {
+    let mut state = State::init(dirs)?;
+
+    let name = unpack_profile(
+        &mut state,
+        &action.file,
+        action.name.as_deref(),
+        action.replace,
+    )
+    .map_err(|e| anyhow!("{}", e))?;
+
+    println!("imported profile \"{name}\", integrate it with `mint profile --locked \"{name}\"`");
+    Ok(())
+}
+
+/// Lints selected by `--lint` and `--skip-lint`.
+fn selected_lints(lints: Vec<LintId>, skip_lints: &[LintId]) -> BTreeSet<LintId> {
+    let mut enabled_lints = if lints.is_empty() {
+        LintId::ALL
+            .into_iter()
+            .filter(|id| *id != LintId::UNMODIFIED_GAME_ASSETS)
+            .collect::<BTreeSet<_>>()
+    } else {
+        lints.into_iter().collect()
+    };
+    for id in skip_lints {
+        enabled_lints.remove(id);
+    }
+    enabled_lints
+}
+
+fn print_lint_findings(findings: &[LintFinding]) {
+    for finding in findings {
+        println!("{}[{}]: {}", finding.level, finding.lint, finding.message);
+        // single-mod findings already name the mod in their message
+        if finding.mods.len() > 1 {
+            for spec in &finding.mods {
+                println!("    {}", spec.url);
+            }
+        }
+        for path in &finding.paths {
+            println!("    {path}");
+        }
+    }
+    if findings.is_empty() {
+        println!("no lint findings");
+    }
+}
+
+async fn action_lint(dirs: Dirs, action: ActionLint) -> Result<ExitCode> {
     let mut state = State::init(dirs)?;
     let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
     debug!(?game_pak_path);
@@ -241,27 +1041,619 @@ async fn action_lint(dirs: Dirs, action: ActionLint) -> Result<()> {
     state.mod_data.for_each_mod(&action.profile, |mc| {
         mods.push(mc.spec.clone());
     });
+    let disabled_paks = state.mod_data.disabled_paks(&action.profile);
+
+    let enabled_lints = selected_lints(action.lints, &action.skip_lints);
+    debug!(?enabled_lints);
 
     let mod_paths = resolve_ordered_with_provider_init(&mut state, &mods, init_provider).await?;
 
     let report = tokio::task::spawn_blocking(move || {
         run_lints(
-            &BTreeSet::from([
-                LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
-                LintId::ASSET_REGISTRY_BIN,
-                LintId::CONFLICTING,
-                LintId::EMPTY_ARCHIVE,
-                LintId::OUTDATED_PAK_VERSION,
-                LintId::SHADER_FILES,
-                LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
-                LintId::NON_ASSET_FILES,
-                LintId::SPLIT_ASSET_PAIRS,
-            ]),
+            &enabled_lints,
             mods.into_iter().zip(mod_paths).collect(),
+            disabled_paks,
             Some(game_pak_path),
         )
     })
     .await??;
-    println!("{:#?}", report);
+
+    let findings = report.findings();
+    match action.format {
+        LintFormat::Human => print_lint_findings(&findings),
+        LintFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        LintFormat::Sarif => println!("{}", serde_json::to_string_pretty(&report.to_sarif())?),
+    }
+
+    Ok(if findings.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+async fn action_profiles(dirs: Dirs, action: ActionProfiles) -> Result<()> {
+    let mut state = State::init(dirs)?;
+
+    match action.action {
+        ProfilesAction::List(ProfilesList { profile: None }) => {
+            for name in state.mod_data.profiles.keys() {
+                let marker = if *name == state.mod_data.active_profile {
+                    "*"
+                } else {
+                    " "
+                };
+                println!("{marker} {name}");
+            }
+        }
+        ProfilesAction::List(ProfilesList {
+            profile: Some(profile),
+        }) => {
+            let print_mod = |mc: &ModConfig, indent: &str| {
+                let enabled = if mc.enabled { "x" } else { " " };
+                let name = state
+                    .store
+                    .get_mod_info(&mc.spec)
+                    .map(|info| format!(" ({})", info.name))
+                    .unwrap_or_default();
+                println!(
+                    "{indent}[{enabled}] {:>4} {}{name}",
+                    mc.priority, mc.spec.url
+                );
+            };
+            for m in &state.mod_data.get_profile(&profile)?.mods {
+                match m {
+                    ModOrGroup::Individual(mc) => print_mod(mc, ""),
+                    ModOrGroup::Group {
+                        group_name,
+                        enabled,
+                    } => {
+                        let enabled = if *enabled { "x" } else { " " };
+                        println!("[{enabled}] group {group_name}");
+                        if let Some(group) = state.mod_data.groups.get(group_name) {
+                            for mc in &group.mods {
+                                print_mod(mc, "    ");
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        ProfilesAction::Create(ProfilesCreate { profile }) => {
+            state.mod_data.create_profile(&profile)?;
+        }
+        ProfilesAction::Rename(ProfilesRename { profile, new_name }) => {
+            state.mod_data.rename_profile(&profile, &new_name)?;
+        }
+        ProfilesAction::Copy(ProfilesCopy { profile, new_name }) => {
+            state.mod_data.copy_profile(&profile, &new_name)?;
+        }
+        ProfilesAction::Delete(ProfilesDelete { profile }) => {
+            state.mod_data.delete_profile(&profile)?;
+        }
+        ProfilesAction::AddMod(ProfilesAddMod { profile, mods }) => {
+            state.mod_data.get_profile(&profile)?;
+            let specs = mods
+                .into_iter()
+                .map(ModSpecification::new)
+                .collect::<Vec<_>>();
+            let resolved =
+                resolve_mods_with_provider_init(&mut state, &specs, false, init_provider)
+                    .await
+                    .map_err(|e| anyhow!("{}", e))?;
+            state
+                .mod_data
+                .add_resolved_mods(&profile, &specs, resolved, false)?;
+        }
+        ProfilesAction::RemoveMod(ProfilesRemoveMod { profile, mods }) => {
+            for url in mods {
+                state.mod_data.remove_mod(&profile, &url)?;
+            }
+        }
+        ProfilesAction::Enable(ProfilesSetEnabled { profile, mods }) => {
+            for url in mods {
+                state.mod_data.get_mod_mut(&profile, &url)?.enabled = true;
+            }
+        }
+        ProfilesAction::Disable(ProfilesSetEnabled { profile, mods }) => {
+            for url in mods {
+                state.mod_data.get_mod_mut(&profile, &url)?.enabled = false;
+            }
+        }
+        ProfilesAction::SetPriority(ProfilesSetPriority {
+            profile,
+            r#mod,
+            priority,
+        }) => {
+            state.mod_data.get_mod_mut(&profile, &r#mod)?.priority = priority;
+        }
+        ProfilesAction::Paks(ProfilesPaks {
+            profile,
+            r#mod,
+            enable,
+            disable,
+        }) => {
+            let spec = state.mod_data.get_mod_mut(&profile, &r#mod)?.spec.clone();
+            let mods =
+                resolve_mods_with_provider_init(&mut state, &[spec.clone()], false, init_provider)
+                    .await
+                    .map_err(|e| anyhow!("{}", e))?;
+            let path = state
+                .store
+                .fetch_mod(&mods[&spec].resolution, false, None)
+                .await
+                .map_err(|e| anyhow!("{}", e))?;
+            let paks = list_archive_paks(&path).map_err(|e| anyhow!("{}", e))?;
+            ensure!(!paks.is_empty(), "{} is a single pak", spec.url);
+            for pak in enable.iter().chain(&disable) {
+                ensure!(
+                    paks.contains(pak),
+                    "{} does not contain pak {pak:?}",
+                    spec.url
+                );
+            }
+
+            let mc = state.mod_data.get_mod_mut(&profile, &r#mod)?;
+            for pak in enable {
+                mc.disabled_paks.remove(&pak);
+            }
+            mc.disabled_paks.extend(disable);
+            for pak in &paks {
+                let enabled = if mc.disabled_paks.contains(pak) {
+                    " "
+                } else {
+                    "x"
+                };
+                println!("[{enabled}] {pak}");
+            }
+        }
+        ProfilesAction::Export(ProfilesExport { profile, output }) => {
+            let export = state.mod_data.export_profile(&profile)?;
+            match output {
+                Some(path) => export.write(path)?,
+                None => println!("{}", export.to_string_pretty()?),
+            }
+        }
+        ProfilesAction::Import(ProfilesImport {
+            file,
+            name,
+            replace,
+        }) => {
+            let export = ProfileExport::read(&file)?;
+            let specs = export.specs();
+            let name = state
+                .mod_data
+                .import_profile(export, name.as_deref(), replace)?;
+            state.mod_data.save()?;
+            println!("imported profile \"{name}\"");
+
+            // make sure every provider the profile needs is configured
+            resolve_mods_with_provider_init(&mut state, &specs, false, init_provider)
+                .await
+                .map_err(|e| anyhow!("{}", e))?;
+        }
+    }
+
+    state.mod_data.save()?;
+    Ok(())
+}
+
+fn action_doctor(dirs: Dirs, action: ActionDoctor) -> Result<ExitCode> {
+    let state = State::init(dirs)?;
+    let game_pak_path = action
+        .fsd_pak
+        .as_ref()
+        .or(state.config.drg_pak_path.as_ref());
+    debug!(?game_pak_path);
+
+    let findings = diagnose(game_pak_path.map(PathBuf::as_path));
+
+    if action.json {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+    } else {
+        for finding in &findings {
+            println!(
+                "[{}] {}: {}",
+                finding.severity, finding.check, finding.message
+            );
+            if let Some(fix) = &finding.fix {
+                println!("    fix: {fix}");
+            }
+        }
+    }
+
+    Ok(if findings.iter().any(|f| f.severity == Severity::Error) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+async fn action_watch(dirs: Dirs, action: ActionWatch) -> Result<ExitCode> {
+    let mut state = State::init(dirs)?;
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+    debug!(?game_pak_path);
+
+    let profile = action
+        .profile
+        .unwrap_or_else(|| state.mod_data.active_profile.clone());
+    state
+        .mod_data
+        .get_profile(&profile)
+        .map_err(|e| anyhow!("{}", e))?;
+
+    let enabled_lints = selected_lints(action.lints, &action.skip_lints);
+    debug!(?enabled_lints);
+
+    let paths = local_mod_paths(&state, &profile);
+    ensure!(
+        !paths.is_empty(),
+        "profile {profile:?} has no enabled local mods to watch"
+    );
+    let mut watcher = LocalModWatcher::new(paths, Duration::from_millis(action.debounce));
+    println!("watching:");
+    for path in watcher.paths() {
+        println!("  {}", path.display());
+    }
+
+    let mut mods = Vec::new();
+    state.mod_data.for_each_enabled_mod(&profile, |mc| {
+        mods.push(mc.spec.clone());
+    });
+    let disabled_paks = state.mod_data.disabled_paks(&profile);
+
+    // only the first successful integration replaces the backup so it keeps the bundle from before
+    // watching
+    let mut backup = true;
+    loop {
+        println!("linting and integrating profile {profile:?}...");
+        let start = Instant::now();
+        match resolve_lint_and_integrate_with_provider_init(
+            &game_pak_path,
+            &mut state,
+            &mods,
+            &disabled_paks,
+            &enabled_lints,
+            backup,
+            init_provider,
+        )
+        .await
+        {
+            Ok(report) => {
+                backup = false;
+                print_lint_findings(&report.findings());
+                println!("integrated in {:.1}s", start.elapsed().as_secs_f32());
+            }
+            // keep watching, the next change may fix it
+            Err(e) => println!("integration failed: {e}"),
+        }
+        println!("waiting for changes...");
+
+        while !watcher.poll() {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+async fn action_search(dirs: Dirs, action: ActionSearch) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    if let Some(profile) = &action.add {
+        state.mod_data.get_profile(profile)?;
+    }
+
+    let query = ModioSearch {
+        text: action.text,
+        tags: action.tags,
+        sort: action.sort.into(),
+        page: action.page as usize - 1,
+        page_size: action.page_size as usize,
+    };
+    let results = search_with_provider_init(&mut state, &query, init_provider)
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+
+    if results.is_empty() {
+        println!("no mods found");
+        return Ok(());
+    }
+    for (i, result) in results.iter().enumerate() {
+        println!(
+            "{:>3}. [{:?}] {} {}",
+            i + 1,
+            result.modio_tags.approval_status,
+            result.name,
+            result.spec.url
+        );
+        if !result.summary.is_empty() {
+            println!("       {}", result.summary);
+        }
+    }
+    if results.len() == query.page_size {
+        println!("more results on --page {}", action.page + 1);
+    }
+
+    let Some(profile) = action.add else {
+        return Ok(());
+    };
+    let picked = if action.pick.is_empty() {
+        if !std::io::stdin().is_terminal() {
+            bail!("stdin is not a terminal, use --pick to select the results to add");
+        }
+        dialoguer::MultiSelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt(format!("Mods to add to \"{profile}\""))
+            .items(&results.iter().map(|r| &r.name).collect::<Vec<_>>())
+            .interact()?
+    } else {
+        action
+            .pick
+            .iter()
+            .map(|&n| {
+                ensure!(
+                    (1..=results.len()).contains(&n),
+                    "there is no result number {n}"
+                );
+                Ok(n - 1)
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    if picked.is_empty() {
+        return Ok(());
+    }
+
+    let specs = picked
+        .into_iter()
+        .map(|i| results[i].spec.clone())
+        .collect::<Vec<_>>();
+    let resolved = resolve_mods_with_provider_init(&mut state, &specs, false, init_provider)
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+    state
+        .mod_data
+        .add_resolved_mods(&profile, &specs, resolved, false)?;
+    state.mod_data.save()?;
+    for spec in &specs {
+        println!("added {} to \"{profile}\"", spec.url);
+    }
+
+    Ok(())
+}
+
+async fn action_subscriptions(dirs: Dirs, action: ActionSubscriptions) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    let (direction, sync) = match action.action {
+        SubscriptionsAction::Pull(sync) => (SyncDirection::Pull, sync),
+        SubscriptionsAction::Push(sync) => (SyncDirection::Push, sync),
+    };
+
+    let diff = subscription_diff_with_provider_init(&mut state, &sync.profile, init_provider)
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+    if diff.is_empty(direction) {
+        println!("nothing to change");
+        return Ok(());
+    }
+    for line in diff.describe(direction, &sync.profile) {
+        println!("{line}");
+    }
+    if sync.dry_run {
+        return Ok(());
+    }
+    if !sync.yes {
+        if !std::io::stdin().is_terminal() {
+            bail!("stdin is not a terminal, pass --yes to apply the changes");
+        }
+        let apply = dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt("Apply these changes?")
+            .default(false)
+            .interact()?;
+        if !apply {
+            return Ok(());
+        }
+    }
+
+    sync_subscriptions_with_provider_init(
+        &mut state,
+        &sync.profile,
+        &diff,
+        direction,
+        init_provider,
+    )
+    .await
+    .map_err(|e| anyhow!("{}", e))?;
+    println!("changes applied");
+
+    Ok(())
+}
+
+fn action_status(dirs: Dirs, action: ActionStatus) -> Result<()> {
+    let state = State::init(dirs)?;
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+    debug!(?game_pak_path);
+
+    let profile = action
+        .profile
+        .unwrap_or_else(|| state.mod_data.active_profile.clone());
+    state
+        .mod_data
+        .get_profile(&profile)
+        .map_err(|e| anyhow!("{}", e))?;
+
+    let Some(meta) = installed_meta(game_pak_path).map_err(|e| anyhow!("{}", e))? else {
+        println!("no mod bundle installed, apply profile {profile:?} with `mint profile`");
+        return Ok(());
+    };
+
+    println!(
+        "mod bundle installed by mint {} with {} mods:",
+        meta.version,
+        meta.mods.len()
+    );
+    for m in &meta.mods {
+        let required = if m.required { "required" } else { "optional" };
+        println!("  [{:?}] {} ({required}) {}", m.approval, m.name, m.url);
+    }
+
+    let diff = compare(&state, &profile, &meta);
+    if diff.is_up_to_date() {
+        println!("up to date with profile {profile:?}");
+    } else {
+        println!("differs from profile {profile:?}, apply it with `mint profile`:");
+        for line in diff.describe() {
+            println!("  {line}");
+        }
+    }
+    Ok(())
+}
+
+fn action_provider(dirs: Dirs, action: ActionProvider) -> Result<()> {
+    let mut state = State::init(dirs)?;
+
+    match action.action {
+        ProviderAction::List(ProviderList {}) => {
+            for factory in ModStore::get_provider_factories() {
+                println!("{}", factory.id);
+                let stored = state.config.provider_parameters.get(factory.id);
+                let from_env = factory.parameters_from_env();
+                for p in factory.parameters {
+                    let source = if from_env.contains_key(p.id) {
+                        "set by environment"
+                    } else if stored.is_some_and(|s| s.contains_key(p.id)) {
+                        "stored"
+                    } else if p.optional {
+                        "optional, not set"
+                    } else {
+                        "not set"
+                    };
+                    println!(
+                        "  {}: {} [{}] ({source})",
+                        p.id,
+                        p.description,
+                        factory.parameter_env_var(p)
+                    );
+                    if let Some(link) = p.link {
+                        println!("    {link}");
+                    }
+                }
+            }
+        }
+        ProviderAction::Set(ProviderSet {
+            provider,
+            parameter,
+            value,
+        }) => {
+            let (factory, p) = find_provider_parameter(&provider, &parameter)?;
+            let value = match value.as_deref() {
+                Some("-") => {
+                    let mut value = String::new();
+                    std::io::stdin().read_line(&mut value)?;
+                    value.trim().to_string()
+                }
+                Some(value) => value.to_string(),
+                None => {
+                    if !std::io::stdin().is_terminal() {
+                        bail!("no value given and stdin is not a terminal, pass the value or - to read it from stdin");
+                    }
+                    prompt_parameter(p).map_err(|e| anyhow!("{}", e))?
+                }
+            };
+            ensure!(
+                !value.is_empty(),
+                "value of {provider}.{parameter} is empty"
+            );
+
+            let mut params = state
+                .config
+                .provider_parameters
+                .get(factory.id)
+                .cloned()
+                .unwrap_or_default();
+            params.insert(p.id.to_owned(), value);
+            if factory
+                .parameters
+                .iter()
+                .all(|p| p.optional || params.contains_key(p.id))
+            {
+                state
+                    .store
+                    .add_provider(factory, &params)
+                    .map_err(|e| anyhow!("{}", e))?;
+            }
+            state
+                .config
+                .provider_parameters
+                .insert(factory.id.to_owned(), params);
+            state.config.save()?;
+            println!("stored {provider}.{parameter}");
+        }
+    }
+    Ok(())
+}
+
+fn action_rollback(dirs: Dirs, action: ActionRollback) -> Result<()> {
+    let state = State::init(dirs)?;
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+    debug!(?game_pak_path);
+
+    let path = rollback(game_pak_path).map_err(|e| anyhow!("{}", e))?;
+    println!("restored previous mod bundle {}", path.display());
+    Ok(())
+}
+
+fn action_uninstall(dirs: Dirs, action: ActionUninstall) -> Result<()> {
+    let state = State::init(dirs)?;
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+    debug!(?game_pak_path);
+
+    let modio_mods =
+        if action.restore_modio || !action.modio_ids.is_empty() || action.profile.is_some() {
+            let mut mods = action.modio_ids.into_iter().collect::<HashSet<_>>();
+            if let Some(profile) = &action.profile {
+                state
+                    .mod_data
+                    .get_profile(profile)
+                    .map_err(|e| anyhow!("{}", e))?;
+                state.mod_data.for_each_enabled_mod(profile, |mc| {
+                    match state.store.get_mod_info(&mc.spec).and_then(|i| i.modio_id) {
+                        Some(modio_id) => {
+                            mods.insert(modio_id);
+                        }
+                        None => debug!("skipping {}: not a cached mod.io mod", mc.spec.url),
+                    }
+                });
+            }
+            Some(mods)
+        } else {
+            None
+        };
+    debug!(?modio_mods);
+
+    let restore_modio = modio_mods.is_some();
+    let report = uninstall(game_pak_path, modio_mods).map_err(|e| anyhow!("{}", e))?;
+
+    if report.removed_files.is_empty() {
+        println!("no mod files found to remove");
+    }
+    for path in &report.removed_files {
+        println!("removed {}", path.display());
+    }
+    if let Some(config_path) = &report.config_path {
+        println!("updated {}", config_path.display());
+        for change in &report.ini_changes {
+            println!(
+                "  [{}] {}: {} -> {}",
+                change.section,
+                change.key,
+                change.old.as_deref().unwrap_or("<unset>"),
+                change.new.as_deref().unwrap_or("<unset>"),
+            );
+        }
+    } else if restore_modio && report.modio_error.is_none() {
+        println!("mod.io directory not found, official integration left untouched");
+    }
+    if let Some(e) = report.modio_error {
+        return Err(anyhow!(
+            "removed mod bundle but failed to restore mod.io integration: {e}"
+        ));
+    }
     Ok(())
 }
@@ -10,12 +10,14 @@ mod split_asset_pairs;
 mod unmodified_game_assets;
 
 use std::collections::{BTreeMap, BTreeSet};
-use std::io::{BufReader, Cursor, Read};
-use std::path::{Path, PathBuf};
+use std::io::{BufReader, Cursor};
+use std::path::PathBuf;
+use std::str::FromStr;
 
 use fs_err as fs;
 use indexmap::IndexSet;
 use repak::PakReader;
+use serde::{Serialize, Serializer};
 use snafu::prelude::*;
 use tracing::trace;
 
@@ -29,6 +31,8 @@ use self::shader_files::ShaderFilesLint;
 pub use self::split_asset_pairs::SplitAssetPair;
 use self::split_asset_pairs::SplitAssetPairsLint;
 use self::unmodified_game_assets::UnmodifiedGameAssetsLint;
+use crate::archive::{read_mod_contents, ArchiveError, ModContents};
+use crate::integrate::{pak_name, DisabledPaks};
 use crate::mod_lints::conflicting_mods::ConflictingModsLint;
 use crate::providers::{ModSpecification, ReadSeek};
 
@@ -42,9 +46,9 @@ pub enum LintError {
     PrefixMismatch { source: std::path::StripPrefixError },
     #[snafu(display("empty archive"))]
     EmptyArchive,
-    #[snafu(display("zip archive error"))]
-    ZipArchiveError,
-    #[snafu(display("zip only contains non-pak files"))]
+    #[snafu(transparent)]
+    ArchiveError { source: ArchiveError },
+    #[snafu(display("archive only contains non-pak files"))]
     OnlyNonPakFiles,
     #[snafu(display("some lints require specifying a valid game pak path"))]
     InvalidGamePath,
@@ -52,16 +56,23 @@ pub enum LintError {
 
 pub struct LintCtxt {
     pub(crate) mods: IndexSet<(ModSpecification, PathBuf)>,
+    /// Paks of archives that are not integrated and therefore not linted, keyed like `mods`.
+    pub(crate) disabled_paks: DisabledPaks,
     pub(crate) fsd_pak_path: Option<PathBuf>,
 }
 
 impl LintCtxt {
     pub fn init(
         mods: IndexSet<(ModSpecification, PathBuf)>,
+        disabled_paks: DisabledPaks,
         fsd_pak_path: Option<PathBuf>,
     ) -> Result<Self, LintError> {
         trace!("LintCtxt::init");
-        Ok(Self { mods, fsd_pak_path })
+        Ok(Self {
+            mods,
+            disabled_paks,
+            fsd_pak_path,
+        })
     }
 
     pub fn for_each_mod<F, EmptyArchiveHandler, OnlyNonPakFilesHandler, MultiplePakFilesHandler>(
@@ -98,12 +109,15 @@ impl LintCtxt {
                 },
             };
 
-            let mut individual_pak_readers = bufs
+            let disabled = self.disabled_paks.get(mod_spec);
+            let individual_pak_readers = bufs
                 .into_iter()
-                .filter_map(|(_, pak_or_non_pak)| match pak_or_non_pak {
-                    PakOrNotPak::Pak(individual_pak_reader) => Some(individual_pak_reader),
+                .filter_map(|(path, pak_or_non_pak)| match pak_or_non_pak {
+                    PakOrNotPak::Pak(individual_pak_reader) => Some((path, individual_pak_reader)),
                     PakOrNotPak::NotPak => None,
                 })
+                .filter(|(path, _)| !disabled.is_some_and(|d| d.contains(&pak_name(path))))
+                .map(|(_, individual_pak_reader)| individual_pak_reader)
                 .collect::<Vec<_>>();
 
             if individual_pak_readers.len() > 1 {
@@ -112,9 +126,11 @@ impl LintCtxt {
                 }
             }
 
-            let mut first_pak_read_seek = individual_pak_readers.remove(0);
-            let pak_reader = repak::PakBuilder::new().reader(&mut first_pak_read_seek)?;
-            f(mod_spec.clone(), &mut first_pak_read_seek, &pak_reader)?
+            // every enabled pak is integrated, so lint them all
+            for mut pak_read_seek in individual_pak_readers {
+                let pak_reader = repak::PakBuilder::new().reader(&mut pak_read_seek)?;
+                f(mod_spec.clone(), &mut pak_read_seek, &pak_reader)?
+            }
         }
 
         Ok(())
@@ -162,48 +178,28 @@ pub(crate) enum PakOrNotPak {
 }
 
 pub(crate) fn lint_get_all_files_from_data(
-    mut data: Box<dyn ReadSeek>,
+    data: Box<dyn ReadSeek>,
 ) -> Result<Vec<(PathBuf, PakOrNotPak)>, LintError> {
-    if let Ok(mut archive) = zip::ZipArchive::new(&mut data) {
-        ensure!(!archive.is_empty(), EmptyArchiveSnafu);
-
-        let mut files = Vec::new();
-        for i in 0..archive.len() {
-            let mut file = archive
-                .by_index(i)
-                .map_err(|_| LintError::ZipArchiveError)?;
-
-            if let Some(p) = file.enclosed_name().map(Path::to_path_buf) {
-                if file.is_file() {
-                    if p.extension().filter(|e| e == &"pak").is_some() {
-                        let mut buf = vec![];
-                        file.read_to_end(&mut buf)?;
-                        files.push((
-                            p.to_path_buf(),
-                            PakOrNotPak::Pak(Box::new(Cursor::new(buf))),
-                        ));
-                    } else {
-                        let mut buf = vec![];
-                        file.read_to_end(&mut buf)?;
-                        files.push((p.to_path_buf(), PakOrNotPak::NotPak));
-                    }
-                }
-            }
-        }
-
-        if files
-            .iter()
-            .filter(|(_, pak_or_not_pak)| matches!(pak_or_not_pak, PakOrNotPak::Pak(..)))
-            .count()
-            >= 1
-        {
-            Ok(files)
-        } else {
-            OnlyNonPakFilesSnafu.fail()?
+    let contents = read_mod_contents(data).map_err(|e| match e {
+        ArchiveError::IoError { source } => LintError::IoError { source },
+        e => LintError::ArchiveError { source: e },
+    })?;
+    match contents {
+        ModContents::Pak(data) => Ok(vec![(PathBuf::from("."), PakOrNotPak::Pak(data))]),
+        ModContents::Archive {
+            paks, other_files, ..
+        } => {
+            ensure!(
+                !paks.is_empty() || !other_files.is_empty(),
+                EmptyArchiveSnafu
+            );
+            ensure!(!paks.is_empty(), OnlyNonPakFilesSnafu);
+            Ok(paks
+                .into_iter()
+                .map(|(p, buf)| (p, PakOrNotPak::Pak(Box::new(Cursor::new(buf)))))
+                .chain(other_files.into_iter().map(|p| (p, PakOrNotPak::NotPak)))
+                .collect())
         }
-    } else {
-        data.rewind()?;
-        Ok(vec![(PathBuf::from("."), PakOrNotPak::Pak(data))])
     }
 }
 
@@ -219,6 +215,10 @@ pub struct LintId {
 }
 
 impl LintId {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
     pub fn to_name_lower(&self) -> String {
         self.name.to_ascii_lowercase()
     }
@@ -253,29 +253,310 @@ impl LintId {
     pub const UNMODIFIED_GAME_ASSETS: Self = LintId {
         name: "unmodified_game_assets",
     };
+
+    pub const ALL: [Self; 10] = [
+        Self::CONFLICTING,
+        Self::ASSET_REGISTRY_BIN,
+        Self::SHADER_FILES,
+        Self::OUTDATED_PAK_VERSION,
+        Self::EMPTY_ARCHIVE,
+        Self::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
+        Self::ARCHIVE_WITH_MULTIPLE_PAKS,
+        Self::NON_ASSET_FILES,
+        Self::SPLIT_ASSET_PAIRS,
+        Self::UNMODIFIED_GAME_ASSETS,
+    ];
+}
+
+impl std::fmt::Display for LintId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl FromStr for LintId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|id| id.name.eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "unknown lint `{s}`, expected one of: {}",
+                    Self::ALL.map(|id| id.name).join(", ")
+                )
+            })
+    }
+}
+
+impl Serialize for LintId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+/// Serialize maps keyed by [`ModSpecification`] as maps keyed by mod URL so they can be
+/// represented in formats that only allow string keys (e.g. JSON).
+fn serialize_by_url<V: Serialize, S: Serializer>(
+    map: &Option<BTreeMap<ModSpecification, V>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.as_ref()
+        .map(|map| {
+            map.iter()
+                .map(|(spec, value)| (&spec.url, value))
+                .collect::<BTreeMap<_, _>>()
+        })
+        .serialize(serializer)
+}
+
+fn serialize_pak_versions<S: Serializer>(
+    map: &Option<BTreeMap<ModSpecification, repak::Version>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.as_ref()
+        .map(|map| {
+            map.iter()
+                .map(|(spec, version)| (&spec.url, version.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        })
+        .serialize(serializer)
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct LintReport {
     pub conflicting_mods: Option<BTreeMap<String, IndexSet<ModSpecification>>>,
+    #[serde(serialize_with = "serialize_by_url")]
     pub asset_register_bin_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
+    #[serde(serialize_with = "serialize_by_url")]
     pub shader_file_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
+    #[serde(serialize_with = "serialize_pak_versions")]
     pub outdated_pak_version_mods: Option<BTreeMap<ModSpecification, repak::Version>>,
     pub empty_archive_mods: Option<BTreeSet<ModSpecification>>,
     pub archive_with_only_non_pak_files_mods: Option<BTreeSet<ModSpecification>>,
     pub archive_with_multiple_paks_mods: Option<BTreeSet<ModSpecification>>,
+    #[serde(serialize_with = "serialize_by_url")]
     pub non_asset_file_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
+    #[serde(serialize_with = "serialize_by_url")]
     pub split_asset_pairs_mods:
         Option<BTreeMap<ModSpecification, BTreeMap<String, SplitAssetPair>>>,
+    #[serde(serialize_with = "serialize_by_url")]
     pub unmodified_game_assets_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintLevel {
+    Note,
+    Warning,
+}
+
+impl std::fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LintLevel::Note => "note",
+            LintLevel::Warning => "warning",
+        })
+    }
+}
+
+/// A single lint finding, flattened out of a [`LintReport`].
+#[derive(Debug, Clone, Serialize)]
+pub struct LintFinding {
+    pub lint: LintId,
+    pub level: LintLevel,
+    pub message: String,
+    pub mods: Vec<ModSpecification>,
+    pub paths: Vec<String>,
+}
+
+impl LintReport {
+    /// Flatten the report into individual findings in lint order.
+    pub fn findings(&self) -> Vec<LintFinding> {
+        let mut findings = vec![];
+
+        let mut push = |lint, level, message, mods, paths| {
+            findings.push(LintFinding {
+                lint,
+                level,
+                message,
+                mods,
+                paths,
+            })
+        };
+
+        if let Some(conflicting_mods) = &self.conflicting_mods {
+            for (path, mods) in conflicting_mods {
+                push(
+                    LintId::CONFLICTING,
+                    LintLevel::Warning,
+                    format!("conflicting modification of asset `{path}`"),
+                    mods.iter().cloned().collect(),
+                    vec![path.clone()],
+                );
+            }
+        }
+        if let Some(mods) = &self.asset_register_bin_mods {
+            for (spec, paths) in mods {
+                push(
+                    LintId::ASSET_REGISTRY_BIN,
+                    LintLevel::Note,
+                    format!("{} includes one or more `AssetRegistry.bin`", spec.url),
+                    vec![spec.clone()],
+                    paths.iter().cloned().collect(),
+                );
+            }
+        }
+        if let Some(mods) = &self.shader_file_mods {
+            for (spec, paths) in mods {
+                push(
+                    LintId::SHADER_FILES,
+                    LintLevel::Warning,
+                    format!("{} includes one or more shader files", spec.url),
+                    vec![spec.clone()],
+                    paths.iter().cloned().collect(),
+                );
+            }
+        }
+        if let Some(mods) = &self.outdated_pak_version_mods {
+            for (spec, version) in mods {
+                push(
+                    LintId::OUTDATED_PAK_VERSION,
+                    LintLevel::Warning,
+                    format!("{} includes outdated pak version {}", spec.url, version),
+                    vec![spec.clone()],
+                    vec![],
+                );
+            }
+        }
+        if let Some(mods) = &self.empty_archive_mods {
+            for spec in mods {
+                push(
+                    LintId::EMPTY_ARCHIVE,
+                    LintLevel::Warning,
+                    format!("{} contains an empty archive", spec.url),
+                    vec![spec.clone()],
+                    vec![],
+                );
+            }
+        }
+        if let Some(mods) = &self.archive_with_only_non_pak_files_mods {
+            for spec in mods {
+                push(
+                    LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
+                    LintLevel::Warning,
+                    format!("{} contains only non-`.pak` files", spec.url),
+                    vec![spec.clone()],
+                    vec![],
+                );
+            }
+        }
+        if let Some(mods) = &self.archive_with_multiple_paks_mods {
+            for spec in mods {
+                push(
+                    LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
+                    LintLevel::Note,
+                    format!(
+                        "{} contains multiple `.pak`s, all of them are loaded unless disabled for the mod",
+                        spec.url
+                    ),
+                    vec![spec.clone()],
+                    vec![],
+                );
+            }
+        }
+        if let Some(mods) = &self.non_asset_file_mods {
+            for (spec, paths) in mods {
+                push(
+                    LintId::NON_ASSET_FILES,
+                    LintLevel::Warning,
+                    format!("{} includes non-asset files", spec.url),
+                    vec![spec.clone()],
+                    paths.iter().cloned().collect(),
+                );
+            }
+        }
+        if let Some(mods) = &self.split_asset_pairs_mods {
+            for (spec, files) in mods {
+                push(
+                    LintId::SPLIT_ASSET_PAIRS,
+                    LintLevel::Warning,
+                    format!("{} includes split {{uexp, uasset}} pairs", spec.url),
+                    vec![spec.clone()],
+                    files.keys().cloned().collect(),
+                );
+            }
+        }
+        if let Some(mods) = &self.unmodified_game_assets_mods {
+            for (spec, paths) in mods {
+                push(
+                    LintId::UNMODIFIED_GAME_ASSETS,
+                    LintLevel::Warning,
+                    format!("{} includes unmodified game assets", spec.url),
+                    vec![spec.clone()],
+                    paths.iter().cloned().collect(),
+                );
+            }
+        }
+
+        findings
+    }
+
+    /// Render the report as a SARIF 2.1.0 log.
+    pub fn to_sarif(&self) -> serde_json::Value {
+        let results = self
+            .findings()
+            .into_iter()
+            .map(|finding| {
+                let mut locations = finding
+                    .paths
+                    .iter()
+                    .map(|path| {
+                        serde_json::json!({
+                            "physicalLocation": { "artifactLocation": { "uri": path } }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                locations.extend(finding.mods.iter().map(|spec| {
+                    serde_json::json!({
+                        "physicalLocation": { "artifactLocation": { "uri": spec.url } }
+                    })
+                }));
+                serde_json::json!({
+                    "ruleId": finding.lint.name,
+                    "level": finding.level,
+                    "message": { "text": finding.message },
+                    "locations": locations,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "mint",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                        "rules": LintId::ALL.map(|id| serde_json::json!({ "id": id.name })),
+                    }
+                },
+                "results": results,
+            }]
+        })
+    }
+}
+
 pub fn run_lints(
     enabled_lints: &BTreeSet<LintId>,
     mods: IndexSet<(ModSpecification, PathBuf)>,
+    disabled_paks: DisabledPaks,
     fsd_pak_path: Option<PathBuf>,
 ) -> Result<LintReport, LintError> {
-    let lint_ctxt = LintCtxt::init(mods, fsd_pak_path)?;
+    let lint_ctxt = LintCtxt::init(mods, disabled_paks, fsd_pak_path)?;
     let mut lint_report = LintReport::default();
 
     for lint_id in enabled_lints {
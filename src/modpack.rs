@@ -0,0 +1,325 @@
+//! Offline modpacks: a single archive containing a profile, the exact versions of its enabled
+//! mods, the files of those mods and the provider metadata required to integrate them without
+//! network access.
+//!
+//! Layout of the archive:
+//! - `manifest.json`: [`ModpackManifest`]
+//! - `cache.json`: subset of the provider cache relevant to the packed mods
+//! - `blobs/<sha256>`: mod files, named after their SHA-256 digest like in the blob cache
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, BufReader, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+use tracing::*;
+
+use crate::providers::{Cache_v0_0_0, ModSpecification, ProviderFactory};
+use crate::state::lockfile::{verify_locked, LockedMod, Lockfile_v0_0_0 as Lockfile};
+use crate::state::profile_export::ProfileExport_v0_0_0 as ProfileExport;
+use crate::state::State;
+use crate::{update_lockfile_with_provider_init, MintError};
+
+const MANIFEST_PATH: &str = "manifest.json";
+const CACHE_PATH: &str = "cache.json";
+const BLOBS_DIR: &str = "blobs";
+
+#[derive(Debug, Snafu)]
+pub enum ModpackError {
+    #[snafu(display("failed to read modpack {}", path.display()))]
+    ReadFailed {
+        path: PathBuf,
+        source: zip::result::ZipError,
+    },
+    #[snafu(display("failed to write modpack {}", path.display()))]
+    WriteFailed {
+        path: PathBuf,
+        source: zip::result::ZipError,
+    },
+    #[snafu(display("modpack is missing {name}"))]
+    MissingEntry {
+        name: String,
+        source: zip::result::ZipError,
+    },
+    #[snafu(display("failed to deserialize modpack {name}"))]
+    DeserializationFailed {
+        name: &'static str,
+        source: serde_json::Error,
+    },
+    #[snafu(display("failed to serialize modpack {name}"))]
+    SerializationFailed {
+        name: &'static str,
+        source: serde_json::Error,
+    },
+    #[snafu(display("unsupported modpack version"))]
+    UnsupportedVersion,
+    #[snafu(display("modpack blob {expected} has SHA-256 {found}"))]
+    BlobHashMismatch { expected: String, found: String },
+}
+
+#[obake::versioned]
+#[obake(version("0.0.0"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModpackManifest {
+    pub profile: ProfileExport,
+    /// Locked enabled mods keyed by [`crate::providers::ModSpecification::url`].
+    pub mods: BTreeMap<String, LockedMod>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "version")]
+pub enum VersionAnnotatedModpackManifest {
+    #[serde(rename = "0.0.0")]
+    V0_0_0(ModpackManifest!["0.0.0"]),
+    #[serde(other)]
+    Unsupported,
+}
+
+/// Lock a profile and write it along with its enabled mods to a modpack at `output`. The
+/// profile's lockfile is updated in the process, but existing locked versions are kept.
+pub async fn pack_profile_with_provider_init<F>(
+    state: &mut State,
+    profile: &str,
+    output: &Path,
+    init: F,
+) -> Result<ModpackManifest!["0.0.0"], MintError>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    let export = state.mod_data.export_profile(profile)?;
+    let (_, lockfile) = update_lockfile_with_provider_init(state, profile, false, init).await?;
+
+    let mut blobs = BTreeMap::new();
+    for (url, locked) in &lockfile.mods {
+        if blobs.contains_key(&locked.sha256) {
+            continue;
+        }
+        // local files are not stored in the blob cache
+        let path = match state.store.get_blob_path(&locked.sha256) {
+            Some(path) => path,
+            None => {
+                let path = state
+                    .store
+                    .fetch_mod(&locked.resolution, false, None)
+                    .await?;
+                verify_locked(&ModSpecification::new(url.clone()), locked, &path)?;
+                path
+            }
+        };
+        blobs.insert(locked.sha256.clone(), path);
+    }
+
+    let urls = lockfile
+        .mods
+        .iter()
+        .flat_map(|(url, locked)| [url.as_str(), locked.resolution.url.0.as_str()])
+        .collect::<Vec<_>>();
+    let cache = state.store.export_cache(&urls);
+
+    let manifest = ModpackManifest_v0_0_0 {
+        profile: export,
+        mods: lockfile.mods,
+    };
+    write_modpack(
+        output,
+        &VersionAnnotatedModpackManifest::V0_0_0(manifest.clone()),
+        &cache,
+        &blobs,
+    )?;
+    Ok(manifest)
+}
+
+fn write_modpack(
+    output: &Path,
+    manifest: &VersionAnnotatedModpackManifest,
+    cache: &Cache_v0_0_0,
+    blobs: &BTreeMap<String, PathBuf>,
+) -> Result<(), MintError> {
+    use zip::write::FileOptions;
+    use zip::CompressionMethod;
+
+    let dir = output
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let tmp = tempfile::NamedTempFile::new_in(dir)?;
+
+    let mut zip = zip::ZipWriter::new(io::BufWriter::new(tmp));
+    let json = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    // mod files are usually already compressed
+    let stored = FileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(true);
+
+    zip.start_file(MANIFEST_PATH, json)
+        .context(WriteFailedSnafu { path: output })?;
+    serde_json::to_writer_pretty(&mut zip, manifest).context(SerializationFailedSnafu {
+        name: MANIFEST_PATH,
+    })?;
+
+    zip.start_file(CACHE_PATH, json)
+        .context(WriteFailedSnafu { path: output })?;
+    serde_json::to_writer(&mut zip, cache)
+        .context(SerializationFailedSnafu { name: CACHE_PATH })?;
+
+    for (hash, path) in blobs {
+        zip.start_file(format!("{BLOBS_DIR}/{hash}"), stored)
+            .context(WriteFailedSnafu { path: output })?;
+        io::copy(&mut fs::File::open(path)?, &mut zip)?;
+    }
+
+    let mut writer = zip.finish().context(WriteFailedSnafu { path: output })?;
+    writer.flush()?;
+    let tmp = writer.into_inner().map_err(|e| e.into_error())?;
+    tmp.persist(output).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Import a modpack: its mod files are added to the blob cache, its provider metadata is merged
+/// into the provider cache and its profile is imported along with a lockfile so the profile can
+/// be integrated without network access. See [`crate::state::ModData_v0_1_0::import_profile`]
+/// for the meaning of `name` and `replace`. Returns the name the profile was imported as.
+pub fn unpack_profile(
+    state: &mut State,
+    input: &Path,
+    name: Option<&str>,
+    replace: bool,
+) -> Result<String, MintError> {
+    let mut zip = zip::ZipArchive::new(BufReader::new(fs::File::open(input)?))
+        .context(ReadFailedSnafu { path: input })?;
+
+    let manifest = read_json(&mut zip, MANIFEST_PATH)?;
+    let manifest = match manifest {
+        VersionAnnotatedModpackManifest::V0_0_0(manifest) => manifest,
+        VersionAnnotatedModpackManifest::Unsupported => UnsupportedVersionSnafu.fail()?,
+    };
+    // the provider cache is versioned alongside the manifest
+    let cache = read_json::<_, Cache_v0_0_0>(&mut zip, CACHE_PATH)?;
+
+    let mut imported = HashSet::new();
+    for locked in manifest.mods.values() {
+        if !imported.insert(locked.sha256.as_str()) {
+            continue;
+        }
+        let entry = format!("{BLOBS_DIR}/{}", locked.sha256);
+        let mut buf = vec![];
+        zip.by_name(&entry)
+            .context(MissingEntrySnafu { name: entry })?
+            .read_to_end(&mut buf)?;
+        let found = state.store.import_blob(&buf)?;
+        ensure!(
+            found.eq_ignore_ascii_case(&locked.sha256),
+            BlobHashMismatchSnafu {
+                expected: locked.sha256.clone(),
+                found,
+            }
+        );
+    }
+    info!("imported {} mod files", imported.len());
+
+    state.store.import_cache(cache)?;
+
+    let profile = state
+        .mod_data
+        .import_profile(manifest.profile, name, replace)?;
+    state.mod_data.save()?;
+    state.write_lockfile(Lockfile {
+        profile: profile.clone(),
+        mods: manifest.mods,
+    })?;
+    Ok(profile)
+}
+
+fn read_json<R, T>(zip: &mut zip::ZipArchive<R>, name: &'static str) -> Result<T, ModpackError>
+where
+    R: Read + Seek,
+    T: for<'de> Deserialize<'de>,
+{
+    let file = zip.by_name(name).context(MissingEntrySnafu { name })?;
+    serde_json::from_reader(BufReader::new(file)).context(DeserializationFailedSnafu { name })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::providers::modio::ModioCache;
+    use crate::providers::ModResolution;
+    use crate::state::ModConfig;
+    use crate::{fetch_locked_mods, Dirs};
+
+    #[tokio::test]
+    async fn test_pack_unpack_locked_without_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = ModSpecification::new("https://mod.io/g/drg/m/test-mod#1/10".to_string());
+
+        // packing a locked profile only reads the caches
+        let mut packer = State::init(Dirs::from_path(dir.path().join("packer")).unwrap()).unwrap();
+        packer
+            .store
+            .import_cache(ModioCache::single_mod_cache(
+                1,
+                "test-mod",
+                "Test Mod",
+                &[10, 20],
+            ))
+            .unwrap();
+        let sha256 = packer.store.import_blob(b"test pak").unwrap();
+        packer
+            .mod_data
+            .add_mod(
+                "default",
+                ModConfig {
+                    spec: spec.clone(),
+                    required: false,
+                    enabled: true,
+                    priority: 0,
+                    disabled_paks: ["Disabled.pak".to_string()].into(),
+                },
+            )
+            .unwrap();
+        packer
+            .write_lockfile(Lockfile {
+                profile: "default".to_string(),
+                mods: [(
+                    spec.url.clone(),
+                    LockedMod {
+                        resolution: ModResolution::resolvable(spec.url.as_str().into()),
+                        modfile_id: Some(10),
+                        sha256: sha256.clone(),
+                    },
+                )]
+                .into(),
+            })
+            .unwrap();
+        let pack = dir.path().join("pack.zip");
+        pack_profile_with_provider_init(&mut packer, "default", &pack, |_, url, _| {
+            panic!("provider for <{url}> requested")
+        })
+        .await
+        .unwrap();
+
+        let mut state = State::init(Dirs::from_path(dir.path().join("offline")).unwrap()).unwrap();
+        let profile = unpack_profile(&mut state, &pack, Some("packed"), false).unwrap();
+        assert_eq!(profile, "packed");
+        assert!(
+            state.store.get_mod_info(&spec).is_none(),
+            "mod.io provider must not be configured"
+        );
+
+        let (mods, disabled_paks) = fetch_locked_mods(&state, &profile).await.unwrap();
+        assert_eq!(mods.len(), 1);
+        let (info, path) = &mods[0];
+        assert_eq!(info.name, "Test Mod");
+        assert_eq!(info.modio_id, Some(1));
+        assert!(info.suggested_require);
+        // the latest modfile is 20 but the locked one is integrated
+        assert_eq!(info.resolution.url.0, spec.url);
+        assert_eq!(fs::read(path).unwrap(), b"test pak");
+        assert_eq!(
+            disabled_paks,
+            [(info.spec.clone(), ["Disabled.pak".to_string()].into())].into()
+        );
+    }
+}
@@ -18,6 +18,17 @@ pub trait ModProviderCache: Sync + Send + std::fmt::Debug {
         Self: Sized;
     fn as_any(&self) -> &dyn std::any::Any;
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
+    /// Copy of the entries required to resolve and fetch the given mod URLs (specs or
+    /// resolutions) without network access.
+    fn subset(&self, urls: &[&str]) -> Box<dyn ModProviderCache>;
+    /// Merge entries from another cache of the same type, overwriting existing entries.
+    fn merge(&mut self, other: &dyn ModProviderCache);
+    /// Mod info built from the cached entries alone, for providers whose
+    /// [`super::ModProvider::get_mod_info`] only reads the cache. Used when the provider itself is
+    /// not configured.
+    fn get_mod_info(&self, _spec: &super::ModSpecification) -> Option<super::ModInfo> {
+        None
+    }
 }
 
 #[obake::versioned]
@@ -28,6 +39,36 @@ pub struct Cache {
 }
 
 impl Cache {
+    /// Copy of the entries of every provider cache relevant to the given mod URLs.
+    pub(super) fn subset(&self, urls: &[&str]) -> Self {
+        Self {
+            cache: self
+                .cache
+                .iter()
+                .map(|(id, c)| (id.clone(), c.subset(urls)))
+                .collect(),
+        }
+    }
+
+    pub(super) fn merge(&mut self, other: Self) {
+        for (id, c) in other.cache {
+            match self.cache.get_mut(&id) {
+                Some(existing) => existing.merge(c.as_ref()),
+                None => {
+                    self.cache.insert(id, c);
+                }
+            }
+        }
+    }
+
+    pub(super) fn get_mod_info(
+        &self,
+        id: &str,
+        spec: &super::ModSpecification,
+    ) -> Option<super::ModInfo> {
+        self.cache.get(id)?.get_mod_info(spec)
+    }
+
     pub(super) fn has<T: ModProviderCache + 'static>(&self, id: &str) -> bool {
         self.cache
             .get(id)
@@ -183,9 +224,16 @@ pub(crate) fn read_cache_metadata_or_default(
     Ok(cache)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlobRef(String);
 
+impl BlobRef {
+    /// SHA-256 hex digest of the blob.
+    pub fn hash(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Debug, Snafu)]
 #[snafu(display("blob cache {kind} failed"))]
 pub struct BlobCacheError {
@@ -224,4 +272,158 @@ impl BlobCache {
         let path = self.path.join(&blob.0);
         path.exists().then_some(path)
     }
+
+    /// Delete a blob that is no longer referenced, doing nothing if it is already gone.
+    pub(super) fn remove(&self, blob: &BlobRef) -> Result<(), BlobCacheError> {
+        match fs::remove_file(self.path.join(&blob.0)) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            res => res.context(BlobCacheSnafu { kind: "remove" }),
+        }
+    }
+
+    /// Look up a blob by its SHA-256 hex digest.
+    pub(super) fn get_path_by_hash(&self, hash: &str) -> Option<PathBuf> {
+        // hashes may come from user supplied files so make sure they can't escape the cache
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.get_path(&BlobRef(hash.to_ascii_lowercase()))
+    }
+
+    /// Open the download of `key`, e.g. a URL, keeping the data of a previously interrupted
+    /// download so it can be resumed.
+    pub(super) fn partial(&self, key: &str) -> Result<PartialBlob, BlobCacheError> {
+        use sha2::{Digest, Sha256};
+
+        let dir = self.path.join(".partial");
+        fs::create_dir_all(&dir).context(BlobCacheSnafu {
+            kind: "create partial dir",
+        })?;
+        let path = dir.join(hex::encode(Sha256::digest(key.as_bytes())));
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)
+            .context(BlobCacheSnafu {
+                kind: "open partial",
+            })?;
+        let mut hasher = Sha256::new();
+        let downloaded = std::io::copy(&mut file, &mut hasher).context(BlobCacheSnafu {
+            kind: "read partial",
+        })?;
+        let validator = fs::read_to_string(path.with_extension("validator")).ok();
+
+        Ok(PartialBlob {
+            path,
+            file,
+            hasher,
+            downloaded,
+            validator,
+        })
+    }
+}
+
+/// Blob being downloaded, hashed as the data arrives. The data is kept on disk together with the
+/// validator identifying the downloaded content until the download is finished, so it can be
+/// resumed from where it was interrupted.
+#[derive(Debug)]
+pub(super) struct PartialBlob {
+    path: PathBuf,
+    file: fs::File,
+    hasher: sha2::Sha256,
+    downloaded: u64,
+    validator: Option<String>,
+}
+
+impl PartialBlob {
+    /// Number of bytes written so far.
+    pub(super) fn downloaded(&self) -> u64 {
+        self.downloaded
+    }
+
+    /// Validator (e.g. ETag) of the content being downloaded, required to resume.
+    pub(super) fn validator(&self) -> Option<&str> {
+        self.validator.as_deref()
+    }
+
+    /// Read up to `len` bytes of the data written so far, starting at `offset`.
+    pub(super) fn read(&self, offset: u64, len: u64) -> std::io::Result<Vec<u8>> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut file = fs::File::open(&self.path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut buf = vec![];
+        file.take(len).read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// SHA-256 hex digest of the data written so far.
+    pub(super) fn hash(&self) -> String {
+        use sha2::Digest;
+
+        hex::encode(self.hasher.clone().finalize())
+    }
+
+    /// Discard the data written so far and start downloading the content identified by
+    /// `validator`.
+    pub(super) fn restart(&mut self, validator: Option<String>) -> Result<(), BlobCacheError> {
+        use sha2::Digest;
+
+        self.file.set_len(0).context(BlobCacheSnafu {
+            kind: "truncate partial",
+        })?;
+        self.hasher = sha2::Sha256::new();
+        self.downloaded = 0;
+        let validator_path = self.path.with_extension("validator");
+        match &validator {
+            Some(validator) => fs::write(validator_path, validator).context(BlobCacheSnafu {
+                kind: "write partial validator",
+            })?,
+            None => {
+                fs::remove_file(validator_path).ok();
+            }
+        }
+        self.validator = validator;
+        Ok(())
+    }
+
+    pub(super) fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        use sha2::Digest;
+        use std::io::Write;
+
+        self.file.write_all(data)?;
+        self.hasher.update(data);
+        self.downloaded += data.len() as u64;
+        Ok(())
+    }
+
+    /// Move the completed download into `cache`.
+    pub(super) fn finish(self, cache: &BlobCache) -> Result<BlobRef, BlobCacheError> {
+        use sha2::Digest;
+
+        let hash = hex::encode(self.hasher.finalize());
+        drop(self.file);
+        fs::rename(&self.path, cache.path.join(&hash))
+            .context(BlobCacheSnafu { kind: "rename" })?;
+        fs::remove_file(self.path.with_extension("validator")).ok();
+
+        Ok(BlobRef(hash))
+    }
+
+    /// Remove the data of a download that is no longer needed.
+    pub(super) fn discard(self) {
+        drop(self.file);
+        fs::remove_file(&self.path).ok();
+        fs::remove_file(self.path.with_extension("validator")).ok();
+    }
+}
+
+/// SHA-256 hex digest of a file's contents, as used for blob names.
+pub fn hash_file<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut fs::File::open(path.as_ref())?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
 }
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use fs_err as fs;
+use snafu::prelude::*;
+use tokio::sync::mpsc::Sender;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    BlobCache, BlobRef, FetchProgress, InvalidModDirectorySnafu, ModDirectoryIoSnafu,
+    ModDirectoryPakSnafu, ModInfo, ModProvider, ModProviderCache, ModResolution, ModResponse,
+    ModSpecification, ProviderCache, ProviderError,
+};
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: DIR_PROVIDER_ID,
+        new: DirProvider::new_provider,
+        can_provide: |url| Path::new(url).is_dir(),
+        parameters: &[],
+    }
+}
+
+/// Provides mods from a directory of loose cooked files laid out like the game's files, i.e.
+/// containing `FSD/Content/...`. A pak is built from the directory every time the mod is fetched.
+#[derive(Debug)]
+pub struct DirProvider {}
+
+impl DirProvider {
+    pub fn new_provider(
+        _parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        Ok(Arc::new(Self::new()))
+    }
+
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+const DIR_PROVIDER_ID: &str = "dir";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DirProviderCache {
+    /// Pak last built from each directory, replaced when the directory is fetched again.
+    dir_blobs: HashMap<String, BlobRef>,
+}
+
+#[typetag::serde]
+impl ModProviderCache for DirProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn subset(&self, dirs: &[&str]) -> Box<dyn ModProviderCache> {
+        Box::new(Self {
+            dir_blobs: dirs
+                .iter()
+                .filter_map(|dir| Some((dir.to_string(), self.dir_blobs.get(*dir)?.clone())))
+                .collect(),
+        })
+    }
+
+    fn merge(&mut self, other: &dyn ModProviderCache) {
+        if let Some(other) = other.as_any().downcast_ref::<Self>() {
+            self.dir_blobs.extend(
+                other
+                    .dir_blobs
+                    .iter()
+                    .map(|(dir, blob)| (dir.clone(), blob.clone())),
+            );
+        }
+    }
+}
+
+fn mod_info(spec: &ModSpecification) -> ModInfo {
+    let path = Path::new(&spec.url);
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| spec.url.to_string());
+    ModInfo {
+        provider: DIR_PROVIDER_ID,
+        name: name.clone(),
+        spec: spec.clone(),
+        versions: vec![],
+        resolution: ModResolution::unresolvable(spec.url.clone().into(), name),
+        suggested_require: false,
+        suggested_dependencies: vec![],
+        modio_tags: None,
+        modio_id: None,
+    }
+}
+
+/// Recursively collect the files below `dir` as (path inside the pak, path on disk). Symlinked
+/// directories are skipped as they may point back up the tree.
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<(String, PathBuf)>,
+) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if file_type.is_symlink() && path.is_dir() {
+            continue;
+        } else {
+            let pak_path = path
+                .strip_prefix(root)
+                .unwrap()
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((pak_path, path));
+        }
+    }
+    Ok(())
+}
+
+/// Build an uncompressed pak from the `FSD` directory inside `dir` with the same mount point as
+/// regular mods. Files are written in a fixed order so the same files always result in the same
+/// pak.
+fn build_pak(dir: &Path) -> Result<Vec<u8>, ProviderError> {
+    let content = ["FSD", "Content"].iter().collect::<PathBuf>();
+    ensure!(
+        dir.join(&content).is_dir(),
+        InvalidModDirectorySnafu {
+            path: dir.to_path_buf()
+        }
+    );
+
+    // only the game's files are packed, not project files, logs or version control next to them
+    let mut files = vec![];
+    collect_files(dir, &dir.join("FSD"), &mut files).context(ModDirectoryIoSnafu { path: dir })?;
+    files.sort();
+
+    let mut buf = Cursor::new(vec![]);
+    let mut pak = repak::PakBuilder::new().writer(
+        &mut buf,
+        repak::Version::V11,
+        "../../../".to_string(),
+        None,
+    );
+    for (pak_path, path) in files {
+        let data = fs::read(&path).context(ModDirectoryIoSnafu { path: &path })?;
+        pak.write_file(&pak_path, data)
+            .context(ModDirectoryPakSnafu { path: dir })?;
+    }
+    pak.write_index()
+        .context(ModDirectoryPakSnafu { path: dir })?;
+    Ok(buf.into_inner())
+}
+
+#[async_trait::async_trait]
+impl ModProvider for DirProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        _update: bool,
+        _cache: ProviderCache,
+    ) -> Result<ModResponse, ProviderError> {
+        Ok(ModResponse::Resolve(mod_info(spec)))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        _update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let pak = build_pak(Path::new(&res.url.0))?;
+        let blob = blob_cache.write(&pak)?;
+        let path = blob_cache.get_path(&blob).unwrap();
+
+        // drop the pak previously built from this directory unless another directory built the
+        // same one
+        let stale = {
+            let mut lock = cache.write().unwrap();
+            let dir_blobs = &mut lock.get_mut::<DirProviderCache>(DIR_PROVIDER_ID).dir_blobs;
+            dir_blobs
+                .insert(res.url.0.clone(), blob.clone())
+                .filter(|prev| prev.hash() != blob.hash())
+                .filter(|prev| !dir_blobs.values().any(|b| b.hash() == prev.hash()))
+        };
+        if let Some(stale) = stale {
+            blob_cache.remove(&stale)?;
+        }
+
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, _cache: ProviderCache) -> Option<ModInfo> {
+        Some(mod_info(spec))
+    }
+
+    fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        true
+    }
+
+    fn get_version_name(&self, _spec: &ModSpecification, _cache: ProviderCache) -> Option<String> {
+        Some("latest".to_string())
+    }
+}
@@ -2,18 +2,20 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use fs_err as fs;
+use snafu::prelude::*;
 use tokio::sync::mpsc::Sender;
 
 use super::{
-    BlobCache, FetchProgress, ModInfo, ModProvider, ModResolution, ModResponse, ModSpecification,
-    ProviderCache, ProviderError,
+    split_sha256_pin, BlobCache, FetchProgress, FileIoSnafu, HashMismatchSnafu, ModInfo,
+    ModProvider, ModResolution, ModResponse, ModSpecification, ProviderCache, ProviderError,
 };
 
 inventory::submit! {
     super::ProviderFactory {
         id: FILE_PROVIDER_ID,
         new: FileProvider::new_provider,
-        can_provide: |url| Path::new(url).exists(),
+        can_provide: |url| file_path(url).is_file(),
         parameters: &[],
     }
 }
@@ -35,6 +37,35 @@ impl FileProvider {
 
 const FILE_PROVIDER_ID: &str = "file";
 
+/// Path of a file mod URL without its `#sha256=` pin.
+fn file_path(url: &str) -> &Path {
+    Path::new(url.rsplit_once("#sha256=").map_or(url, |(path, _)| path))
+}
+
+fn mod_info(spec: &ModSpecification) -> ModInfo {
+    let path = file_path(&spec.url);
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| spec.url.to_string());
+    ModInfo {
+        provider: FILE_PROVIDER_ID,
+        name,
+        spec: spec.clone(),
+        versions: vec![],
+        resolution: ModResolution::unresolvable(
+            spec.url.clone().into(),
+            path.file_name()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+        ),
+        suggested_require: false,
+        suggested_dependencies: vec![],
+        modio_tags: None,
+        modio_id: None,
+    }
+}
+
 #[async_trait::async_trait]
 impl ModProvider for FileProvider {
     async fn resolve_mod(
@@ -43,27 +74,8 @@ impl ModProvider for FileProvider {
         _update: bool,
         _cache: ProviderCache,
     ) -> Result<ModResponse, ProviderError> {
-        let path = Path::new(&spec.url);
-        let name = path
-            .file_name()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| spec.url.to_string());
-        Ok(ModResponse::Resolve(ModInfo {
-            provider: FILE_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::unresolvable(
-                spec.url.clone().into(),
-                path.file_name()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "unknown".to_string()),
-            ),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            modio_tags: None,
-            modio_id: None,
-        }))
+        split_sha256_pin(&spec.url)?;
+        Ok(ModResponse::Resolve(mod_info(spec)))
     }
 
     async fn fetch_mod(
@@ -71,9 +83,35 @@ impl ModProvider for FileProvider {
         res: &ModResolution,
         _update: bool,
         _cache: ProviderCache,
-        _blob_cache: &BlobCache,
+        blob_cache: &BlobCache,
         tx: Option<Sender<FetchProgress>>,
     ) -> Result<PathBuf, ProviderError> {
+        let (path, pin) = split_sha256_pin(&res.url.0)?;
+        let path = match pin {
+            // pinned files are copied into the blob cache after checking them, so later changes
+            // to the file can't affect the integrated mod
+            Some(expected) => match blob_cache.get_path_by_hash(&expected) {
+                Some(path) => path,
+                None => {
+                    use sha2::{Digest, Sha256};
+
+                    let data = fs::read(path).context(FileIoSnafu { path })?;
+                    let actual = hex::encode(Sha256::digest(&data));
+                    ensure!(
+                        actual == expected,
+                        HashMismatchSnafu {
+                            url: &res.url.0,
+                            expected,
+                            actual,
+                        }
+                    );
+                    let blob = blob_cache.write(&data)?;
+                    blob_cache.get_path(&blob).unwrap()
+                }
+            },
+            None => PathBuf::from(path),
+        };
+
         if let Some(tx) = tx {
             tx.send(FetchProgress::Complete {
                 resolution: res.clone(),
@@ -81,7 +119,7 @@ impl ModProvider for FileProvider {
             .await
             .unwrap();
         }
-        Ok(PathBuf::from(&res.url.0))
+        Ok(path)
     }
 
     async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
@@ -93,34 +131,17 @@ impl ModProvider for FileProvider {
     }
 
     fn get_mod_info(&self, spec: &ModSpecification, _cache: ProviderCache) -> Option<ModInfo> {
-        let path = Path::new(&spec.url);
-        let name = path
-            .file_name()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| spec.url.to_string());
-        Some(ModInfo {
-            provider: FILE_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::unresolvable(
-                spec.url.clone().into(),
-                path.file_name()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "unknown".to_string()),
-            ),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            modio_tags: None,
-            modio_id: None,
-        })
+        Some(mod_info(spec))
     }
 
     fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
         true
     }
 
-    fn get_version_name(&self, _spec: &ModSpecification, _cache: ProviderCache) -> Option<String> {
-        Some("latest".to_string())
+    fn get_version_name(&self, spec: &ModSpecification, _cache: ProviderCache) -> Option<String> {
+        Some(match split_sha256_pin(&spec.url) {
+            Ok((_, Some(hash))) => format!("sha256 {}", &hash[..8]),
+            _ => "latest".to_string(),
+        })
     }
 }
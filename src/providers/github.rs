@@ -0,0 +1,657 @@
+use std::sync::OnceLock;
+
+use mint_lib::update::GITHUB_REQ_USER_AGENT;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: GITHUB_PROVIDER_ID,
+        new: GitHubProvider::new_provider,
+        can_provide,
+        parameters: &[
+            super::ProviderParameter {
+                id: "token",
+                name: "Token",
+                description: "GitHub personal access token, raises the API rate limit",
+                link: Some("https://github.com/settings/tokens"),
+                optional: true,
+                secret: true,
+            },
+            super::ProviderParameter {
+                id: "api_url",
+                name: "API URL",
+                description: "GitHub API endpoint, defaults to https://api.github.com",
+                link: None,
+                optional: true,
+                secret: false,
+            },
+        ]
+    }
+}
+
+const GITHUB_PROVIDER_ID: &str = "github";
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// Whether `url` is a repository or release asset URL handled by this provider rather than the
+/// generic HTTP provider.
+pub fn can_provide(url: &str) -> bool {
+    re_repo().is_match(url) || re_asset().is_match(url)
+}
+
+/// `https://github.com/<owner>/<repo>[@<tag>][?asset=<pattern>]`
+static RE_REPO: OnceLock<regex::Regex> = OnceLock::new();
+fn re_repo() -> &'static regex::Regex {
+    RE_REPO.get_or_init(|| {
+        regex::Regex::new(r"^https?://github\.com/(?P<owner>[\w.-]+)/(?P<repo>[\w.-]+?)(@(?P<tag>[^/?#]+))?/?(\?asset=(?P<asset>[^#]+))?$").unwrap()
+    })
+}
+
+/// `https://github.com/<owner>/<repo>/releases/download/<tag>/<asset>`
+static RE_ASSET: OnceLock<regex::Regex> = OnceLock::new();
+fn re_asset() -> &'static regex::Regex {
+    RE_ASSET.get_or_init(|| {
+        regex::Regex::new(r"^https?://github\.com/(?P<owner>[\w.-]+)/(?P<repo>[\w.-]+)/releases/download/(?P<tag>[^/]+)/(?P<asset>[^/?#]+)$").unwrap()
+    })
+}
+
+/// Parsed repository URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RepoSpec {
+    owner: String,
+    repo: String,
+    tag: Option<String>,
+    asset: Option<String>,
+}
+
+impl RepoSpec {
+    fn parse(url: &str) -> Option<Self> {
+        let captures = re_repo().captures(url)?;
+        Some(Self {
+            owner: captures["owner"].to_string(),
+            repo: captures["repo"].to_string(),
+            tag: captures.name("tag").map(|m| m.as_str().to_string()),
+            asset: captures.name("asset").map(|m| m.as_str().to_string()),
+        })
+    }
+
+    /// Key of the repository in [`GitHubProviderCache::releases`].
+    fn key(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+
+    fn to_spec(&self, tag: Option<&str>) -> ModSpecification {
+        let mut url = format!("https://github.com/{}/{}", self.owner, self.repo);
+        if let Some(tag) = tag {
+            url.push('@');
+            url.push_str(tag);
+        }
+        if let Some(asset) = &self.asset {
+            url.push_str("?asset=");
+            url.push_str(asset);
+        }
+        ModSpecification::new(url)
+    }
+
+    fn asset_url(&self, tag: &str, asset: &str) -> String {
+        format!(
+            "https://github.com/{}/{}/releases/download/{tag}/{asset}",
+            self.owner, self.repo
+        )
+    }
+}
+
+/// Parsed release asset URL.
+struct AssetSpec {
+    owner: String,
+    repo: String,
+    tag: String,
+    asset: String,
+}
+
+impl AssetSpec {
+    fn parse(url: &str) -> Option<Self> {
+        let captures = re_asset().captures(url)?;
+        Some(Self {
+            owner: captures["owner"].to_string(),
+            repo: captures["repo"].to_string(),
+            tag: captures["tag"].to_string(),
+            asset: captures["asset"].to_string(),
+        })
+    }
+
+    fn key(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+}
+
+/// Release as returned by the GitHub API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Case-insensitive match of `name` against `pattern`, which may contain `*` and `?` wildcards.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase().chars().collect::<Vec<_>>();
+    let name = name.to_ascii_lowercase().chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bn)) => {
+                    p = bp + 1;
+                    n = bn + 1;
+                    backtrack = Some((bp, bn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Pick the release `tag`, or the latest stable release (falling back to the latest prerelease)
+/// if `None`. Releases are ordered newest first like the API returns them.
+fn select_release<'a>(releases: &'a [Release], tag: Option<&str>) -> Option<&'a Release> {
+    let mut releases = releases.iter().filter(|r| !r.draft);
+    match tag {
+        Some(tag) => releases.find(|r| r.tag_name == tag),
+        None => {
+            let releases = releases.collect::<Vec<_>>();
+            releases
+                .iter()
+                .find(|r| !r.prerelease)
+                .or(releases.first())
+                .copied()
+        }
+    }
+}
+
+/// Pattern describing the assets picked when no pattern is given, for error messages.
+fn default_asset_pattern() -> String {
+    std::iter::once("pak")
+        .chain(crate::archive::formats().flat_map(|f| f.extensions.iter().copied()))
+        .map(|ext| format!("*.{ext}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Pick the asset matching `pattern`, or the only .pak or archive asset if there is no pattern.
+fn select_asset<'a>(
+    url: &str,
+    release: &'a Release,
+    pattern: Option<&str>,
+) -> Result<&'a ReleaseAsset, ProviderError> {
+    let candidates = release
+        .assets
+        .iter()
+        .filter(|a| match pattern {
+            Some(pattern) => glob_match(pattern, &a.name),
+            None => {
+                let name = a.name.to_ascii_lowercase();
+                name.ends_with(".pak") || crate::archive::is_archive_name(&name)
+            }
+        })
+        .collect::<Vec<_>>();
+    match candidates.as_slice() {
+        [asset] => Ok(asset),
+        [] => NoMatchingReleaseAssetSnafu {
+            url,
+            tag: release.tag_name.clone(),
+            pattern: pattern
+                .map(str::to_owned)
+                .unwrap_or_else(default_asset_pattern),
+            assets: release
+                .assets
+                .iter()
+                .map(|a| a.name.clone())
+                .collect::<Vec<_>>(),
+        }
+        .fail(),
+        _ => AmbiguousReleaseAssetSnafu {
+            url,
+            tag: release.tag_name.clone(),
+            assets: candidates
+                .iter()
+                .map(|a| a.name.clone())
+                .collect::<Vec<_>>(),
+        }
+        .fail(),
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GitHubProviderCache {
+    /// Releases keyed by `owner/repo`, newest first.
+    releases: HashMap<String, Vec<Release>>,
+    /// Downloaded assets keyed by release asset URL.
+    url_blobs: HashMap<String, BlobRef>,
+}
+
+impl GitHubProviderCache {
+    /// Repository key of a repository or release asset URL.
+    fn key(url: &str) -> Option<String> {
+        RepoSpec::parse(url)
+            .map(|s| s.key())
+            .or_else(|| AssetSpec::parse(url).map(|s| s.key()))
+    }
+
+    fn find_asset(&self, asset: &AssetSpec) -> Option<&ReleaseAsset> {
+        self.releases
+            .get(&asset.key())?
+            .iter()
+            .find(|r| r.tag_name == asset.tag)?
+            .assets
+            .iter()
+            .find(|a| a.name == asset.asset)
+    }
+}
+
+#[typetag::serde]
+impl ModProviderCache for GitHubProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn subset(&self, urls: &[&str]) -> Box<dyn ModProviderCache> {
+        Box::new(Self {
+            releases: urls
+                .iter()
+                .filter_map(|url| {
+                    let key = Self::key(url)?;
+                    let releases = self.releases.get(&key)?.clone();
+                    Some((key, releases))
+                })
+                .collect(),
+            url_blobs: urls
+                .iter()
+                .filter_map(|url| Some((url.to_string(), self.url_blobs.get(*url)?.clone())))
+                .collect(),
+        })
+    }
+
+    fn merge(&mut self, other: &dyn ModProviderCache) {
+        if let Some(other) = other.as_any().downcast_ref::<Self>() {
+            self.releases.extend(
+                other
+                    .releases
+                    .iter()
+                    .map(|(key, releases)| (key.clone(), releases.clone())),
+            );
+            self.url_blobs.extend(
+                other
+                    .url_blobs
+                    .iter()
+                    .map(|(url, blob)| (url.clone(), blob.clone())),
+            );
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GitHubProvider {
+    client: reqwest::Client,
+    api_url: String,
+    token: Option<String>,
+}
+
+impl GitHubProvider {
+    pub fn new_provider(
+        parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        let param = |id: &str| parameters.get(id).filter(|v| !v.is_empty()).cloned();
+        let client = reqwest::Client::builder()
+            .user_agent(GITHUB_REQ_USER_AGENT)
+            .build()
+            .map_err(|_| ProviderError::InitProviderFailed {
+                id: GITHUB_PROVIDER_ID,
+                parameters: parameters.clone(),
+            })?;
+        Ok(Arc::new(Self {
+            client,
+            api_url: param("api_url")
+                .map(|u| u.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| DEFAULT_API_URL.to_string()),
+            token: param("token"),
+        }))
+    }
+
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.get(url);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Fetch every release of the repository, following the `Link` header through all pages.
+    async fn fetch_releases(&self, owner: &str, repo: &str) -> Result<Vec<Release>, ProviderError> {
+        let mut next = Some(format!(
+            "{}/repos/{owner}/{repo}/releases?per_page=100",
+            self.api_url
+        ));
+        let mut releases = vec![];
+        while let Some(url) = next.take() {
+            info!("fetching releases {url:?}...");
+            let response = self
+                .get(&url)
+                .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+                .send()
+                .await
+                .context(RequestFailedSnafu { url: url.clone() })?
+                .error_for_status()
+                .context(ResponseSnafu { url: url.clone() })?;
+            next = next_page_url(response.headers());
+            releases.extend(
+                response
+                    .json::<Vec<Release>>()
+                    .await
+                    .context(ResponseSnafu { url })?,
+            );
+        }
+        Ok(releases)
+    }
+
+    /// Releases of the repository from the cache, fetching them if missing or `update` is set.
+    async fn releases(
+        &self,
+        owner: &str,
+        repo: &str,
+        update: bool,
+        cache: &ProviderCache,
+    ) -> Result<Vec<Release>, ProviderError> {
+        let key = format!("{owner}/{repo}");
+        if !update {
+            let cached = cache
+                .read()
+                .unwrap()
+                .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+                .and_then(|c| c.releases.get(&key).cloned());
+            if let Some(releases) = cached {
+                return Ok(releases);
+            }
+        }
+        let releases = self.fetch_releases(owner, repo).await?;
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+            .releases
+            .insert(key, releases.clone());
+        Ok(releases)
+    }
+}
+
+/// URL of the `rel="next"` link of a paginated response, e.g.
+/// `Link: <https://api.github.com/...&page=2>; rel="next", <...&page=5>; rel="last"`.
+fn next_page_url(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let links = headers.get(reqwest::header::LINK)?.to_str().ok()?;
+    links.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
+
+fn mod_info(
+    spec: &ModSpecification,
+    repo: &RepoSpec,
+    releases: &[Release],
+) -> Result<ModInfo, ProviderError> {
+    let release = select_release(releases, repo.tag.as_deref()).context(ReleaseNotFoundSnafu {
+        url: spec.url.clone(),
+        tag: repo.tag.clone(),
+    })?;
+    let asset = select_asset(&spec.url, release, repo.asset.as_deref())?;
+    Ok(ModInfo {
+        provider: GITHUB_PROVIDER_ID,
+        name: repo.repo.clone(),
+        spec: repo.to_spec(None),
+        versions: releases
+            .iter()
+            .filter(|r| !r.draft)
+            .map(|r| repo.to_spec(Some(&r.tag_name)))
+            .collect(),
+        resolution: ModResolution::resolvable(
+            repo.asset_url(&release.tag_name, &asset.name).into(),
+        ),
+        suggested_require: false,
+        suggested_dependencies: vec![],
+        modio_tags: None,
+        modio_id: None,
+    })
+}
+
+fn asset_mod_info(spec: &ModSpecification, asset: &AssetSpec) -> ModInfo {
+    ModInfo {
+        provider: GITHUB_PROVIDER_ID,
+        name: asset.asset.clone(),
+        spec: spec.clone(),
+        versions: vec![],
+        resolution: ModResolution::resolvable(spec.url.as_str().into()),
+        suggested_require: false,
+        suggested_dependencies: vec![],
+        modio_tags: None,
+        modio_id: None,
+    }
+}
+
+#[async_trait::async_trait]
+impl ModProvider for GitHubProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+        cache: ProviderCache,
+    ) -> Result<ModResponse, ProviderError> {
+        if let Some(asset) = AssetSpec::parse(&spec.url) {
+            return Ok(ModResponse::Resolve(asset_mod_info(spec, &asset)));
+        }
+        let repo = RepoSpec::parse(&spec.url).context(InvalidUrlSnafu {
+            url: spec.url.clone(),
+        })?;
+
+        let releases = self
+            .releases(&repo.owner, &repo.repo, update, &cache)
+            .await?;
+        // a pinned tag may be newer than the cached releases
+        let releases = if !update
+            && repo.tag.is_some()
+            && select_release(&releases, repo.tag.as_deref()).is_none()
+        {
+            self.releases(&repo.owner, &repo.repo, true, &cache).await?
+        } else {
+            releases
+        };
+        Ok(ModResponse::Resolve(mod_info(spec, &repo, &releases)?))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let url = &res.url.0;
+        let cached = (!update)
+            .then(|| {
+                cache
+                    .read()
+                    .unwrap()
+                    .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+                    .and_then(|c| c.url_blobs.get(url))
+                    .and_then(|r| blob_cache.get_path(r))
+            })
+            .flatten();
+
+        let path = match cached {
+            Some(path) => path,
+            None => {
+                // the API may serve assets from elsewhere, e.g. a stand-in server
+                let download_url = AssetSpec::parse(url)
+                    .and_then(|asset| {
+                        cache
+                            .read()
+                            .unwrap()
+                            .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+                            .and_then(|c| c.find_asset(&asset))
+                            .map(|a| a.browser_download_url.clone())
+                    })
+                    .unwrap_or_else(|| url.clone());
+
+                info!("downloading mod {download_url:?}...");
+                let response = self
+                    .get(&download_url)
+                    .header(reqwest::header::ACCEPT, "application/octet-stream")
+                    .send()
+                    .await
+                    .context(RequestFailedSnafu {
+                        url: download_url.clone(),
+                    })?
+                    .error_for_status()
+                    .context(ResponseSnafu {
+                        url: download_url.clone(),
+                    })?;
+                let size = response.content_length();
+
+                use futures::stream::TryStreamExt;
+
+                let mut buf = vec![];
+                let mut stream = response.bytes_stream();
+                while let Some(bytes) = stream.try_next().await.context(FetchSnafu {
+                    url: download_url.clone(),
+                })? {
+                    buf.extend_from_slice(&bytes);
+                    if let (Some(size), Some(tx)) = (size, &tx) {
+                        tx.send(FetchProgress::Progress {
+                            resolution: res.clone(),
+                            progress: buf.len() as u64,
+                            size,
+                        })
+                        .await
+                        .unwrap();
+                    }
+                }
+
+                let blob = blob_cache.write(&buf)?;
+                let path = blob_cache.get_path(&blob).unwrap();
+                cache
+                    .write()
+                    .unwrap()
+                    .get_mut::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+                    .url_blobs
+                    .insert(url.to_owned(), blob);
+                path
+            }
+        };
+
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, cache: ProviderCache) -> Result<(), ProviderError> {
+        let repos = cache
+            .read()
+            .unwrap()
+            .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+            .map(|c| c.releases.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        for key in repos {
+            if let Some((owner, repo)) = key.split_once('/') {
+                self.releases(owner, repo, true, &cache).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        let url = format!("{}/rate_limit", self.api_url);
+        self.get(&url)
+            .send()
+            .await
+            .context(RequestFailedSnafu { url: url.clone() })?
+            .error_for_status()
+            .context(ResponseSnafu { url })?;
+        Ok(())
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+        if let Some(asset) = AssetSpec::parse(&spec.url) {
+            return Some(asset_mod_info(spec, &asset));
+        }
+        let repo = RepoSpec::parse(&spec.url)?;
+        let cache = cache.read().unwrap();
+        let releases = cache
+            .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)?
+            .releases
+            .get(&repo.key())?;
+        mod_info(spec, &repo, releases).ok()
+    }
+
+    fn is_pinned(&self, spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        AssetSpec::parse(&spec.url).is_some()
+            || RepoSpec::parse(&spec.url).is_some_and(|r| r.tag.is_some())
+    }
+
+    fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String> {
+        if let Some(asset) = AssetSpec::parse(&spec.url) {
+            return Some(asset.tag);
+        }
+        let repo = RepoSpec::parse(&spec.url)?;
+        if let Some(tag) = repo.tag {
+            return Some(tag);
+        }
+        let cache = cache.read().unwrap();
+        let releases = cache
+            .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)?
+            .releases
+            .get(&repo.key())?;
+        select_release(releases, None).map(|r| format!("latest ({})", r.tag_name))
+    }
+}
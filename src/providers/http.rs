@@ -1,7 +1,7 @@
 use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::providers::*;
 
@@ -16,14 +16,61 @@ inventory::submit! {
                 .map_or(false, |h| {
                     !["mod.io", "drg.mod.io", "drg.old.mod.io"].contains(&h.as_str())
                 })
+                && !super::github::can_provide(url)
         },
-        parameters: &[],
+        parameters: &[
+            super::ProviderParameter {
+                id: "content_types",
+                name: "Content type overrides",
+                description: "Whitespace separated <url prefix>=<content type>[,<content type>...] \
+                    entries. Downloads from matching URLs served with one of the content types are \
+                    accepted without checking that they are a .pak or an archive",
+                link: None,
+                optional: true,
+                secret: false,
+            },
+            super::ProviderParameter {
+                id: "link_rules",
+                name: "Share link rules",
+                description: "Whitespace separated pairs of <regex> <replacement> rewriting share \
+                    links to direct download links, tried before the built-in rules. Replacements \
+                    can refer to named groups as ${name}",
+                link: None,
+                optional: true,
+                secret: false,
+            },
+        ],
+    }
+}
+
+/// Validators of a downloaded URL, sent with conditional requests to check for updates.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct HttpValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl HttpValidators {
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        Self {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        }
     }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct HttpProviderCache {
+    /// Downloaded files keyed by URL, the [`BlobRef`] doubles as the content hash.
     url_blobs: HashMap<String, BlobRef>,
+    #[serde(default)]
+    url_validators: HashMap<String, HttpValidators>,
 }
 
 #[typetag::serde]
@@ -39,42 +86,107 @@ impl ModProviderCache for HttpProviderCache {
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
+
+    fn subset(&self, urls: &[&str]) -> Box<dyn ModProviderCache> {
+        Box::new(Self {
+            url_blobs: urls
+                .iter()
+                .filter_map(|url| Some((url.to_string(), self.url_blobs.get(*url)?.clone())))
+                .collect(),
+            url_validators: urls
+                .iter()
+                .filter_map(|url| Some((url.to_string(), self.url_validators.get(*url)?.clone())))
+                .collect(),
+        })
+    }
+
+    fn merge(&mut self, other: &dyn ModProviderCache) {
+        if let Some(other) = other.as_any().downcast_ref::<Self>() {
+            self.url_blobs.extend(
+                other
+                    .url_blobs
+                    .iter()
+                    .map(|(url, blob)| (url.clone(), blob.clone())),
+            );
+            self.url_validators.extend(
+                other
+                    .url_validators
+                    .iter()
+                    .map(|(url, validators)| (url.clone(), validators.clone())),
+            );
+        }
+    }
+}
+
+/// Format an HTTP date such as `Wed, 21 Oct 2015 07:28:00 GMT` as `2015-10-21`.
+fn format_http_date(date: &str) -> Option<String> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let mut parts = date.split_whitespace().skip(1);
+    let day: u32 = parts.next()?.parse().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? + 1;
+    let year: u32 = parts.next()?.parse().ok()?;
+    Some(format!("{year:04}-{month:02}-{day:02}"))
 }
 
 #[derive(Debug)]
 pub struct HttpProvider {
     client: reqwest::Client,
+    /// URL prefixes and the content types accepted from them without checking the content.
+    content_type_overrides: Vec<(String, Vec<String>)>,
+    /// User supplied share link rules, tried before [`builtin_link_rules`].
+    link_rules: Vec<LinkRule>,
 }
 
 impl HttpProvider {
+    /// Invalid entries of the optional parameters are logged and ignored rather than failing,
+    /// which would prevent every other provider from being used as well.
     pub fn new_provider(
-        _parameters: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
     ) -> Result<Arc<dyn ModProvider>, ProviderError> {
-        Ok(Arc::new(Self::new()))
+        let content_type_overrides = parameters
+            .get("content_types")
+            .map(|value| parse_content_type_overrides(value))
+            .unwrap_or_default();
+        let link_rules = parameters
+            .get("link_rules")
+            .map(|value| LinkRule::parse_all(value))
+            .unwrap_or_default();
+        Ok(Arc::new(Self {
+            content_type_overrides,
+            link_rules,
+            ..Self::new()
+        }))
     }
 
     pub fn new() -> Self {
         Self {
             client: reqwest::Client::new(),
+            content_type_overrides: vec![],
+            link_rules: vec![],
         }
     }
-}
 
-static RE_MOD: OnceLock<regex::Regex> = OnceLock::new();
-fn re_mod() -> &'static regex::Regex {
-    RE_MOD.get_or_init(|| regex::Regex::new(r"^https?://(?P<hostname>[^/]+)(/|$)").unwrap())
-}
-
-const HTTP_PROVIDER_ID: &str = "http";
+    /// URL to download the mod `url` from, with share links rewritten to direct download links.
+    /// A `#sha256=` pin is kept.
+    fn download_url(&self, url: &str) -> Result<String, ProviderError> {
+        let (base, pin) = split_sha256_pin(url)?;
+        let rewritten = self
+            .link_rules
+            .iter()
+            .chain(builtin_link_rules())
+            .find_map(|rule| rule.apply(base))
+            .unwrap_or_else(|| base.to_string());
+        Ok(match pin {
+            Some(hash) => format!("{rewritten}#sha256={hash}"),
+            None => rewritten,
+        })
+    }
 
-#[async_trait::async_trait]
-impl ModProvider for HttpProvider {
-    async fn resolve_mod(
-        &self,
-        spec: &ModSpecification,
-        _update: bool,
-        _cache: ProviderCache,
-    ) -> Result<ModResponse, ProviderError> {
+    fn mod_info(&self, spec: &ModSpecification) -> Result<ModInfo, ProviderError> {
+        let download_url = self.download_url(&spec.url)?;
         let Ok(url) = url::Url::parse(&spec.url) else {
             return Err(ProviderError::InvalidUrl {
                 url: spec.url.to_string(),
@@ -87,124 +199,484 @@ impl ModProvider for HttpProvider {
             .map(|s| s.to_string())
             .unwrap_or_else(|| url.to_string());
 
-        Ok(ModResponse::Resolve(ModInfo {
+        Ok(ModInfo {
             provider: HTTP_PROVIDER_ID,
             name,
             spec: spec.clone(),
             versions: vec![],
-            resolution: ModResolution::resolvable(spec.url.as_str().into()),
+            resolution: ModResolution::resolvable(download_url.into()),
             suggested_require: false,
             suggested_dependencies: vec![],
             modio_tags: None,
             modio_id: None,
-        }))
+        })
     }
 
-    async fn fetch_mod(
+    /// Whether `content_type` is accepted from `url` without checking the content.
+    fn accepts_content_type(&self, url: &str, content_type: Option<&str>) -> bool {
+        let Some(content_type) = content_type else {
+            return false;
+        };
+        let media_type = media_type(content_type);
+        self.content_type_overrides
+            .iter()
+            .any(|(prefix, types)| url.starts_with(prefix) && types.contains(&media_type))
+    }
+}
+
+/// Parse `<url prefix>=<content type>[,<content type>...]` entries separated by whitespace,
+/// skipping invalid ones.
+fn parse_content_type_overrides(value: &str) -> Vec<(String, Vec<String>)> {
+    value
+        .split_whitespace()
+        .filter_map(|entry| {
+            let parsed = entry.rsplit_once('=').and_then(|(prefix, types)| {
+                let types = types
+                    .split(',')
+                    .map(media_type)
+                    .filter(|t| !t.is_empty())
+                    .collect::<Vec<_>>();
+                (!prefix.is_empty() && !types.is_empty()).then(|| (prefix.to_string(), types))
+            });
+            if parsed.is_none() {
+                warn!("ignoring invalid content type override {entry:?}");
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Rewrites URLs matching `pattern` to `replacement`, which can refer to named groups as
+/// `${name}` and to the OneDrive share token of the URL as `{share_token}`.
+#[derive(Debug)]
+struct LinkRule {
+    pattern: regex::Regex,
+    replacement: String,
+}
+
+impl LinkRule {
+    fn new(pattern: &str, replacement: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: regex::Regex::new(pattern)?,
+            replacement: replacement.to_string(),
+        })
+    }
+
+    /// Parse whitespace separated pairs of `<regex> <replacement>`, skipping invalid ones.
+    fn parse_all(value: &str) -> Vec<Self> {
+        let parts = value.split_whitespace().collect::<Vec<_>>();
+        parts
+            .chunks(2)
+            .filter_map(|pair| match pair {
+                [pattern, replacement] => Self::new(pattern, replacement)
+                    .inspect_err(|e| warn!("ignoring link rule {pattern:?}: {e}"))
+                    .ok(),
+                _ => {
+                    warn!("ignoring link rule {:?} without a replacement", pair[0]);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn apply(&self, url: &str) -> Option<String> {
+        let captures = self.pattern.captures(url)?;
+        let replacement = self
+            .replacement
+            .replace("{share_token}", &onedrive_share_token(url));
+        let mut rewritten = String::new();
+        captures.expand(&replacement, &mut rewritten);
+        Some(rewritten)
+    }
+}
+
+/// Rules for the share links of common file hosts.
+static BUILTIN_LINK_RULES: OnceLock<Vec<LinkRule>> = OnceLock::new();
+fn builtin_link_rules() -> &'static [LinkRule] {
+    BUILTIN_LINK_RULES.get_or_init(|| {
+        [
+            // https://drive.google.com/file/d/<id>/view?usp=sharing
+            (
+                r"^https://drive\.google\.com/file/d/(?P<id>[\w-]+)(/[^?#]*)?(\?[^#]*)?$",
+                "https://drive.google.com/uc?export=download&confirm=t&id=${id}",
+            ),
+            // https://drive.google.com/open?id=<id>
+            (
+                r"^https://drive\.google\.com/(open|uc)\?([^#]*&)?id=(?P<id>[\w-]+)(&[^#]*)?$",
+                "https://drive.google.com/uc?export=download&confirm=t&id=${id}",
+            ),
+            // https://www.dropbox.com/scl/fi/<id>/Mod.zip?rlkey=<key>&dl=0
+            (
+                r"^https://(www\.)?dropbox\.com/(?P<path>[^?#]+)(?P<query>\?[^#]*)?$",
+                "https://dl.dropboxusercontent.com/${path}${query}",
+            ),
+            // https://1drv.ms/u/s!<id>
+            (
+                r"^https://1drv\.ms/.+$",
+                "https://api.onedrive.com/v1.0/shares/{share_token}/root/content",
+            ),
+            // https://onedrive.live.com/redir?cid=<cid>&resid=<resid>&authkey=<key>
+            (
+                r"^https://onedrive\.live\.com/(redir|embed)?\?(?P<query>[^#]*)$",
+                "https://onedrive.live.com/download?${query}",
+            ),
+            // https://media.discordapp.net/attachments/<channel>/<id>/Mod.zip?ex=...
+            (
+                r"^https://media\.discordapp\.net/attachments/(?P<rest>[^#]+)$",
+                "https://cdn.discordapp.com/attachments/${rest}",
+            ),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| LinkRule::new(pattern, replacement).unwrap())
+        .collect()
+    })
+}
+
+/// Encode a URL as a share token for the OneDrive API: `u!` followed by the unpadded base64url
+/// encoded URL.
+fn onedrive_share_token(url: &str) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    let mut token = "u!".to_string();
+    for chunk in url.as_bytes().chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | ((*b as u32) << (16 - 8 * i)));
+        for i in 0..=chunk.len() {
+            token.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+        }
+    }
+    token
+}
+
+/// Lowercase media type of a content type without parameters such as the charset.
+fn media_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Length of the start of a download checked for magic bytes and HTML titles.
+const SNIFF_HEAD_LEN: u64 = 8 * 1024;
+/// Length of the end of a download searched for the pak footer, which is well below this.
+const SNIFF_TAIL_LEN: u64 = 512;
+const PAK_MAGIC: [u8; 4] = 0x5A6F12E1u32.to_le_bytes();
+
+/// Check that a download is a pak or a supported archive by its magic bytes, regardless of the
+/// content type the server claims, which is often wrong.
+fn check_content(
+    url: &str,
+    content_type: Option<&str>,
+    head: &[u8],
+    tail: &[u8],
+) -> Result<(), ProviderError> {
+    let is_archive = crate::archive::detect_format(head).is_some();
+    let is_pak = tail.windows(PAK_MAGIC.len()).any(|w| w == PAK_MAGIC);
+    if is_archive || is_pak {
+        return Ok(());
+    }
+    if content_type.is_some_and(|t| media_type(t) == "text/html") || looks_like_html(head) {
+        return HtmlPageSnafu {
+            url,
+            title: html_title(head),
+        }
+        .fail();
+    }
+    UnrecognizedContentSnafu {
+        url,
+        content_type: content_type.map(media_type),
+    }
+    .fail()
+}
+
+fn looks_like_html(head: &[u8]) -> bool {
+    let start = String::from_utf8_lossy(&head[..head.len().min(256)])
+        .trim_start()
+        .to_ascii_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html")
+}
+
+/// Text of the `<title>` element of an HTML page, if it's within `head`.
+fn html_title(head: &[u8]) -> Option<String> {
+    let html = String::from_utf8_lossy(head);
+    // lowercasing ASCII keeps the offsets the same
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = html[start..end]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+static RE_MOD: OnceLock<regex::Regex> = OnceLock::new();
+fn re_mod() -> &'static regex::Regex {
+    RE_MOD.get_or_init(|| regex::Regex::new(r"^https?://(?P<hostname>[^/]+)(/|$)").unwrap())
+}
+
+const HTTP_PROVIDER_ID: &str = "http";
+
+/// How often a download is resumed after the connection dropped before giving up.
+const MAX_RESUMES: usize = 3;
+
+/// Validator for `If-Range`, which requires a strong ETag or a date.
+fn range_validator(validators: &HttpValidators) -> Option<String> {
+    validators
+        .etag
+        .clone()
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| validators.last_modified.clone())
+}
+
+/// Parse a `Content-Range` such as `bytes 100-199/200` into the start and the total size, if
+/// known.
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+    Some((start.parse().ok()?, total.parse().ok()))
+}
+
+impl HttpProvider {
+    /// Download `res` into the blob cache and remember it and its validators. If `cached` is
+    /// set, the request is made conditional on the content having changed since it was fetched.
+    ///
+    /// The response is streamed into a partial blob, which is resumed with a range request if
+    /// the connection drops or a previous download of the same URL was interrupted. If the URL is
+    /// pinned to a hash, the data is only stored if it matches.
+    async fn download(
         &self,
         res: &ModResolution,
-        update: bool,
+        cached: Option<PathBuf>,
+        validators: Option<HttpValidators>,
         cache: ProviderCache,
         blob_cache: &BlobCache,
-        tx: Option<Sender<FetchProgress>>,
+        tx: &Option<Sender<FetchProgress>>,
     ) -> Result<PathBuf, ProviderError> {
+        use futures::stream::TryStreamExt;
+        use reqwest::header;
+        use reqwest::StatusCode;
+
         let url = &res.url;
-        Ok(
-            if let Some(path) = if update {
-                None
-            } else {
-                cache
-                    .read()
-                    .unwrap()
-                    .get::<HttpProviderCache>(HTTP_PROVIDER_ID)
-                    .and_then(|c| c.url_blobs.get(&url.0))
-                    .and_then(|r| blob_cache.get_path(r))
-            } {
-                if let Some(tx) = tx {
-                    tx.send(FetchProgress::Complete {
-                        resolution: res.clone(),
-                    })
-                    .await
-                    .unwrap();
+        let (_, pin) = split_sha256_pin(&url.0)?;
+        let mut partial = blob_cache.partial(&url.0)?;
+        let mut resumes = 0;
+        'request: loop {
+            // the body is stored as is, so it must not be compressed. This also keeps sizes and
+            // ranges in terms of the stored bytes
+            let mut request = self
+                .client
+                .get(&url.0)
+                .header(header::ACCEPT_ENCODING, "identity");
+            // only ask for an unchanged response if there is a cached file to fall back to
+            if let (Some(_), Some(validators)) = (&cached, &validators) {
+                if let Some(etag) = &validators.etag {
+                    request = request.header(header::IF_NONE_MATCH, etag);
                 }
-                path
-            } else {
-                info!("downloading mod {url:?}...");
-                let response = self
-                    .client
-                    .get(&url.0)
-                    .send()
-                    .await
-                    .context(RequestFailedSnafu {
-                        url: url.0.to_string(),
-                    })?
-                    .error_for_status()
-                    .context(ResponseSnafu {
-                        url: url.0.to_string(),
-                    })?;
-                let size = response.content_length(); // TODO will be incorrect if compressed
-                if let Some(mime) = response
-                    .headers()
-                    .get(reqwest::header::HeaderName::from_static("content-type"))
-                {
-                    let content_type = mime.to_str().context(InvalidMimeSnafu {
-                        url: url.0.to_string(),
-                    })?;
-                    ensure!(
-                        ["application/zip", "application/octet-stream"].contains(&content_type),
-                        UnexpectedContentTypeSnafu {
-                            found_content_type: content_type.to_string(),
-                            url: url.0.to_string(),
-                        }
-                    );
+                if let Some(last_modified) = &validators.last_modified {
+                    request = request.header(header::IF_MODIFIED_SINCE, last_modified);
                 }
+            }
+            if let (1.., Some(validator)) = (partial.downloaded(), partial.validator()) {
+                request = request
+                    .header(header::RANGE, format!("bytes={}-", partial.downloaded()))
+                    .header(header::IF_RANGE, validator);
+            }
 
-                use futures::stream::TryStreamExt;
-                use tokio::io::AsyncWriteExt;
-
-                let mut cursor = std::io::Cursor::new(vec![]);
-                let mut stream = response.bytes_stream();
-                while let Some(bytes) = stream.try_next().await.with_context(|_| FetchSnafu {
+            info!("downloading mod {url:?}...");
+            let response = request
+                .send()
+                .await
+                .context(RequestFailedSnafu {
                     url: url.0.to_string(),
-                })? {
-                    cursor
-                        .write_all(&bytes)
-                        .await
-                        .with_context(|_| BufferIoSnafu {
+                })?
+                .error_for_status()
+                .context(ResponseSnafu {
+                    url: url.0.to_string(),
+                })?;
+
+            if let Some(path) = cached
+                .as_ref()
+                .filter(|_| response.status() == StatusCode::NOT_MODIFIED)
+            {
+                info!("mod {url:?} not modified");
+                partial.discard();
+                return Ok(path.clone());
+            }
+
+            let content_type = response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string());
+            let response_validators = HttpValidators::from_headers(response.headers());
+
+            let content_range = (response.status() == StatusCode::PARTIAL_CONTENT)
+                .then(|| response.headers().get(header::CONTENT_RANGE))
+                .flatten()
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range);
+            let total = match content_range {
+                Some((start, total)) if start == partial.downloaded() => {
+                    info!("resuming download of {url:?} at {start} bytes");
+                    total
+                }
+                _ => {
+                    partial.restart(range_validator(&response_validators))?;
+                    if response.status() == StatusCode::PARTIAL_CONTENT {
+                        // not the requested range, start over with a plain request
+                        ensure!(
+                            resumes < MAX_RESUMES,
+                            UnexpectedContentRangeSnafu {
+                                url: url.0.to_string(),
+                            }
+                        );
+                        resumes += 1;
+                        continue 'request;
+                    }
+                    response.content_length()
+                }
+            };
+            // the size is unknown if the server compressed the body regardless
+            let encoded = response
+                .headers()
+                .get(header::CONTENT_ENCODING)
+                .is_some_and(|e| e != "identity");
+            let size = total.filter(|_| !encoded);
+
+            let mut stream = response.bytes_stream();
+            loop {
+                match stream.try_next().await {
+                    Ok(Some(bytes)) => {
+                        partial.write(&bytes).with_context(|_| BufferIoSnafu {
                             url: url.0.to_string(),
                         })?;
-                    if let Some(size) = size {
-                        if let Some(tx) = &tx {
+                        if let (Some(size), Some(tx)) = (size, tx) {
                             tx.send(FetchProgress::Progress {
                                 resolution: res.clone(),
-                                progress: cursor.get_ref().len() as u64,
+                                progress: partial.downloaded(),
                                 size,
                             })
                             .await
                             .unwrap();
                         }
                     }
+                    Ok(None) => break,
+                    Err(e) if resumes < MAX_RESUMES && partial.validator().is_some() => {
+                        resumes += 1;
+                        warn!("download of {url:?} interrupted, resuming: {e}");
+                        continue 'request;
+                    }
+                    Err(e) => {
+                        return Err(e).context(FetchSnafu {
+                            url: url.0.to_string(),
+                        })
+                    }
                 }
+            }
 
-                let blob = blob_cache.write(&cursor.into_inner())?;
-                let path = blob_cache.get_path(&blob).unwrap();
-                cache
-                    .write()
-                    .unwrap()
-                    .get_mut::<HttpProviderCache>(HTTP_PROVIDER_ID)
-                    .url_blobs
-                    .insert(url.0.to_owned(), blob);
-
-                if let Some(tx) = tx {
-                    tx.send(FetchProgress::Complete {
-                        resolution: res.clone(),
+            if !self.accepts_content_type(&url.0, content_type.as_deref()) {
+                let read = |offset, len| {
+                    partial.read(offset, len).with_context(|_| BufferIoSnafu {
+                        url: url.0.to_string(),
                     })
-                    .await
-                    .unwrap();
+                };
+                let head = read(0, SNIFF_HEAD_LEN)?;
+                let tail = read(
+                    partial.downloaded().saturating_sub(SNIFF_TAIL_LEN),
+                    SNIFF_TAIL_LEN,
+                )?;
+                if let Err(e) = check_content(&url.0, content_type.as_deref(), &head, &tail) {
+                    partial.discard();
+                    return Err(e);
                 }
-                path
-            },
-        )
+            }
+
+            if let Some(expected) = &pin {
+                let actual = partial.hash();
+                if actual != *expected {
+                    partial.discard();
+                    return HashMismatchSnafu {
+                        url: url.0.to_string(),
+                        expected,
+                        actual,
+                    }
+                    .fail();
+                }
+            }
+
+            let blob = partial.finish(blob_cache)?;
+            let path = blob_cache.get_path(&blob).unwrap();
+            let mut lock = cache.write().unwrap();
+            let c = lock.get_mut::<HttpProviderCache>(HTTP_PROVIDER_ID);
+            c.url_blobs.insert(url.0.to_owned(), blob);
+            c.url_validators
+                .insert(url.0.to_owned(), response_validators);
+            return Ok(path);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ModProvider for HttpProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        _update: bool,
+        _cache: ProviderCache,
+    ) -> Result<ModResponse, ProviderError> {
+        Ok(ModResponse::Resolve(self.mod_info(spec)?))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let url = &res.url;
+        let (_, pin) = split_sha256_pin(&url.0)?;
+        // pinned content can't change so there is no need to check for updates, and content
+        // cached under a different hash must not be used
+        let pinned = pin
+            .as_deref()
+            .and_then(|hash| blob_cache.get_path_by_hash(hash));
+        let (cached, validators) = if pin.is_some() {
+            (None, None)
+        } else {
+            let lock = cache.read().unwrap();
+            let c = lock.get::<HttpProviderCache>(HTTP_PROVIDER_ID);
+            (
+                c.and_then(|c| c.url_blobs.get(&url.0))
+                    .and_then(|r| blob_cache.get_path(r)),
+                c.and_then(|c| c.url_validators.get(&url.0)).cloned(),
+            )
+        };
+
+        let path = match (pinned, cached) {
+            (Some(path), _) => path,
+            (None, Some(path)) if !update => path,
+            (None, cached) => {
+                self.download(res, cached, validators, cache, blob_cache, &tx)
+                    .await?
+            }
+        };
+
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
     }
 
     async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
@@ -216,30 +688,31 @@ impl ModProvider for HttpProvider {
     }
 
     fn get_mod_info(&self, spec: &ModSpecification, _cache: ProviderCache) -> Option<ModInfo> {
-        let url = url::Url::parse(&spec.url).ok()?;
-        let name = url
-            .path_segments()
-            .and_then(|s| s.last())
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| url.to_string());
-        Some(ModInfo {
-            provider: HTTP_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::resolvable(spec.url.as_str().into()),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            modio_tags: None,
-            modio_id: None,
-        })
+        self.mod_info(spec).ok()
     }
 
     fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
         true
     }
 
-    fn get_version_name(&self, _spec: &ModSpecification, _cache: ProviderCache) -> Option<String> {
-        Some("latest".to_string())
+    fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String> {
+        let url = self.download_url(&spec.url).ok()?;
+        let lock = cache.read().unwrap();
+        let Some(c) = lock.get::<HttpProviderCache>(HTTP_PROVIDER_ID) else {
+            return Some("latest".to_string());
+        };
+        let Some(blob) = c.url_blobs.get(&url) else {
+            return Some("latest".to_string());
+        };
+        let hash = &blob.hash()[..8];
+        let date = c
+            .url_validators
+            .get(&url)
+            .and_then(|v| v.last_modified.as_deref())
+            .and_then(format_http_date);
+        Some(match date {
+            Some(date) => format!("{date} ({hash})"),
+            None => hash.to_string(),
+        })
     }
 }
@@ -1,4 +1,6 @@
+pub mod dir;
 pub mod file;
+pub mod github;
 pub mod http;
 pub mod modio;
 #[macro_use]
@@ -65,6 +67,23 @@ pub trait ModProvider: Send + Sync {
     /// Check if provider is configured correctly
     async fn check(&self) -> Result<(), ProviderError>;
     fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo>;
+    /// Search the mods of the provider. Only mod.io supports searching.
+    async fn search(
+        &self,
+        _query: &modio::ModioSearch,
+    ) -> Result<Vec<modio::ModioSearchResult>, ProviderError> {
+        SearchUnsupportedSnafu.fail()
+    }
+    /// Mods the user is subscribed to. Only mod.io supports subscriptions.
+    async fn subscriptions(
+        &self,
+        _cache: ProviderCache,
+    ) -> Result<Vec<modio::ModioSearchResult>, ProviderError> {
+        SubscriptionsUnsupportedSnafu.fail()
+    }
+    async fn set_subscribed(&self, _modio_id: u32, _subscribed: bool) -> Result<(), ProviderError> {
+        SubscriptionsUnsupportedSnafu.fail()
+    }
     fn is_pinned(&self, spec: &ModSpecification, cache: ProviderCache) -> bool;
     fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String>;
 }
@@ -88,28 +107,83 @@ pub enum ProviderError {
     BlobCacheError { source: BlobCacheError },
     #[snafu(display("could not find mod provider for {url}"))]
     ProviderNotFound { url: String },
+    #[snafu(display(
+        "provider {id} requires parameter {parameter} ({description}), set {env_var} or pass --provider-param {id}.{parameter}=<value>"
+    ))]
+    MissingParameter {
+        id: &'static str,
+        parameter: &'static str,
+        description: &'static str,
+        env_var: String,
+    },
     NoProvider {
         url: String,
         factory: &'static ProviderFactory,
     },
     #[snafu(display("invalid url <{url}>"))]
     InvalidUrl { url: String },
+    #[snafu(display("no release{} found for <{url}>", tag.as_ref().map(|t| format!(" {t}")).unwrap_or_default()))]
+    ReleaseNotFound { url: String, tag: Option<String> },
+    #[snafu(display(
+        "release {tag} of <{url}> has no asset matching {pattern}, available assets: {}",
+        assets.join(", ")
+    ))]
+    NoMatchingReleaseAsset {
+        url: String,
+        tag: String,
+        pattern: String,
+        assets: Vec<String>,
+    },
+    #[snafu(display(
+        "release {tag} of <{url}> has multiple mod assets ({}), select one by appending ?asset=<name or pattern>",
+        assets.join(", ")
+    ))]
+    AmbiguousReleaseAsset {
+        url: String,
+        tag: String,
+        assets: Vec<String>,
+    },
+    #[snafu(display("{} is not a mod directory, it must contain FSD/Content", path.display()))]
+    InvalidModDirectory { path: PathBuf },
+    #[snafu(display("I/O error reading mod directory {}: {source}", path.display()))]
+    ModDirectoryIoError {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("failed to build pak from mod directory {}: {source}", path.display()))]
+    ModDirectoryPakError { source: repak::Error, path: PathBuf },
     #[snafu(display("request for <{url}> failed: {source}"))]
     RequestFailed { source: reqwest::Error, url: String },
     #[snafu(display("response from <{url}> failed: {source}"))]
     ResponseError { source: reqwest::Error, url: String },
-    #[snafu(display("mime from <{url}> contains non-ascii characters"))]
-    InvalidMime {
-        source: reqwest::header::ToStrError,
-        url: String,
-    },
-    #[snafu(display("unexpected content type from <{url}>: {found_content_type}"))]
-    UnexpectedContentType {
-        found_content_type: String,
+    #[snafu(display(
+        "<{url}> returned a web page{} instead of a mod, it may link to a download page rather than the file itself",
+        title.as_ref().map(|t| format!(" \"{t}\"")).unwrap_or_default()
+    ))]
+    HtmlPage { url: String, title: Option<String> },
+    #[snafu(display(
+        "content from <{url}>{} is neither a .pak nor a supported archive",
+        content_type.as_ref().map(|t| format!(" ({t})")).unwrap_or_default()
+    ))]
+    UnrecognizedContent {
         url: String,
+        content_type: Option<String>,
     },
     #[snafu(display("error while fetching mod <{url}>"))]
     FetchError { source: reqwest::Error, url: String },
+    #[snafu(display("server for <{url}> keeps responding with a different range than requested"))]
+    UnexpectedContentRange { url: String },
+    #[snafu(display("I/O error reading mod file {}: {source}", path.display()))]
+    FileIoError {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("content of <{url}> does not match the pinned hash: expected sha256 {expected}, got {actual}"))]
+    HashMismatch {
+        url: String,
+        expected: String,
+        actual: String,
+    },
     #[snafu(display("error processing <{url}> while writing to local buffer"))]
     BufferIoError { source: std::io::Error, url: String },
     #[snafu(display("preview mod links cannot be added directly, please subscribe to the mod on mod.io and and then use the non-preview link"))]
@@ -120,6 +194,10 @@ pub enum ProviderError {
     AmbiguousModNameId { name_id: String },
     #[snafu(display("no mods returned for name \"{name_id}\""))]
     NoModsForNameId { name_id: String },
+    #[snafu(display("provider does not support searching mods"))]
+    SearchUnsupported,
+    #[snafu(display("provider does not support subscriptions"))]
+    SubscriptionsUnsupported,
 }
 
 impl ProviderError {
@@ -133,6 +211,19 @@ impl ProviderError {
     }
 }
 
+/// Split a `#sha256=<hex>` fragment pinning the exact content off a mod URL, e.g.
+/// `https://host/mod.zip#sha256=...`. The hash is returned lowercase.
+pub fn split_sha256_pin(url: &str) -> Result<(&str, Option<String>), ProviderError> {
+    let Some((base, hash)) = url.rsplit_once("#sha256=") else {
+        return Ok((url, None));
+    };
+    ensure!(
+        hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()),
+        InvalidUrlSnafu { url }
+    );
+    Ok((base, Some(hash.to_ascii_lowercase())))
+}
+
 #[derive(Clone)]
 pub struct ProviderFactory {
     pub id: &'static str,
@@ -151,12 +242,45 @@ impl std::fmt::Debug for ProviderFactory {
     }
 }
 
+impl ProviderFactory {
+    pub fn find(id: &str) -> Option<&'static ProviderFactory> {
+        ModStore::get_provider_factories().find(|f| f.id == id)
+    }
+
+    /// Environment variable `parameter` can be supplied through, e.g. `MINT_MODIO_OAUTH`.
+    pub fn parameter_env_var(&self, parameter: &ProviderParameter) -> String {
+        format!(
+            "MINT_{}_{}",
+            self.id.to_ascii_uppercase(),
+            parameter.id.to_ascii_uppercase()
+        )
+    }
+
+    /// Parameters supplied through environment variables. These take precedence over the
+    /// parameters stored in the config and are never written to it.
+    pub fn parameters_from_env(&self) -> HashMap<String, String> {
+        self.parameters
+            .iter()
+            .filter_map(|p| {
+                std::env::var(self.parameter_env_var(p))
+                    .ok()
+                    .filter(|v| !v.is_empty())
+                    .map(|v| (p.id.to_owned(), v))
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProviderParameter<'a> {
     pub id: &'a str,
     pub name: &'a str,
     pub description: &'a str,
     pub link: Option<&'a str>,
+    /// Optional parameters are never prompted for, the provider is initialized without them.
+    pub optional: bool,
+    /// Secret parameters such as tokens are masked while they are entered.
+    pub secret: bool,
 }
 
 inventory::collect!(ProviderFactory);
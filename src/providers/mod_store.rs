@@ -6,6 +6,7 @@ use tracing::*;
 
 use crate::providers::*;
 use crate::state::config::ConfigWrapper;
+use crate::state::StateError;
 
 pub struct ModStore {
     providers: Providers,
@@ -20,8 +21,13 @@ impl ModStore {
     ) -> Result<Self, ProviderError> {
         let mut providers = HashMap::new();
         for prov in Self::get_provider_factories() {
-            let params = parameters.get(prov.id).cloned().unwrap_or_default();
-            if prov.parameters.iter().all(|p| params.contains_key(p.id)) {
+            let mut params = parameters.get(prov.id).cloned().unwrap_or_default();
+            params.extend(prov.parameters_from_env());
+            if prov
+                .parameters
+                .iter()
+                .all(|p| p.optional || params.contains_key(p.id))
+            {
                 let Ok(provider) = (prov.new)(&params) else {
                     return Err(ProviderError::InitProviderFailed {
                         id: prov.id,
@@ -62,6 +68,12 @@ impl ModStore {
         Ok(())
     }
 
+    /// Use `provider` for the mods of the factory `id` without creating it from parameters.
+    #[cfg(test)]
+    pub(crate) fn set_provider(&self, id: &'static str, provider: Arc<dyn ModProvider>) {
+        self.providers.write().unwrap().insert(id, provider);
+    }
+
     pub async fn add_provider_checked(
         &self,
         provider_factory: &ProviderFactory,
@@ -93,6 +105,72 @@ impl ModStore {
         })
     }
 
+    fn get_modio_provider(&self) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        let factory = ProviderFactory::find(modio::MODIO_PROVIDER_ID).unwrap();
+        let provider = self.providers.read().unwrap().get(factory.id).cloned();
+        provider.context(NoProviderSnafu {
+            url: "https://mod.io/g/drg".to_string(),
+            factory,
+        })
+    }
+
+    /// Search mod.io for mods, see [`modio::DrgModio::search_mods`].
+    pub async fn search(
+        &self,
+        query: &modio::ModioSearch,
+    ) -> Result<Vec<modio::ModioSearchResult>, ProviderError> {
+        self.get_modio_provider()?.search(query).await
+    }
+
+    /// Mods the mod.io user is subscribed to. They are added to the cache if missing.
+    pub async fn modio_subscriptions(
+        &self,
+    ) -> Result<Vec<modio::ModioSearchResult>, ProviderError> {
+        self.get_modio_provider()?
+            .subscriptions(self.cache.clone())
+            .await
+    }
+
+    /// Subscribe to or unsubscribe from a mod on mod.io.
+    pub async fn set_modio_subscribed(
+        &self,
+        modio_id: u32,
+        subscribed: bool,
+    ) -> Result<(), ProviderError> {
+        self.get_modio_provider()?
+            .set_subscribed(modio_id, subscribed)
+            .await
+    }
+
+    /// Modfile each mod.io mod of `specs` currently resolves to according to the cache, keyed by
+    /// mod ID. Pass it to [`Self::modio_updates_since`] after updating.
+    pub fn modio_modfiles(&self, specs: &[ModSpecification]) -> HashMap<u32, u32> {
+        specs
+            .iter()
+            .filter_map(|spec| {
+                let info = self.get_cached_mod_info(spec)?;
+                modio::modfile_ids(&info.resolution.url.0)
+            })
+            .collect()
+    }
+
+    /// Mods of `specs` that now resolve to a newer modfile than the one recorded in `modfiles` by
+    /// [`Self::modio_modfiles`], see [`modio::ModioCache::updates_between`]. Pinned mods never
+    /// do.
+    pub fn modio_updates_since(
+        &self,
+        specs: &[ModSpecification],
+        modfiles: &HashMap<u32, u32>,
+    ) -> Vec<modio::ModfileUpdate> {
+        let current = self.modio_modfiles(specs);
+        self.cache
+            .read()
+            .unwrap()
+            .get::<modio::ModioCache>(modio::MODIO_PROVIDER_ID)
+            .map(|c| c.updates_between(modfiles, &current))
+            .unwrap_or_default()
+    }
+
     pub async fn resolve_mods(
         &self,
         mods: &[ModSpecification],
@@ -215,12 +293,44 @@ impl ModStore {
         Ok(())
     }
 
+    /// Path of a cached blob with the given SHA-256 hex digest, if it exists.
+    pub fn get_blob_path(&self, sha256: &str) -> Option<PathBuf> {
+        self.blob_cache.get_path_by_hash(sha256)
+    }
+
+    /// Add a blob to the blob cache, returning its SHA-256 hex digest.
+    pub fn import_blob(&self, blob: &[u8]) -> Result<String, ProviderError> {
+        Ok(self.blob_cache.write(blob)?.hash().to_string())
+    }
+
+    /// Copy of the cached provider metadata required to resolve and fetch the given mod URLs
+    /// without network access.
+    pub fn export_cache(&self, urls: &[&str]) -> Cache!["0.0.0"] {
+        self.cache.read().unwrap().subset(urls)
+    }
+
+    /// Merge previously exported provider metadata into the cache, overwriting existing entries.
+    pub fn import_cache(&self, cache: Cache!["0.0.0"]) -> Result<(), StateError> {
+        let mut lock = self.cache.write().unwrap();
+        lock.merge(cache);
+        lock.save()
+    }
+
     pub fn get_mod_info(&self, spec: &ModSpecification) -> Option<ModInfo> {
         self.get_provider(&spec.url)
             .ok()?
             .get_mod_info(spec, self.cache.clone())
     }
 
+    /// Like [`Self::get_mod_info`] but falls back to the provider cache if the provider is not
+    /// configured, e.g. when integrating an unpacked modpack offline.
+    pub fn get_cached_mod_info(&self, spec: &ModSpecification) -> Option<ModInfo> {
+        self.get_mod_info(spec).or_else(|| {
+            let factory = Self::get_provider_factories().find(|f| (f.can_provide)(&spec.url))?;
+            self.cache.read().unwrap().get_mod_info(factory.id, spec)
+        })
+    }
+
     pub fn is_pinned(&self, spec: &ModSpecification) -> bool {
         self.get_provider(&spec.url)
             .unwrap()
@@ -21,7 +21,7 @@ fn re_mod() -> &'static regex::Regex {
 }
 
 const MODIO_DRG_ID: u32 = 2475;
-const MODIO_PROVIDER_ID: &str = "modio";
+pub(crate) const MODIO_PROVIDER_ID: &str = "modio";
 
 inventory::submit! {
     super::ProviderFactory {
@@ -34,6 +34,8 @@ inventory::submit! {
                 name: "OAuth Token",
                 description: "mod.io OAuth token",
                 link: Some("https://mod.io/me/access"),
+                optional: false,
+                secret: true,
             },
         ]
     }
@@ -47,6 +49,30 @@ fn format_spec(name_id: &str, mod_id: u32, file_id: Option<u32>) -> ModSpecifica
     })
 }
 
+/// Whether `url` is a mod on mod.io.
+pub fn is_modio_url(url: &str) -> bool {
+    re_mod().is_match(url)
+}
+
+/// Extract the mod ID and modfile ID from a pinned mod.io URL.
+pub fn modfile_ids(url: &str) -> Option<(u32, u32)> {
+    let captures = re_mod().captures(url)?;
+    Some((
+        captures.name("mod_id")?.as_str().parse().ok()?,
+        captures.name("modfile_id")?.as_str().parse().ok()?,
+    ))
+}
+
+/// Extract the modfile ID from a pinned mod.io URL.
+pub fn modfile_id(url: &str) -> Option<u32> {
+    re_mod()
+        .captures(url)?
+        .name("modfile_id")?
+        .as_str()
+        .parse()
+        .ok()
+}
+
 pub struct ModioProvider<M: DrgModio> {
     modio: M,
 }
@@ -83,6 +109,57 @@ impl Default for ModioCache {
     }
 }
 
+impl ModioCache {
+    /// Mods resolving to a newer modfile in `after` than in `before`, both keyed by mod ID, with
+    /// the changelogs of every modfile in between.
+    pub fn updates_between(
+        &self,
+        before: &HashMap<u32, u32>,
+        after: &HashMap<u32, u32>,
+    ) -> Vec<ModfileUpdate> {
+        let mut updates = before
+            .iter()
+            .filter_map(|(mod_id, old_id)| {
+                let new_id = *after.get(mod_id)?;
+                // modfile IDs only ever increase
+                if new_id <= *old_id {
+                    return None;
+                }
+                let mod_ = self.mods.get(mod_id)?;
+                let version_name = |id: u32| {
+                    mod_.modfiles
+                        .iter()
+                        .find(|f| f.id == id)
+                        .map(ModioFile::version_name)
+                        .unwrap_or_else(|| id.to_string())
+                };
+                let mut changelogs = mod_
+                    .modfiles
+                    .iter()
+                    .filter(|f| f.id > *old_id && f.id <= new_id)
+                    .collect::<Vec<_>>();
+                changelogs.sort_by_key(|f| std::cmp::Reverse(f.id));
+                Some(ModfileUpdate {
+                    modio_id: *mod_id,
+                    name: mod_.name.clone(),
+                    spec: format_spec(&mod_.name_id, *mod_id, None),
+                    old_version: version_name(*old_id),
+                    new_version: version_name(new_id),
+                    changelogs: changelogs
+                        .into_iter()
+                        .map(|f| ModfileChangelog {
+                            version: f.version_name(),
+                            changelog: f.changelog.clone().filter(|c| !c.trim().is_empty()),
+                        })
+                        .collect(),
+                })
+            })
+            .collect::<Vec<_>>();
+        updates.sort_by(|a, b| a.name.cmp(&b.name));
+        updates
+    }
+}
+
 #[typetag::serde]
 impl ModProviderCache for ModioCache {
     fn new() -> Self {
@@ -94,6 +171,111 @@ impl ModProviderCache for ModioCache {
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
+
+    fn subset(&self, urls: &[&str]) -> Box<dyn ModProviderCache> {
+        let mut subset = ModioCache {
+            last_update_time: self.last_update_time,
+            ..Default::default()
+        };
+
+        let mut mod_ids = HashSet::new();
+        for captures in urls.iter().filter_map(|url| re_mod().captures(url)) {
+            let mod_id = match captures.name("mod_id") {
+                Some(mod_id) => mod_id.as_str().parse::<u32>().ok(),
+                None => self
+                    .mod_id_map
+                    .get(captures.name("name_id").unwrap().as_str())
+                    .copied(),
+            };
+            mod_ids.extend(mod_id);
+            if let Some(modfile_id) = captures.name("modfile_id") {
+                let modfile_id = modfile_id.as_str().parse::<u32>().unwrap();
+                if let Some(blob) = self.modfile_blobs.get(&modfile_id) {
+                    subset.modfile_blobs.insert(modfile_id, blob.clone());
+                }
+            }
+        }
+
+        // resolving a mod also requires the names of its dependencies
+        for id in mod_ids.clone() {
+            if let Some(deps) = self.dependencies.get(&id) {
+                subset.dependencies.insert(id, deps.clone());
+                mod_ids.extend(deps);
+            }
+        }
+        for id in &mod_ids {
+            if let Some(mod_) = self.mods.get(id) {
+                subset.mods.insert(*id, mod_.clone());
+            }
+        }
+        subset.mod_id_map = self
+            .mod_id_map
+            .iter()
+            .filter(|(_, id)| mod_ids.contains(id))
+            .map(|(name, id)| (name.clone(), *id))
+            .collect();
+
+        Box::new(subset)
+    }
+
+    fn merge(&mut self, other: &dyn ModProviderCache) {
+        if let Some(other) = other.as_any().downcast_ref::<Self>() {
+            self.mod_id_map.extend(other.mod_id_map.clone());
+            self.modfile_blobs.extend(other.modfile_blobs.clone());
+            self.dependencies.extend(other.dependencies.clone());
+            self.mods.extend(other.mods.clone());
+        }
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification) -> Option<ModInfo> {
+        let url = &spec.url;
+        let captures = re_mod().captures(url)?;
+
+        let mod_id = if let Some(mod_id) = captures.name("mod_id") {
+            mod_id.as_str().parse::<u32>().ok()
+        } else if let Some(name_id) = captures.name("name_id") {
+            self.mod_id_map.get(name_id.as_str()).cloned()
+        } else {
+            None
+        }?;
+        let mod_ = self.mods.get(&mod_id)?;
+        let modfile_id = if let Some(modfile_id) = captures.name("modfile_id") {
+            modfile_id.as_str().parse::<u32>().ok()
+        } else {
+            mod_.modfiles.last().map(|f| f.id)
+        }?;
+
+        let deps = self
+            .dependencies
+            .get(&mod_id)?
+            .iter()
+            .map(|id| {
+                self.mods
+                    .get(id)
+                    .map(|m| format_spec(&m.name_id, *id, None))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(ModInfo {
+            provider: MODIO_PROVIDER_ID,
+            spec: format_spec(&mod_.name_id, mod_id, None),
+            name: mod_.name.clone(),
+            versions: mod_
+                .modfiles
+                .iter()
+                .map(|f| format_spec(&mod_.name_id, mod_id, Some(f.id)))
+                .collect(),
+            resolution: ModResolution::resolvable(
+                format_spec(&mod_.name_id, mod_id, Some(modfile_id))
+                    .url
+                    .into(),
+            ),
+            suggested_require: mod_.tags.contains("RequiredByAll"),
+            suggested_dependencies: deps,
+            modio_tags: Some(process_modio_tags(&mod_.tags)),
+            modio_id: Some(mod_id),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -144,6 +326,128 @@ impl ModioFile {
             changelog: file.changelog,
         }
     }
+
+    fn version_name(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{} - {}", self.id, version),
+            None => self.id.to_string(),
+        }
+    }
+}
+
+/// A mod that resolves to a newer modfile than before, see [`ModioCache::updates_between`].
+#[derive(Debug, Clone)]
+pub struct ModfileUpdate {
+    pub modio_id: u32,
+    pub name: String,
+    /// Unpinned specification of the mod.
+    pub spec: ModSpecification,
+    pub old_version: String,
+    pub new_version: String,
+    /// Modfiles after the old one up to and including the new one, newest first.
+    pub changelogs: Vec<ModfileChangelog>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModfileChangelog {
+    pub version: String,
+    pub changelog: Option<String>,
+}
+
+/// Order of [`ModioSearch`] results.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ModioSort {
+    #[default]
+    Popular,
+    Downloads,
+    Rating,
+    Subscribers,
+    Newest,
+    Updated,
+    Name,
+}
+
+/// Query for [`DrgModio::search_mods`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModioSearch {
+    /// Full text search of the mod names, summaries and descriptions.
+    pub text: Option<String>,
+    /// Tags every result must have, e.g. `QoL` or `Verified`.
+    pub tags: Vec<String>,
+    pub sort: ModioSort,
+    /// Zero-based index of the page to return.
+    pub page: usize,
+    pub page_size: usize,
+}
+
+impl Default for ModioSearch {
+    fn default() -> Self {
+        Self {
+            text: None,
+            tags: vec![],
+            sort: Default::default(),
+            page: 0,
+            page_size: 20,
+        }
+    }
+}
+
+impl ModioSearch {
+    /// mod.io API filter returning the requested page of results.
+    fn filter(&self) -> modio::filter::Filter {
+        use modio::filter::{Eq, OrderBy};
+        use modio::mods::filters::{
+            DateAdded, DateUpdated, Downloads, Fulltext, Name, Popular, Rating, Subscribers, Tags,
+        };
+
+        let mut filter = match self.sort {
+            ModioSort::Popular => Popular::desc(),
+            ModioSort::Downloads => Downloads::desc(),
+            ModioSort::Rating => Rating::desc(),
+            ModioSort::Subscribers => Subscribers::desc(),
+            ModioSort::Newest => DateAdded::desc(),
+            ModioSort::Updated => DateUpdated::desc(),
+            ModioSort::Name => Name::asc(),
+        }
+        .limit(self.page_size)
+        .offset(self.page * self.page_size);
+        if let Some(text) = &self.text {
+            filter = filter.and(Fulltext::eq(text.clone()));
+        }
+        if !self.tags.is_empty() {
+            // mod.io only returns mods having all of the comma separated tags
+            filter = filter.and(Tags::eq(self.tags.join(",")));
+        }
+        filter
+    }
+}
+
+/// A mod found by [`DrgModio::search_mods`] or in the subscriptions of the user.
+#[derive(Debug, Clone)]
+pub struct ModioSearchResult {
+    pub modio_id: u32,
+    /// Unpinned specification the mod can be added to a profile with.
+    pub spec: ModSpecification,
+    pub name: String,
+    pub summary: String,
+    pub modio_tags: ModioTags,
+}
+
+impl From<modio::mods::Mod> for ModioSearchResult {
+    fn from(value: modio::mods::Mod) -> Self {
+        let tags = value
+            .tags
+            .into_iter()
+            .map(|t| t.name)
+            .collect::<HashSet<_>>();
+        Self {
+            modio_id: value.id,
+            spec: format_spec(&value.name_id, value.id, None),
+            name: value.name,
+            summary: value.summary,
+            modio_tags: process_modio_tags(&tags),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -219,6 +523,17 @@ pub enum DrgModioError {
         url: String,
         mod_id: u32,
     },
+    #[snafu(display("failed to fetch mod.io subscriptions: {source}"))]
+    FetchSubscriptionsFailed { source: modio::Error },
+    #[snafu(display(
+        "failed to {} mod (mod_id = {mod_id}): {source}",
+        if *subscribed { "subscribe to" } else { "unsubscribe from" }
+    ))]
+    SetSubscribedFailed {
+        source: modio::Error,
+        mod_id: u32,
+        subscribed: bool,
+    },
     #[snafu(display("encountered mod.io-related error: {msg}"))]
     GenericError { msg: &'static str },
 }
@@ -229,7 +544,8 @@ impl DrgModioError {
             DrgModioError::FetchModFilesFailed { mod_id, .. }
             | DrgModioError::FetchModFileFailed { mod_id, .. }
             | DrgModioError::FetchModFailed { mod_id, .. }
-            | DrgModioError::FetchDependenciesFailed { mod_id, .. } => Some(*mod_id),
+            | DrgModioError::FetchDependenciesFailed { mod_id, .. }
+            | DrgModioError::SetSubscribedFailed { mod_id, .. } => Some(*mod_id),
             _ => None,
         }
     }
@@ -265,6 +581,14 @@ pub trait DrgModio: Sync + Send {
         mod_ids: Vec<u32>,
         last_update: u64,
     ) -> Result<HashSet<u32>, DrgModioError>;
+    /// Return one page of the DRG mods matching `query`. There may be more pages if it is full.
+    async fn search_mods(
+        &self,
+        query: ModioSearch,
+    ) -> Result<Vec<ModioSearchResult>, DrgModioError>;
+    /// Return the DRG mods the authenticated user is subscribed to.
+    async fn fetch_subscriptions(&self) -> Result<Vec<ModioSearchResult>, DrgModioError>;
+    async fn set_subscribed(&self, mod_id: u32, subscribed: bool) -> Result<(), DrgModioError>;
     fn download<A: 'static>(&self, action: A) -> modio::download::Downloader
     where
         modio::download::DownloadAction: From<A>;
@@ -457,6 +781,47 @@ impl DrgModio for modio::Modio {
         Ok(events.iter().map(|e| e.mod_id).collect::<HashSet<_>>())
     }
 
+    async fn search_mods(
+        &self,
+        query: ModioSearch,
+    ) -> Result<Vec<ModioSearchResult>, DrgModioError> {
+        Ok(self
+            .game(MODIO_DRG_ID)
+            .mods()
+            .search(query.filter())
+            .first_page()
+            .await
+            .context(GenericModioSnafu)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn fetch_subscriptions(&self) -> Result<Vec<ModioSearchResult>, DrgModioError> {
+        use modio::filter::Eq;
+        use modio::mods::filters::GameId;
+
+        Ok(self
+            .user()
+            .subscriptions(GameId::eq(MODIO_DRG_ID))
+            .collect()
+            .await
+            .context(FetchSubscriptionsFailedSnafu)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn set_subscribed(&self, mod_id: u32, subscribed: bool) -> Result<(), DrgModioError> {
+        let mod_ = self.game(MODIO_DRG_ID).mod_(mod_id);
+        if subscribed {
+            mod_.subscribe().await.map(|_| ())
+        } else {
+            mod_.unsubscribe().await
+        }
+        .context(SetSubscribedFailedSnafu { mod_id, subscribed })
+    }
+
     fn download<A>(&self, action: A) -> modio::download::Downloader
     where
         modio::download::DownloadAction: From<A>,
@@ -901,56 +1266,49 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
     }
 
     fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
-        let url = &spec.url;
-        let captures = re_mod().captures(url)?;
-
-        let cache = cache.read().unwrap();
-        let prov = cache.get::<ModioCache>(MODIO_PROVIDER_ID)?;
+        cache
+            .read()
+            .unwrap()
+            .get::<ModioCache>(MODIO_PROVIDER_ID)?
+            .get_mod_info(spec)
+    }
 
-        let mod_id = if let Some(mod_id) = captures.name("mod_id") {
-            mod_id.as_str().parse::<u32>().ok()
-        } else if let Some(name_id) = captures.name("name_id") {
-            prov.mod_id_map.get(name_id.as_str()).cloned()
-        } else {
-            None
-        }?;
-        let mod_ = prov.mods.get(&mod_id)?;
-        let modfile_id = if let Some(modfile_id) = captures.name("modfile_id") {
-            modfile_id.as_str().parse::<u32>().ok()
-        } else {
-            mod_.modfiles.last().map(|f| f.id)
-        }?;
+    async fn search(&self, query: &ModioSearch) -> Result<Vec<ModioSearchResult>, ProviderError> {
+        Ok(self.modio.search_mods(query.clone()).await?)
+    }
 
-        let deps = prov
-            .dependencies
-            .get(&mod_id)?
+    async fn subscriptions(
+        &self,
+        cache: ProviderCache,
+    ) -> Result<Vec<ModioSearchResult>, ProviderError> {
+        let subscriptions = self.modio.fetch_subscriptions().await?;
+
+        // cache the subscribed mods so they can be resolved like any other mod
+        let cached = cache
+            .read()
+            .unwrap()
+            .get::<ModioCache>(MODIO_PROVIDER_ID)
+            .map(|c| c.mods.keys().copied().collect::<HashSet<_>>())
+            .unwrap_or_default();
+        for m in subscriptions
             .iter()
-            .map(|id| {
-                prov.mods
-                    .get(id)
-                    .map(|m| format_spec(&m.name_id, *id, None))
-            })
-            .collect::<Option<Vec<_>>>()?;
+            .filter(|m| !cached.contains(&m.modio_id))
+        {
+            let mod_ = self
+                .modio
+                .fetch_files(m.spec.url.clone(), m.modio_id)
+                .await?;
+            let mut lock = cache.write().unwrap();
+            let c = lock.get_mut::<ModioCache>(MODIO_PROVIDER_ID);
+            c.mod_id_map.insert(mod_.name_id.to_owned(), m.modio_id);
+            c.mods.insert(m.modio_id, mod_);
+        }
 
-        Some(ModInfo {
-            provider: MODIO_PROVIDER_ID,
-            spec: format_spec(&mod_.name_id, mod_id, None),
-            name: mod_.name.clone(),
-            versions: mod_
-                .modfiles
-                .iter()
-                .map(|f| format_spec(&mod_.name_id, mod_id, Some(f.id)))
-                .collect(),
-            resolution: ModResolution::resolvable(
-                format_spec(&mod_.name_id, mod_id, Some(modfile_id))
-                    .url
-                    .into(),
-            ),
-            suggested_require: mod_.tags.contains("RequiredByAll"),
-            suggested_dependencies: deps,
-            modio_tags: Some(process_modio_tags(&mod_.tags)),
-            modio_id: Some(mod_id),
-        })
+        Ok(subscriptions)
+    }
+
+    async fn set_subscribed(&self, modio_id: u32, subscribed: bool) -> Result<(), ProviderError> {
+        Ok(self.modio.set_subscribed(modio_id, subscribed).await?)
     }
 
     fn is_pinned(&self, spec: &ModSpecification, _cache: ProviderCache) -> bool {
@@ -980,11 +1338,7 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
                 if let Some(file_id_str) = captures.name("modfile_id") {
                     let file_id = file_id_str.as_str().parse::<u32>().unwrap();
                     if let Some(file) = mod_.modfiles.iter().find(|f| f.id == file_id) {
-                        if let Some(version) = &file.version {
-                            Some(format!("{} - {}", file.id, version))
-                        } else {
-                            Some(file_id_str.as_str().to_string())
-                        }
+                        Some(file.version_name())
                     } else {
                         Some(file_id_str.as_str().to_string())
                     }
@@ -1037,12 +1391,52 @@ fn process_modio_tags(set: &HashSet<String>) -> ModioTags {
     }
 }
 
+#[cfg(test)]
+impl ModioCache {
+    /// Provider cache containing only one mod without dependencies, for tests of other modules.
+    pub(crate) fn single_mod_cache(
+        mod_id: u32,
+        name_id: &str,
+        name: &str,
+        modfile_ids: &[u32],
+    ) -> Cache_v0_0_0 {
+        let mod_ = ModioMod {
+            name_id: name_id.to_string(),
+            name: name.to_string(),
+            latest_modfile: modfile_ids.last().copied(),
+            modfiles: modfile_ids
+                .iter()
+                .map(|id| ModioFile {
+                    id: *id,
+                    date_added: *id as u64,
+                    version: None,
+                    changelog: None,
+                })
+                .collect(),
+            tags: HashSet::from(["RequiredByAll".to_string()]),
+        };
+        let cache = ModioCache {
+            mod_id_map: HashMap::from([(name_id.to_string(), mod_id)]),
+            dependencies: HashMap::from([(mod_id, vec![])]),
+            mods: HashMap::from([(mod_id, mod_)]),
+            ..Default::default()
+        };
+        Cache_v0_0_0 {
+            cache: HashMap::from([(
+                MODIO_PROVIDER_ID.to_string(),
+                Box::new(cache) as Box<dyn ModProviderCache>,
+            )]),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::{
         Arc, DrgModioError, HashMap, HashSet, MockDrgModio, ModProvider, ModResponse,
-        ModSpecification, ModioCache, ModioFile, ModioMod, ModioModResponse, ModioProvider,
-        OnceLock, RwLock, VersionAnnotatedCache, MODIO_PROVIDER_ID,
+        ModSpecification, ModStore, ModioCache, ModioFile, ModioMod, ModioModResponse,
+        ModioProvider, ModioSearch, ModioSearchResult, ModioSort, OnceLock, RwLock,
+        VersionAnnotatedCache, MODIO_PROVIDER_ID,
     };
     use crate::state::config::ConfigWrapper;
 
@@ -1064,6 +1458,296 @@ mod test {
         assert!(modio_provider.check().await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_search() {
+        let mut mock = MockDrgModio::new();
+        mock.expect_search_mods()
+            .withf(|query| {
+                query.text.as_deref() == Some("difficulty")
+                    && query.tags == ["Verified"]
+                    && query.sort == ModioSort::Downloads
+                    && query.page == 1
+            })
+            .times(1)
+            .returning(|_| {
+                Ok(vec![ModioSearchResult {
+                    modio_id: 1,
+                    spec: super::format_spec("custom-difficulty", 1, None),
+                    name: "Custom Difficulty".to_string(),
+                    summary: String::new(),
+                    modio_tags: super::process_modio_tags(&HashSet::from(["Verified".into()])),
+                }])
+            });
+        let modio_provider = ModioProvider::new(mock);
+        let results = modio_provider
+            .search(&ModioSearch {
+                text: Some("difficulty".to_string()),
+                tags: vec!["Verified".to_string()],
+                sort: ModioSort::Downloads,
+                page: 1,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].spec.url,
+            "https://mod.io/g/drg/m/custom-difficulty#1"
+        );
+    }
+
+    #[test]
+    fn test_search_filter() {
+        let params = |query: ModioSearch| {
+            url::form_urlencoded::parse(query.filter().to_string().as_bytes())
+                .into_owned()
+                .collect::<HashMap<_, _>>()
+        };
+        let param = |k: &str, v: &str| (k.to_string(), v.to_string());
+
+        assert_eq!(
+            HashMap::from([
+                param("_sort", "-popular"),
+                param("_limit", "20"),
+                param("_offset", "0"),
+            ]),
+            params(ModioSearch::default())
+        );
+        assert_eq!(
+            HashMap::from([
+                param("_sort", "name"),
+                param("_limit", "50"),
+                param("_offset", "100"),
+                param("_q", "difficulty"),
+                param("tags", "QoL,Verified"),
+            ]),
+            params(ModioSearch {
+                text: Some("difficulty".to_string()),
+                tags: vec!["QoL".to_string(), "Verified".to_string()],
+                sort: ModioSort::Name,
+                page: 2,
+                page_size: 50,
+            })
+        );
+        assert_eq!(
+            Some(&"-date_added".to_string()),
+            params(ModioSearch {
+                sort: ModioSort::Newest,
+                ..Default::default()
+            })
+            .get("_sort")
+        );
+    }
+
+    #[test]
+    fn test_updates_between() {
+        let file = |id: u32, version: &str, changelog: &str| ModioFile {
+            id,
+            date_added: id as u64,
+            version: Some(version.to_string()),
+            changelog: Some(changelog.to_string()),
+        };
+        let cache = ModioCache {
+            mods: HashMap::from([
+                (
+                    1,
+                    ModioMod {
+                        name_id: "test-mod".to_string(),
+                        name: "Test Mod".to_string(),
+                        latest_modfile: Some(30),
+                        modfiles: vec![
+                            file(10, "1.0", "first"),
+                            file(20, "1.1", "second"),
+                            file(30, "1.2", ""),
+                        ],
+                        tags: HashSet::new(),
+                    },
+                ),
+                (
+                    2,
+                    ModioMod {
+                        name_id: "up-to-date".to_string(),
+                        name: "Up To Date".to_string(),
+                        latest_modfile: Some(5),
+                        modfiles: vec![file(5, "1.0", "first")],
+                        tags: HashSet::new(),
+                    },
+                ),
+            ]),
+            ..Default::default()
+        };
+
+        let updates = cache.updates_between(
+            &HashMap::from([(1, 10), (2, 5), (3, 1)]),
+            &HashMap::from([(1, 30), (2, 5), (3, 2)]),
+        );
+        assert_eq!(updates.len(), 1);
+        let update = &updates[0];
+        assert_eq!(update.spec.url, "https://mod.io/g/drg/m/test-mod#1");
+        assert_eq!(update.old_version, "10 - 1.0");
+        assert_eq!(update.new_version, "30 - 1.2");
+        assert_eq!(
+            update
+                .changelogs
+                .iter()
+                .map(|c| (c.version.as_str(), c.changelog.as_deref()))
+                .collect::<Vec<_>>(),
+            [("30 - 1.2", None), ("20 - 1.1", Some("second"))]
+        );
+    }
+
+    #[test]
+    fn test_store_updates_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ModStore::new(dir.path(), &HashMap::new()).unwrap();
+        store
+            .import_cache(ModioCache::single_mod_cache(
+                1,
+                "test-mod",
+                "Test Mod",
+                &[10, 20],
+            ))
+            .unwrap();
+        let pinned = vec![ModSpecification::new(
+            "https://mod.io/g/drg/m/test-mod#1/10".to_string(),
+        )];
+        let unpinned = vec![ModSpecification::new(
+            "https://mod.io/g/drg/m/test-mod".to_string(),
+        )];
+        let pinned_modfiles = store.modio_modfiles(&pinned);
+        let unpinned_modfiles = store.modio_modfiles(&unpinned);
+        assert_eq!(pinned_modfiles, HashMap::from([(1, 10)]));
+        assert_eq!(unpinned_modfiles, HashMap::from([(1, 20)]));
+
+        // a new modfile is fetched while updating
+        store
+            .import_cache(ModioCache::single_mod_cache(
+                1,
+                "test-mod",
+                "Test Mod",
+                &[10, 20, 30],
+            ))
+            .unwrap();
+        assert!(store
+            .modio_updates_since(&pinned, &pinned_modfiles)
+            .is_empty());
+        let updates = store.modio_updates_since(&unpinned, &unpinned_modfiles);
+        assert_eq!(
+            updates
+                .iter()
+                .map(|u| (u.modio_id, u.old_version.as_str(), u.new_version.as_str()))
+                .collect::<Vec<_>>(),
+            [(1, "20", "30")]
+        );
+        // mods that are not integrated are not reported
+        assert!(store
+            .modio_updates_since(&[], &unpinned_modfiles)
+            .is_empty());
+    }
+
+    fn subscription(modio_id: u32, name_id: &str, name: &str) -> ModioSearchResult {
+        ModioSearchResult {
+            modio_id,
+            spec: super::format_spec(name_id, modio_id, None),
+            name: name.to_string(),
+            summary: String::new(),
+            modio_tags: super::process_modio_tags(&HashSet::new()),
+        }
+    }
+
+    fn modio_mod(name_id: &str, name: &str) -> ModioMod {
+        ModioMod {
+            name_id: name_id.to_string(),
+            name: name.to_string(),
+            latest_modfile: Some(5),
+            modfiles: vec![ModioFile {
+                id: 5,
+                date_added: 12345,
+                version: None,
+                changelog: None,
+            }],
+            tags: HashSet::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_caches_missing_mods() {
+        let mut mock = MockDrgModio::new();
+        mock.expect_fetch_subscriptions().times(1).returning(|| {
+            Ok(vec![
+                subscription(1, "cached", "Cached"),
+                subscription(2, "missing", "Missing"),
+            ])
+        });
+        mock.expect_fetch_files()
+            .withf(|url, mod_id| url == "https://mod.io/g/drg/m/missing#2" && *mod_id == 2)
+            .times(1)
+            .returning(|_, _| Ok(modio_mod("missing", "Missing")));
+
+        let cache = Arc::new(RwLock::new(ConfigWrapper::<VersionAnnotatedCache>::memory(
+            VersionAnnotatedCache::default(),
+        )));
+        {
+            let mut lock = cache.write().unwrap();
+            let c = lock.get_mut::<ModioCache>(MODIO_PROVIDER_ID);
+            c.mod_id_map.insert("cached".to_string(), 1);
+            c.mods.insert(1, modio_mod("cached", "Cached"));
+        }
+
+        let modio_provider = ModioProvider::new(mock);
+        let subscriptions = modio_provider.subscriptions(cache.clone()).await.unwrap();
+        assert_eq!(
+            subscriptions.iter().map(|s| s.modio_id).collect::<Vec<_>>(),
+            [1, 2]
+        );
+
+        let lock = cache.read().unwrap();
+        let modio_cache = lock.get::<ModioCache>(MODIO_PROVIDER_ID).unwrap();
+        assert_eq!(
+            modio_cache.mod_id_map,
+            HashMap::from([("cached".to_string(), 1), ("missing".to_string(), 2)])
+        );
+        assert_eq!(
+            modio_cache.mods,
+            HashMap::from([
+                (1, modio_mod("cached", "Cached")),
+                (2, modio_mod("missing", "Missing")),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_push_subscriptions() {
+        use crate::subscriptions::{push, SubscriptionDiff, SyncMod};
+        use mockall::predicate::eq;
+
+        let mut mock = MockDrgModio::new();
+        mock.expect_set_subscribed()
+            .with(eq(2), eq(true))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock.expect_set_subscribed()
+            .with(eq(3), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = ModStore::new(dir.path(), &HashMap::new()).unwrap();
+        store.set_provider(MODIO_PROVIDER_ID, Arc::new(ModioProvider::new(mock)));
+
+        let sync_mod = |modio_id: u32, name_id: &str| SyncMod {
+            modio_id,
+            spec: super::format_spec(name_id, modio_id, None),
+            name: name_id.to_string(),
+            disabled_spec: None,
+        };
+        let diff = SubscriptionDiff {
+            only_subscribed: vec![sync_mod(3, "only-subscribed")],
+            only_in_profile: vec![sync_mod(2, "only-in-profile")],
+        };
+        push(&store, &diff).await.unwrap();
+    }
+
     struct FullMod {
         mod_: ModioMod,
         dependencies: Vec<u32>,
@@ -38,19 +38,23 @@ impl<C: ConfigTrait> ConfigWrapper<C> {
     /// See <https://stackoverflow.com/questions/70362352/atomic-file-create-write>.
     pub fn save(&self) -> Result<(), StateError> {
         if let Some(final_path) = &self.path {
-            let mut temp_file = tempfile::NamedTempFile::new_in(final_path.parent().unwrap())?;
-            temp_file
-                .write_all(
-                    &serde_json::to_vec_pretty(&self.config)
-                        .context(CfgSerializationFailedSnafu)?,
-                )
-                .context(CfgSaveFailedSnafu)?;
-            temp_file.persist(final_path)?;
+            write_json_atomic(final_path, &self.config)?;
         }
         Ok(())
     }
 }
 
+/// Serialize `value` as pretty JSON to a temporary file next to `path` and then replace `path`
+/// with it.
+pub(crate) fn write_json_atomic<T: Serialize>(path: &Path, value: &T) -> Result<(), StateError> {
+    let mut temp_file = tempfile::NamedTempFile::new_in(path.parent().unwrap())?;
+    temp_file
+        .write_all(&serde_json::to_vec_pretty(value).context(CfgSerializationFailedSnafu)?)
+        .context(CfgSaveFailedSnafu)?;
+    temp_file.persist(path)?;
+    Ok(())
+}
+
 impl<C: ConfigTrait> std::ops::Deref for ConfigWrapper<C> {
     type Target = C;
     fn deref(&self) -> &Self::Target {
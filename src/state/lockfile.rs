@@ -0,0 +1,295 @@
+use std::path::{Path, PathBuf};
+
+use super::config::write_json_atomic;
+use super::*;
+use crate::providers::ModResolution;
+
+/// Exact version of a mod recorded in a [`Lockfile`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedMod {
+    pub resolution: ModResolution,
+    /// mod.io modfile ID, only present for mods from mod.io.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modfile_id: Option<u32>,
+    /// SHA-256 of the fetched mod file, also the name of its blob in the blob cache.
+    pub sha256: String,
+}
+
+#[obake::versioned]
+#[obake(version("0.0.0"))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub profile: String,
+    /// Locked mods keyed by [`ModSpecification::url`].
+    pub mods: BTreeMap<String, LockedMod>,
+}
+
+impl Lockfile!["0.0.0"] {
+    pub fn get(&self, spec: &ModSpecification) -> Option<&LockedMod> {
+        self.mods.get(&spec.url)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "version")]
+pub enum VersionAnnotatedLockfile {
+    #[serde(rename = "0.0.0")]
+    V0_0_0(Lockfile!["0.0.0"]),
+    #[serde(other)]
+    Unsupported,
+}
+
+impl State {
+    /// Lockfiles are stored per profile in `<config dir>/locks/`. Characters that are not safe in
+    /// file names are replaced and a hash of the profile name is appended in that case, so
+    /// profiles only differing in those characters don't share a lockfile.
+    pub fn lockfile_path(&self, profile: &str) -> PathBuf {
+        let mut file_name = profile
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, '-' | '_' | ' ' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        if file_name != profile {
+            use sha2::{Digest, Sha256};
+
+            let hash = hex::encode(Sha256::digest(profile.as_bytes()));
+            file_name = format!("{file_name}.{}", &hash[..8]);
+        }
+        self.dirs
+            .config_dir
+            .join("locks")
+            .join(format!("{file_name}.lock.json"))
+    }
+
+    /// Read the lockfile of a profile, returns `None` if the profile has not been locked yet.
+    pub fn read_lockfile(&self, profile: &str) -> Result<Option<Lockfile!["0.0.0"]>, StateError> {
+        let path = self.lockfile_path(profile);
+        let lockfile = match fs::read(&path) {
+            Ok(buf) => serde_json::from_slice::<VersionAnnotatedLockfile>(&buf)
+                .context(LockfileDeserializationFailedSnafu { path: path.clone() })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => Err(e)?,
+        };
+        let lockfile = match lockfile {
+            VersionAnnotatedLockfile::V0_0_0(lockfile) => lockfile,
+            VersionAnnotatedLockfile::Unsupported => {
+                UnsupportedLockfileVersionSnafu { path: path.clone() }.fail()?
+            }
+        };
+        ensure!(
+            lockfile.profile == profile,
+            LockfileProfileMismatchSnafu {
+                path,
+                profile,
+                found: lockfile.profile,
+            }
+        );
+        Ok(Some(lockfile))
+    }
+
+    pub fn write_lockfile(&self, lockfile: Lockfile!["0.0.0"]) -> Result<PathBuf, StateError> {
+        let path = self.lockfile_path(&lockfile.profile);
+        fs::create_dir_all(path.parent().unwrap())?;
+        write_json_atomic(&path, &VersionAnnotatedLockfile::V0_0_0(lockfile))?;
+        Ok(path)
+    }
+}
+
+/// Check that the file at `path` has the locked hash.
+pub fn verify_locked(
+    spec: &ModSpecification,
+    locked: &LockedMod,
+    path: &Path,
+) -> Result<(), StateError> {
+    let found = crate::providers::hash_file(path)?;
+    ensure!(
+        found.eq_ignore_ascii_case(&locked.sha256),
+        LockedHashMismatchSnafu {
+            url: spec.url.clone(),
+            expected: locked.sha256.clone(),
+            found,
+        }
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::providers::ProviderFactory;
+    use crate::{fetch_locked_mods, update_lockfile_with_provider_init, Dirs, MintError};
+
+    fn init_state(dir: &Path) -> State {
+        State::init(Dirs::from_path(dir.join("state")).unwrap()).unwrap()
+    }
+
+    fn add_file_mod(state: &mut State, path: &Path) -> ModSpecification {
+        let spec = ModSpecification::new(path.to_string_lossy().to_string());
+        state
+            .mod_data
+            .add_mod(
+                "default",
+                ModConfig {
+                    spec: spec.clone(),
+                    required: false,
+                    enabled: true,
+                    priority: 0,
+                    disabled_paks: Default::default(),
+                },
+            )
+            .unwrap();
+        spec
+    }
+
+    fn locked_mod(sha256: &str) -> LockedMod {
+        LockedMod {
+            resolution: ModResolution::resolvable("https://mod.io/g/drg/m/test-mod#1/10".into()),
+            modfile_id: Some(10),
+            sha256: sha256.to_string(),
+        }
+    }
+
+    fn no_provider(_: &mut State, url: String, _: &ProviderFactory) -> Result<(), MintError> {
+        panic!("provider for <{url}> requested")
+    }
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = init_state(dir.path());
+        assert!(state.read_lockfile("default").unwrap().is_none());
+
+        let mods = BTreeMap::from([(
+            "https://mod.io/g/drg/m/test-mod".to_string(),
+            locked_mod("abc"),
+        )]);
+        let path = state
+            .write_lockfile(Lockfile_v0_0_0 {
+                profile: "default".to_string(),
+                mods: mods.clone(),
+            })
+            .unwrap();
+        assert_eq!(path, state.lockfile_path("default"));
+
+        let lockfile = state.read_lockfile("default").unwrap().unwrap();
+        assert_eq!(lockfile.profile, "default");
+        assert_eq!(lockfile.mods, mods);
+    }
+
+    #[test]
+    fn test_lockfile_path_sanitized_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = init_state(dir.path());
+        let profiles = ["a/b", "a_b", "a:b"];
+
+        let paths = profiles
+            .iter()
+            .map(|p| state.lockfile_path(p))
+            .collect::<HashSet<_>>();
+        assert_eq!(paths.len(), profiles.len());
+        assert_eq!(
+            state.lockfile_path("a_b").file_name().unwrap(),
+            "a_b.lock.json"
+        );
+
+        for (i, profile) in profiles.iter().enumerate() {
+            state
+                .write_lockfile(Lockfile_v0_0_0 {
+                    profile: profile.to_string(),
+                    mods: BTreeMap::from([(profile.to_string(), locked_mod(&i.to_string()))]),
+                })
+                .unwrap();
+        }
+        for (i, profile) in profiles.iter().enumerate() {
+            let lockfile = state.read_lockfile(profile).unwrap().unwrap();
+            assert_eq!(lockfile.profile, *profile);
+            assert_eq!(lockfile.mods[*profile].sha256, i.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_lockfile_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = init_state(dir.path());
+        let mod_path = dir.path().join("test.pak");
+        fs::write(&mod_path, b"version 1").unwrap();
+        let spec = add_file_mod(&mut state, &mod_path);
+
+        let (_, lockfile) =
+            update_lockfile_with_provider_init(&mut state, "default", false, no_provider)
+                .await
+                .unwrap();
+        let locked = lockfile.get(&spec).unwrap().clone();
+        assert_eq!(
+            locked.sha256,
+            crate::providers::hash_file(&mod_path).unwrap()
+        );
+        assert_eq!(locked.modfile_id, None);
+        verify_locked(&spec, &locked, &mod_path).unwrap();
+
+        // locked versions are kept unless updating
+        fs::write(&mod_path, b"version 2").unwrap();
+        let (_, lockfile) =
+            update_lockfile_with_provider_init(&mut state, "default", false, no_provider)
+                .await
+                .unwrap();
+        assert_eq!(lockfile.get(&spec), Some(&locked));
+
+        assert!(matches!(
+            verify_locked(&spec, &locked, &mod_path),
+            Err(StateError::LockedHashMismatch { ref url, .. }) if *url == spec.url
+        ));
+        assert!(matches!(
+            fetch_locked_mods(&state, "default").await,
+            Err(MintError::StateError {
+                source: StateError::LockedHashMismatch { .. }
+            })
+        ));
+
+        let (_, lockfile) =
+            update_lockfile_with_provider_init(&mut state, "default", true, no_provider)
+                .await
+                .unwrap();
+        let locked = lockfile.get(&spec).unwrap();
+        assert_eq!(
+            locked.sha256,
+            crate::providers::hash_file(&mod_path).unwrap()
+        );
+        let (mods, _) = fetch_locked_mods(&state, "default").await.unwrap();
+        assert_eq!(mods[0].1, mod_path);
+    }
+
+    #[tokio::test]
+    async fn test_not_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = init_state(dir.path());
+        assert!(matches!(
+            fetch_locked_mods(&state, "default").await,
+            Err(MintError::StateError {
+                source: StateError::NoLockfile { .. }
+            })
+        ));
+
+        let locked_path = dir.path().join("locked.pak");
+        fs::write(&locked_path, b"locked").unwrap();
+        add_file_mod(&mut state, &locked_path);
+        update_lockfile_with_provider_init(&mut state, "default", false, no_provider)
+            .await
+            .unwrap();
+
+        let unlocked_path = dir.path().join("unlocked.pak");
+        fs::write(&unlocked_path, b"unlocked").unwrap();
+        let unlocked = add_file_mod(&mut state, &unlocked_path);
+        assert!(matches!(
+            fetch_locked_mods(&state, "default").await,
+            Err(MintError::StateError {
+                source: StateError::NotLocked { ref profile, ref url }
+            }) if profile == "default" && *url == unlocked.url
+        ));
+    }
+}
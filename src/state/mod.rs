@@ -1,7 +1,9 @@
 pub mod config;
+pub mod lockfile;
+pub mod profile_export;
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     ops::{Deref, DerefMut},
     path::PathBuf,
     sync::Arc,
@@ -14,14 +16,15 @@ use snafu::prelude::*;
 use self::config::ConfigWrapper;
 use crate::{
     gui::GuiTheme,
-    providers::{ModSpecification, ModStore},
+    integrate::DisabledPaks,
+    providers::{ModInfo, ModSpecification, ModStore},
     Dirs,
 };
 use crate::{gui::SortBy, providers::ProviderError};
 use mint_lib::{mod_info::MetaConfig, DRGInstallation};
 
 /// Mod configuration, holds ModSpecification as well as other metadata
-#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModConfig {
     pub spec: ModSpecification,
     pub required: bool,
@@ -30,6 +33,10 @@ pub struct ModConfig {
     pub enabled: bool,
     #[serde(default, skip_serializing_if = "is_zero")]
     pub priority: i32,
+    /// Paths of paks inside the mod's archive that are not integrated. Every other pak in the
+    /// archive is, including ones added by later versions of the mod.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub disabled_paks: BTreeSet<String>,
 }
 
 fn default_true() -> bool {
@@ -40,7 +47,7 @@ fn is_zero(value: &i32) -> bool {
     *value == 0
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModGroup {
     pub mods: Vec<ModConfig>,
 }
@@ -163,6 +170,17 @@ impl ModData!["0.1.0"] {
         self.for_each_mod_predicate(profile, f, std::convert::identity, |mc| mc.enabled)
     }
 
+    /// Paks disabled in the archives of the enabled mods of a profile.
+    pub fn disabled_paks(&self, profile: &str) -> DisabledPaks {
+        let mut disabled_paks = DisabledPaks::new();
+        self.for_each_enabled_mod(profile, |mc| {
+            if !mc.disabled_paks.is_empty() {
+                disabled_paks.insert(mc.spec.clone(), mc.disabled_paks.clone());
+            }
+        });
+        disabled_paks
+    }
+
     pub fn for_each_mod_mut<F: FnMut(&mut ModConfig)>(&mut self, profile: &str, f: F) {
         self.for_each_mod_predicate_mut(profile, f, |_| true, |_| true)
     }
@@ -329,6 +347,219 @@ impl ModData!["0.1.0"] {
         self.profiles.remove(&self.active_profile);
         self.active_profile = self.profiles.keys().next().unwrap().to_string();
     }
+
+    pub fn get_profile(&self, profile: &str) -> Result<&ModProfile!["0.1.0"], StateError> {
+        self.profiles.get(profile).context(ProfileNotFoundSnafu {
+            profile: profile.to_string(),
+        })
+    }
+
+    pub fn get_profile_mut(
+        &mut self,
+        profile: &str,
+    ) -> Result<&mut ModProfile!["0.1.0"], StateError> {
+        self.profiles
+            .get_mut(profile)
+            .context(ProfileNotFoundSnafu {
+                profile: profile.to_string(),
+            })
+    }
+
+    pub fn create_profile(&mut self, profile: &str) -> Result<(), StateError> {
+        ensure!(
+            !self.profiles.contains_key(profile),
+            ProfileAlreadyExistsSnafu {
+                profile: profile.to_string()
+            }
+        );
+        self.profiles
+            .insert(profile.to_string(), Default::default());
+        Ok(())
+    }
+
+    pub fn rename_profile(&mut self, profile: &str, new_name: &str) -> Result<(), StateError> {
+        ensure!(
+            !self.profiles.contains_key(new_name),
+            ProfileAlreadyExistsSnafu {
+                profile: new_name.to_string()
+            }
+        );
+        let p = self
+            .profiles
+            .remove(profile)
+            .context(ProfileNotFoundSnafu {
+                profile: profile.to_string(),
+            })?;
+        self.profiles.insert(new_name.to_string(), p);
+        if self.active_profile == profile {
+            self.active_profile = new_name.to_string();
+        }
+        Ok(())
+    }
+
+    pub fn copy_profile(&mut self, profile: &str, new_name: &str) -> Result<(), StateError> {
+        ensure!(
+            !self.profiles.contains_key(new_name),
+            ProfileAlreadyExistsSnafu {
+                profile: new_name.to_string()
+            }
+        );
+        let p = self.get_profile(profile)?.clone();
+        self.profiles.insert(new_name.to_string(), p);
+        Ok(())
+    }
+
+    pub fn delete_profile(&mut self, profile: &str) -> Result<(), StateError> {
+        self.get_profile(profile)?;
+        ensure!(self.profiles.len() > 1, LastProfileSnafu);
+        self.profiles.remove(profile);
+        if self.active_profile == profile {
+            self.active_profile = self.profiles.keys().next().unwrap().to_string();
+        }
+        Ok(())
+    }
+
+    /// Adds mods returned from [`ModStore::resolve_mods`] to a profile. If a mod is a dependency
+    /// and the profile already contains a (possibly disabled) mod satisfying it, that mod is
+    /// enabled instead of adding a new one. Non-dependencies are always added since the user
+    /// explicitly asked for that specific mod version, see [`Self::add_mod`] for mods the profile
+    /// already contains.
+    pub fn add_resolved_mods(
+        &mut self,
+        profile: &str,
+        specs: &[ModSpecification],
+        resolved_mods: HashMap<ModSpecification, ModInfo>,
+        is_dependency: bool,
+    ) -> Result<(), StateError> {
+        self.get_profile(profile)?;
+        let primary_mods = specs.iter().collect::<HashSet<_>>();
+        for (resolved_spec, info) in resolved_mods {
+            let is_dep = is_dependency || !primary_mods.contains(&resolved_spec);
+            let add = !is_dep
+                || !self.any_mod_mut(profile, |mc, mod_group_enabled| {
+                    if mc.spec.satisfies_dependency(&resolved_spec) {
+                        mc.enabled = true;
+                        if let Some(mod_group_enabled) = mod_group_enabled {
+                            *mod_group_enabled = true;
+                        }
+                        true
+                    } else {
+                        false
+                    }
+                });
+
+            if add {
+                self.add_mod(
+                    profile,
+                    ModConfig {
+                        spec: info.spec.clone(),
+                        required: info.suggested_require,
+                        enabled: true,
+                        priority: 0,
+                        disabled_paks: Default::default(),
+                    },
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Enables the mods of a profile with one of `specs` and the groups they are in, keeping their
+    /// other settings.
+    pub fn enable_mods(
+        &mut self,
+        profile: &str,
+        specs: &[ModSpecification],
+    ) -> Result<(), StateError> {
+        self.get_profile(profile)?;
+        for spec in specs {
+            self.any_mod_mut(profile, |mc, mod_group_enabled| {
+                if &mc.spec == spec {
+                    mc.enabled = true;
+                    if let Some(mod_group_enabled) = mod_group_enabled {
+                        *mod_group_enabled = true;
+                    }
+                    true
+                } else {
+                    false
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds a mod to the top of a profile. If the profile already contains the spec, the existing
+    /// entry keeps its place and settings and is only enabled together with its group if `mc` is.
+    pub fn add_mod(&mut self, profile: &str, mc: ModConfig) -> Result<(), StateError> {
+        self.get_profile(profile)?;
+        let exists = self.any_mod_mut(profile, |existing, mod_group_enabled| {
+            if existing.spec != mc.spec {
+                return false;
+            }
+            if mc.enabled {
+                existing.enabled = true;
+                if let Some(mod_group_enabled) = mod_group_enabled {
+                    *mod_group_enabled = true;
+                }
+            }
+            true
+        });
+        if !exists {
+            self.get_profile_mut(profile)?
+                .mods
+                .insert(0, ModOrGroup::Individual(mc));
+        }
+        Ok(())
+    }
+
+    pub fn remove_mod(&mut self, profile: &str, url: &str) -> Result<ModConfig, StateError> {
+        let p = self.get_profile_mut(profile)?;
+        let index = p
+            .mods
+            .iter()
+            .position(|m| matches!(m, ModOrGroup::Individual(mc) if mc.spec.url == url))
+            .context(ModNotInProfileSnafu {
+                profile: profile.to_string(),
+                url: url.to_string(),
+            })?;
+        let ModOrGroup::Individual(mc) = p.mods.remove(index) else {
+            unreachable!()
+        };
+        Ok(mc)
+    }
+
+    /// Finds a mod in a profile by URL, looking through both individual mods and mod groups.
+    pub fn get_mod_mut(&mut self, profile: &str, url: &str) -> Result<&mut ModConfig, StateError> {
+        let location =
+            self.get_profile(profile)?
+                .mods
+                .iter()
+                .enumerate()
+                .find_map(|(i, m)| match m {
+                    ModOrGroup::Individual(mc) => (mc.spec.url == url).then_some((i, None)),
+                    ModOrGroup::Group { group_name, .. } => self
+                        .groups
+                        .get(group_name)?
+                        .mods
+                        .iter()
+                        .position(|mc| mc.spec.url == url)
+                        .map(|j| (i, Some((group_name.clone(), j)))),
+                });
+        match location {
+            Some((i, None)) => match &mut self.profiles.get_mut(profile).unwrap().mods[i] {
+                ModOrGroup::Individual(mc) => Ok(mc),
+                ModOrGroup::Group { .. } => unreachable!(),
+            },
+            Some((_, Some((group_name, j)))) => {
+                Ok(&mut self.groups.get_mut(&group_name).unwrap().mods[j])
+            }
+            None => ModNotInProfileSnafu {
+                profile: profile.to_string(),
+                url: url.to_string(),
+            }
+            .fail(),
+        }
+    }
 }
 
 fn is_false(value: &bool) -> bool {
@@ -454,6 +685,48 @@ pub enum StateError {
     ModDataDeserializationFailed { source: serde_json::Error },
     #[snafu(display("failed to deserialize legacy profiles"))]
     LegacyProfilesDeserializationFailed { source: serde_json::Error },
+    #[snafu(display("profile \"{profile}\" does not exist"))]
+    ProfileNotFound { profile: String },
+    #[snafu(display("profile \"{profile}\" already exists"))]
+    ProfileAlreadyExists { profile: String },
+    #[snafu(display("cannot delete the last remaining profile"))]
+    LastProfile,
+    #[snafu(display("profile \"{profile}\" does not contain mod <{url}>"))]
+    ModNotInProfile { profile: String, url: String },
+    #[snafu(display("failed to deserialize lockfile {}", path.display()))]
+    LockfileDeserializationFailed {
+        source: serde_json::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("unsupported lockfile version in {}", path.display()))]
+    UnsupportedLockfileVersion { path: PathBuf },
+    #[snafu(display("lockfile {} belongs to profile \"{found}\", not \"{profile}\"", path.display()))]
+    LockfileProfileMismatch {
+        path: PathBuf,
+        profile: String,
+        found: String,
+    },
+    #[snafu(display("profile \"{profile}\" has no lockfile, create one with `mint lock`"))]
+    NoLockfile { profile: String },
+    #[snafu(display(
+        "mod <{url}> is not in the lockfile of profile \"{profile}\", run `mint lock` to add it"
+    ))]
+    NotLocked { profile: String, url: String },
+    #[snafu(display("SHA-256 of mod <{url}> is {found} but the lockfile expects {expected}"))]
+    LockedHashMismatch {
+        url: String,
+        expected: String,
+        found: String,
+    },
+    #[snafu(display("failed to deserialize profile export {}", path.display()))]
+    ProfileExportDeserializationFailed {
+        source: serde_json::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("unsupported profile export version in {}", path.display()))]
+    UnsupportedProfileExportVersion { path: PathBuf },
+    #[snafu(display("profile export references group \"{group}\" which it does not contain"))]
+    ProfileExportMissingGroup { group: String },
 }
 
 pub struct State {
@@ -561,6 +834,7 @@ mod mod_data_tests {
             required: false,
             enabled: false,
             priority: 50,
+            disabled_paks: Default::default(),
         };
 
         let mod_2 = ModConfig {
@@ -568,6 +842,7 @@ mod mod_data_tests {
             required: true,
             enabled: false,
             priority: 50,
+            disabled_paks: Default::default(),
         };
 
         let mod_3 = ModConfig {
@@ -575,6 +850,7 @@ mod mod_data_tests {
             required: false,
             enabled: true,
             priority: 50,
+            disabled_paks: Default::default(),
         };
 
         let mod_data = ModData {
@@ -615,6 +891,7 @@ mod mod_data_tests {
             required: false,
             enabled: false,
             priority: 50,
+            disabled_paks: Default::default(),
         };
 
         let mod_2 = ModConfig {
@@ -622,6 +899,7 @@ mod mod_data_tests {
             required: true,
             enabled: false,
             priority: 50,
+            disabled_paks: Default::default(),
         };
 
         let mod_3 = ModConfig {
@@ -629,6 +907,7 @@ mod mod_data_tests {
             required: false,
             enabled: true,
             priority: 50,
+            disabled_paks: Default::default(),
         };
 
         let mod_data = ModData {
@@ -669,6 +948,7 @@ mod mod_data_tests {
             required: false,
             enabled: false,
             priority: 50,
+            disabled_paks: Default::default(),
         };
 
         let mod_2 = ModConfig {
@@ -676,6 +956,7 @@ mod mod_data_tests {
             required: true,
             enabled: false,
             priority: 50,
+            disabled_paks: Default::default(),
         };
 
         let mod_3 = ModConfig {
@@ -683,6 +964,7 @@ mod mod_data_tests {
             required: false,
             enabled: true,
             priority: 50,
+            disabled_paks: Default::default(),
         };
 
         let mod_data = ModData {
@@ -712,4 +994,274 @@ mod mod_data_tests {
         let any_required = mod_data.any_mod("default", |mc, _| mc.required);
         assert!(any_required);
     }
+
+    #[test]
+    fn test_profile_management() {
+        let mut mod_data = ModData::default();
+
+        mod_data.create_profile("a").unwrap();
+        assert!(mod_data.create_profile("a").is_err());
+
+        mod_data.active_profile = "a".to_string();
+        mod_data.rename_profile("a", "b").unwrap();
+        assert_eq!(mod_data.active_profile, "b");
+        assert!(mod_data.get_profile("a").is_err());
+
+        mod_data.copy_profile("b", "c").unwrap();
+        mod_data.delete_profile("b").unwrap();
+        assert_ne!(mod_data.active_profile, "b");
+
+        mod_data.delete_profile("c").unwrap();
+        assert!(mod_data.delete_profile("default").is_err());
+    }
+
+    #[test]
+    fn test_get_mod_mut() {
+        let mod_1 = ModConfig {
+            spec: ModSpecification::new("a".to_string()),
+            required: false,
+            enabled: false,
+            priority: 0,
+            disabled_paks: Default::default(),
+        };
+
+        let mod_2 = ModConfig {
+            spec: ModSpecification::new("b".to_string()),
+            required: false,
+            enabled: false,
+            priority: 0,
+            disabled_paks: Default::default(),
+        };
+
+        let mut mod_data = ModData {
+            active_profile: "default".to_string(),
+            profiles: [(
+                "default".to_string(),
+                ModProfile {
+                    mods: vec![ModOrGroup::Group {
+                        group_name: "mg1".to_string(),
+                        enabled: true,
+                    }],
+                },
+            )]
+            .into(),
+            groups: [("mg1".to_string(), ModGroup { mods: vec![mod_2] })].into(),
+        };
+
+        mod_data.add_mod("default", mod_1.clone()).unwrap();
+        mod_data.add_mod("default", mod_1).unwrap();
+        assert_eq!(mod_data.get_profile("default").unwrap().mods.len(), 2);
+
+        mod_data.get_mod_mut("default", "a").unwrap().enabled = true;
+        mod_data.get_mod_mut("default", "b").unwrap().priority = 5;
+        assert!(mod_data.get_mod_mut("default", "c").is_err());
+
+        let mut enabled = vec![];
+        mod_data.for_each_enabled_mod("default", |mc| enabled.push(mc.spec.url.clone()));
+        assert_eq!(enabled, vec!["a".to_string()]);
+        assert_eq!(mod_data.groups["mg1"].mods[0].priority, 5);
+
+        assert!(mod_data.remove_mod("default", "b").is_err());
+        mod_data.remove_mod("default", "a").unwrap();
+        assert_eq!(mod_data.get_profile("default").unwrap().mods.len(), 1);
+    }
+
+    #[test]
+    fn test_add_mod_keeps_existing_entry() {
+        let mod_config = |url: &str, enabled: bool, priority: i32| ModConfig {
+            spec: ModSpecification::new(url.to_string()),
+            required: false,
+            enabled,
+            priority,
+            disabled_paks: Default::default(),
+        };
+        let mut existing = mod_config("a", false, 5);
+        existing.required = true;
+        existing.disabled_paks = ["Variant.pak".to_string()].into();
+
+        let mut mod_data = ModData {
+            active_profile: "default".to_string(),
+            profiles: [(
+                "default".to_string(),
+                ModProfile {
+                    mods: vec![
+                        ModOrGroup::Individual(mod_config("b", true, 0)),
+                        ModOrGroup::Individual(existing),
+                        ModOrGroup::Group {
+                            group_name: "mg1".to_string(),
+                            enabled: false,
+                        },
+                    ],
+                },
+            )]
+            .into(),
+            groups: [(
+                "mg1".to_string(),
+                ModGroup {
+                    mods: vec![mod_config("c", false, 0)],
+                },
+            )]
+            .into(),
+        };
+
+        // adding a disabled mod leaves the existing entry as it is
+        mod_data
+            .add_mod("default", mod_config("a", false, 0))
+            .unwrap();
+        assert!(!mod_data.get_mod_mut("default", "a").unwrap().enabled);
+
+        for url in ["a", "c", "d"] {
+            mod_data
+                .add_mod("default", mod_config(url, true, 0))
+                .unwrap();
+        }
+
+        let mods = &mod_data.get_profile("default").unwrap().mods;
+        let urls = mods
+            .iter()
+            .map(|m| match m {
+                ModOrGroup::Individual(mc) => mc.spec.url.as_str(),
+                ModOrGroup::Group { group_name, .. } => group_name.as_str(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(urls, ["d", "b", "a", "mg1"]);
+
+        let a = mod_data.get_mod_mut("default", "a").unwrap();
+        assert!(a.enabled);
+        assert!(a.required);
+        assert_eq!(a.priority, 5);
+        assert_eq!(
+            a.disabled_paks,
+            std::collections::BTreeSet::from(["Variant.pak".to_string()])
+        );
+
+        let mut enabled = vec![];
+        mod_data.for_each_enabled_mod("default", |mc| enabled.push(mc.spec.url.clone()));
+        assert_eq!(enabled, ["d", "b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_disabled_paks() {
+        let mod_1 = ModConfig {
+            spec: ModSpecification::new("a".to_string()),
+            required: false,
+            enabled: true,
+            priority: 0,
+            disabled_paks: ["Variant.pak".to_string()].into(),
+        };
+        let mod_2 = ModConfig {
+            spec: ModSpecification::new("b".to_string()),
+            required: false,
+            enabled: false,
+            priority: 0,
+            disabled_paks: ["Variant.pak".to_string()].into(),
+        };
+        let mod_3 = ModConfig {
+            spec: ModSpecification::new("c".to_string()),
+            required: false,
+            enabled: true,
+            priority: 0,
+            disabled_paks: Default::default(),
+        };
+
+        let mod_data = ModData {
+            active_profile: "default".to_string(),
+            profiles: [(
+                "default".to_string(),
+                ModProfile {
+                    mods: vec![
+                        ModOrGroup::Individual(mod_1.clone()),
+                        ModOrGroup::Individual(mod_2),
+                        ModOrGroup::Individual(mod_3),
+                    ],
+                },
+            )]
+            .into(),
+            groups: Default::default(),
+        };
+
+        let disabled_paks = mod_data.disabled_paks("default");
+        assert_eq!(disabled_paks.len(), 1);
+        assert_eq!(disabled_paks[&mod_1.spec], mod_1.disabled_paks);
+
+        let json = serde_json::to_string(&mod_1).unwrap();
+        assert_eq!(serde_json::from_str::<ModConfig>(&json).unwrap(), mod_1);
+        assert!(
+            !serde_json::to_string(&mod_data.profiles["default"].mods[2])
+                .unwrap()
+                .contains("disabled_paks")
+        );
+    }
+
+    #[test]
+    fn test_profile_export_import() {
+        let mod_1 = ModConfig {
+            spec: ModSpecification::new("a".to_string()),
+            required: false,
+            enabled: true,
+            priority: 3,
+            disabled_paks: Default::default(),
+        };
+        let mod_2 = ModConfig {
+            spec: ModSpecification::new("b".to_string()),
+            required: false,
+            enabled: false,
+            priority: 0,
+            disabled_paks: Default::default(),
+        };
+
+        let mut mod_data = ModData {
+            active_profile: "default".to_string(),
+            profiles: [(
+                "default".to_string(),
+                ModProfile {
+                    mods: vec![
+                        ModOrGroup::Individual(mod_1),
+                        ModOrGroup::Group {
+                            group_name: "mg1".to_string(),
+                            enabled: true,
+                        },
+                    ],
+                },
+            )]
+            .into(),
+            groups: [("mg1".to_string(), ModGroup { mods: vec![mod_2] })].into(),
+        };
+
+        let export = mod_data.export_profile("default").unwrap();
+        let buf = export.clone().to_string_pretty().unwrap();
+        let parsed =
+            serde_json::from_str::<super::profile_export::VersionAnnotatedProfileExport>(&buf)
+                .unwrap();
+        assert!(matches!(
+            parsed,
+            super::profile_export::VersionAnnotatedProfileExport::V0_0_0(_)
+        ));
+        assert_eq!(export.specs().len(), 2);
+
+        // identical group is reused, clashing profile name is renamed
+        let name = mod_data
+            .import_profile(export.clone(), None, false)
+            .unwrap();
+        assert_eq!(name, "default (2)");
+        assert_eq!(mod_data.groups.len(), 1);
+
+        // group with different contents is renamed
+        mod_data.groups.get_mut("mg1").unwrap().mods.clear();
+        let name = mod_data
+            .import_profile(export.clone(), None, false)
+            .unwrap();
+        assert_eq!(name, "default (3)");
+        assert_eq!(mod_data.groups["mg1 (2)"].mods.len(), 1);
+        assert!(matches!(
+            &mod_data.get_profile(&name).unwrap().mods[1],
+            ModOrGroup::Group { group_name, .. } if group_name == "mg1 (2)"
+        ));
+
+        let name = mod_data
+            .import_profile(export, Some("default"), true)
+            .unwrap();
+        assert_eq!(name, "default");
+        assert_eq!(mod_data.profiles.len(), 3);
+    }
 }
@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use super::config::write_json_atomic;
+use super::*;
+
+/// Portable representation of a single profile, including the groups it references.
+#[obake::versioned]
+#[obake(version("0.0.0"))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileExport {
+    pub name: String,
+    pub mods: Vec<ModOrGroup>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, ModGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "version")]
+pub enum VersionAnnotatedProfileExport {
+    #[serde(rename = "0.0.0")]
+    V0_0_0(ProfileExport!["0.0.0"]),
+    #[serde(other)]
+    Unsupported,
+}
+
+impl ProfileExport!["0.0.0"] {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, StateError> {
+        let path = path.as_ref();
+        let export = serde_json::from_slice::<VersionAnnotatedProfileExport>(&fs::read(path)?)
+            .context(ProfileExportDeserializationFailedSnafu { path })?;
+        match export {
+            VersionAnnotatedProfileExport::V0_0_0(export) => Ok(export),
+            VersionAnnotatedProfileExport::Unsupported => {
+                UnsupportedProfileExportVersionSnafu { path }.fail()
+            }
+        }
+    }
+
+    pub fn write<P: AsRef<Path>>(self, path: P) -> Result<(), StateError> {
+        write_json_atomic(path.as_ref(), &VersionAnnotatedProfileExport::V0_0_0(self))
+    }
+
+    pub fn to_string_pretty(self) -> Result<String, StateError> {
+        serde_json::to_string_pretty(&VersionAnnotatedProfileExport::V0_0_0(self))
+            .context(CfgSerializationFailedSnafu)
+    }
+
+    pub fn specs(&self) -> Vec<ModSpecification> {
+        self.mods
+            .iter()
+            .flat_map(|m| match m {
+                ModOrGroup::Individual(mc) => vec![mc.spec.clone()],
+                ModOrGroup::Group { group_name, .. } => self
+                    .groups
+                    .get(group_name)
+                    .map(|g| g.mods.iter().map(|mc| mc.spec.clone()).collect())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+/// Append " (2)", " (3)", ... to `base` until `exists` returns false.
+fn unique_name(base: &str, exists: impl Fn(&str) -> bool) -> String {
+    if !exists(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|i| format!("{base} ({i})"))
+        .find(|name| !exists(name))
+        .unwrap()
+}
+
+impl ModData_v0_1_0 {
+    pub fn export_profile(&self, profile: &str) -> Result<ProfileExport!["0.0.0"], StateError> {
+        let mods = self.get_profile(profile)?.mods.clone();
+        let groups = mods
+            .iter()
+            .filter_map(|m| match m {
+                ModOrGroup::Group { group_name, .. } => self
+                    .groups
+                    .get(group_name)
+                    .map(|g| (group_name.clone(), g.clone())),
+                ModOrGroup::Individual(_) => None,
+            })
+            .collect();
+        Ok(ProfileExport_v0_0_0 {
+            name: profile.to_string(),
+            mods,
+            groups,
+        })
+    }
+
+    /// Import an exported profile as `name` (or the exported name if `None`) and return the name
+    /// it was imported as. If a profile of that name already exists it is overwritten if `replace`
+    /// is set, otherwise the imported profile is renamed. Groups are shared between profiles so a
+    /// clashing group with different contents is always imported under a new name.
+    pub fn import_profile(
+        &mut self,
+        export: ProfileExport!["0.0.0"],
+        name: Option<&str>,
+        replace: bool,
+    ) -> Result<String, StateError> {
+        let ProfileExport_v0_0_0 {
+            name: exported_name,
+            mut mods,
+            groups,
+        } = export;
+
+        for m in &mods {
+            if let ModOrGroup::Group { group_name, .. } = m {
+                ensure!(
+                    groups.contains_key(group_name),
+                    ProfileExportMissingGroupSnafu {
+                        group: group_name.clone()
+                    }
+                );
+            }
+        }
+
+        let mut group_renames = HashMap::new();
+        for (group_name, group) in groups {
+            let new_name = match self.groups.get(&group_name) {
+                Some(existing) if *existing == group => group_name.clone(),
+                Some(_) => unique_name(&group_name, |n| self.groups.contains_key(n)),
+                None => group_name.clone(),
+            };
+            self.groups.insert(new_name.clone(), group);
+            group_renames.insert(group_name, new_name);
+        }
+        for m in &mut mods {
+            if let ModOrGroup::Group { group_name, .. } = m {
+                *group_name = group_renames[group_name.as_str()].clone();
+            }
+        }
+
+        let name = name.unwrap_or(&exported_name);
+        let name = if replace {
+            name.to_string()
+        } else {
+            unique_name(name, |n| self.profiles.contains_key(n))
+        };
+        self.profiles
+            .insert(name.clone(), ModProfile_v0_1_0 { mods });
+        Ok(name)
+    }
+}
@@ -0,0 +1,87 @@
+//! Comparison of the installed mod bundle with a profile, used by `mint status` and the installed
+//! bundle indicator of the GUI.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use mint_lib::mod_info::Meta;
+use mint_lib::DRGInstallation;
+
+use crate::integrate::{read_bundle_meta, IntegrationError};
+use crate::state::State;
+
+/// Read the [`Meta`] of the mod bundle installed into the installation `pak_path` belongs to.
+/// Returns `None` if no mod bundle is installed.
+pub fn installed_meta<P: AsRef<Path>>(pak_path: P) -> Result<Option<Meta>, IntegrationError> {
+    let Ok(installation) = DRGInstallation::from_pak_path(&pak_path) else {
+        return Err(IntegrationError::DrgInstallationNotFound {
+            path: pak_path.as_ref().to_path_buf(),
+        });
+    };
+    let path = installation.paks_path().join("mods_P.pak");
+    if !path.exists() {
+        return Ok(None);
+    }
+    read_bundle_meta(&path).map(Some)
+}
+
+/// Differences between the installed mod bundle and a profile.
+#[derive(Debug, Default)]
+pub struct BundleDiff {
+    /// Enabled mods of the profile that are not in the bundle.
+    pub missing: Vec<String>,
+    /// Mods in the bundle that are not enabled in the profile.
+    pub extra: Vec<String>,
+    /// Whether the bundle was integrated with different settings.
+    pub config_changed: bool,
+}
+
+impl BundleDiff {
+    pub fn is_up_to_date(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && !self.config_changed
+    }
+
+    /// One line per difference, suitable for printing or a tooltip.
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![];
+        for url in &self.missing {
+            lines.push(format!("not installed: {url}"));
+        }
+        for url in &self.extra {
+            lines.push(format!("no longer enabled: {url}"));
+        }
+        if self.config_changed {
+            lines.push("settings changed".to_string());
+        }
+        lines
+    }
+}
+
+/// Compare the enabled mods of `profile` with the mods of the installed bundle `meta`.
+///
+/// Mods are compared by their resolved URL as recorded in the bundle. Mods that have never been
+/// resolved are compared by their specification URL instead, so they show up as missing until
+/// changes are applied.
+pub fn compare(state: &State, profile: &str, meta: &Meta) -> BundleDiff {
+    let mut enabled = BTreeSet::new();
+    state.mod_data.for_each_enabled_mod(profile, |mc| {
+        let url = state
+            .store
+            .get_mod_info(&mc.spec)
+            .map(|info| info.resolution.get_resolvable_url_or_name().to_string())
+            .unwrap_or_else(|| mc.spec.url.clone());
+        enabled.insert(url);
+    });
+    let installed = meta
+        .mods
+        .iter()
+        .map(|m| m.url.clone())
+        .collect::<BTreeSet<_>>();
+
+    BundleDiff {
+        missing: enabled.difference(&installed).cloned().collect(),
+        extra: installed.difference(&enabled).cloned().collect(),
+        config_changed: meta.config.disable_fix_exploding_gas
+            != state.config.disable_fix_exploding_gas,
+    }
+}
@@ -0,0 +1,357 @@
+//! Synchronization between profiles and the mod.io subscriptions used by the official mod
+//! integration, used by `mint subscriptions` and the subscriptions window of the GUI.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::providers::modio::{is_modio_url, ModioSearchResult};
+use crate::providers::{ModInfo, ModSpecification, ModStore, ProviderError};
+use crate::state::ModData_v0_1_0 as ModData;
+
+/// Direction changes are applied in when synchronizing a profile with the subscriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    /// Add the subscribed mods to the profile.
+    Pull,
+    /// Subscribe to the mods of the profile and unsubscribe from the others.
+    Push,
+}
+
+/// A mod.io mod that is only subscribed to or only in the profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncMod {
+    pub modio_id: u32,
+    pub spec: ModSpecification,
+    pub name: String,
+    /// Disabled entry of a subscribed mod in the profile, which pulling enables instead of adding
+    /// the mod again.
+    pub disabled_spec: Option<ModSpecification>,
+}
+
+/// Differences between the mod.io subscriptions and the enabled mod.io mods of a profile.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionDiff {
+    /// Subscribed mods the profile does not contain or only contains disabled.
+    pub only_subscribed: Vec<SyncMod>,
+    /// Enabled mods of the profile that are not subscribed to.
+    pub only_in_profile: Vec<SyncMod>,
+}
+
+impl SubscriptionDiff {
+    /// Whether applying the diff in `direction` would not change anything.
+    pub fn is_empty(&self, direction: SyncDirection) -> bool {
+        match direction {
+            SyncDirection::Pull => self.only_subscribed.is_empty(),
+            SyncDirection::Push => {
+                self.only_subscribed.is_empty() && self.only_in_profile.is_empty()
+            }
+        }
+    }
+
+    /// One line per change applying the diff in `direction` makes, suitable for printing.
+    pub fn describe(&self, direction: SyncDirection, profile: &str) -> Vec<String> {
+        let mut lines = vec![];
+        match direction {
+            SyncDirection::Pull => {
+                for m in &self.only_subscribed {
+                    lines.push(match &m.disabled_spec {
+                        Some(spec) => format!("enable in \"{profile}\": {} {}", m.name, spec.url),
+                        None => format!("add to \"{profile}\": {} {}", m.name, m.spec.url),
+                    });
+                }
+            }
+            SyncDirection::Push => {
+                for m in &self.only_in_profile {
+                    lines.push(format!("subscribe: {} {}", m.name, m.spec.url));
+                }
+                for m in &self.only_subscribed {
+                    lines.push(format!("unsubscribe: {} {}", m.name, m.spec.url));
+                }
+            }
+        }
+        lines
+    }
+
+    /// Specifications to add to the profile when pulling.
+    pub fn specs_to_add(&self) -> Vec<ModSpecification> {
+        self.only_subscribed
+            .iter()
+            .filter(|m| m.disabled_spec.is_none())
+            .map(|m| m.spec.clone())
+            .collect()
+    }
+
+    /// Specifications of disabled profile entries to enable when pulling.
+    pub fn specs_to_enable(&self) -> Vec<ModSpecification> {
+        self.only_subscribed
+            .iter()
+            .filter_map(|m| m.disabled_spec.clone())
+            .collect()
+    }
+}
+
+/// A mod of a profile compared with the subscriptions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileMod {
+    pub spec: ModSpecification,
+    /// Whether the mod and the group it is in, if any, are enabled.
+    pub enabled: bool,
+}
+
+/// Mods of `profile` that come from mod.io, enabled or not.
+pub fn profile_modio_mods(mod_data: &ModData, profile: &str) -> Vec<ProfileMod> {
+    let mut enabled = HashSet::new();
+    mod_data.for_each_enabled_mod(profile, |mc| {
+        enabled.insert(mc.spec.clone());
+    });
+    let mut mods = vec![];
+    mod_data.for_each_mod(profile, |mc| {
+        if is_modio_url(&mc.spec.url) {
+            mods.push(ProfileMod {
+                spec: mc.spec.clone(),
+                enabled: enabled.contains(&mc.spec),
+            });
+        }
+    });
+    mods
+}
+
+/// Compare the mods returned by [`ModStore::modio_subscriptions`] with the mod.io mods of a
+/// profile resolved to `resolved`. Mods are matched by their mod.io ID, so a pinned version in the
+/// profile matches a subscription.
+pub fn diff(
+    subscriptions: Vec<ModioSearchResult>,
+    profile_mods: &[ProfileMod],
+    resolved: &HashMap<ModSpecification, ModInfo>,
+) -> SubscriptionDiff {
+    let profile_mods = profile_mods
+        .iter()
+        .filter_map(|m| {
+            let info = resolved.get(&m.spec)?;
+            Some((m, info.modio_id?, info))
+        })
+        .collect::<Vec<_>>();
+    let enabled = profile_mods
+        .iter()
+        .filter(|(m, _, _)| m.enabled)
+        .map(|(_, modio_id, _)| *modio_id)
+        .collect::<HashSet<_>>();
+    let subscribed = subscriptions
+        .iter()
+        .map(|s| s.modio_id)
+        .collect::<HashSet<_>>();
+
+    let mut seen = HashSet::new();
+    let mut only_subscribed = subscriptions
+        .into_iter()
+        .filter(|s| !enabled.contains(&s.modio_id) && seen.insert(s.modio_id))
+        .map(|s| SyncMod {
+            modio_id: s.modio_id,
+            disabled_spec: profile_mods
+                .iter()
+                .find(|(_, modio_id, _)| *modio_id == s.modio_id)
+                .map(|(m, _, _)| m.spec.clone()),
+            spec: s.spec,
+            name: s.name,
+        })
+        .collect::<Vec<_>>();
+    let mut seen = HashSet::new();
+    let mut only_in_profile = profile_mods
+        .iter()
+        .filter(|(m, modio_id, _)| {
+            m.enabled && !subscribed.contains(modio_id) && seen.insert(*modio_id)
+        })
+        .map(|(m, modio_id, info)| SyncMod {
+            modio_id: *modio_id,
+            spec: m.spec.clone(),
+            name: info.name.clone(),
+            disabled_spec: None,
+        })
+        .collect::<Vec<_>>();
+    only_subscribed.sort_by(|a, b| a.name.cmp(&b.name));
+    only_in_profile.sort_by(|a, b| a.name.cmp(&b.name));
+
+    SubscriptionDiff {
+        only_subscribed,
+        only_in_profile,
+    }
+}
+
+/// Fetch the subscriptions and resolve `profile_mods` to compare them, see [`diff`].
+pub async fn fetch_diff(
+    store: &ModStore,
+    profile_mods: &[ProfileMod],
+) -> Result<SubscriptionDiff, ProviderError> {
+    let subscriptions = store.modio_subscriptions().await?;
+    let specs = profile_mods
+        .iter()
+        .map(|m| m.spec.clone())
+        .collect::<Vec<_>>();
+    let resolved = store.resolve_mods(&specs, false).await?;
+    Ok(diff(subscriptions, profile_mods, &resolved))
+}
+
+/// Subscribe to the mods only in the profile and unsubscribe from the mods only subscribed to.
+pub async fn push(store: &ModStore, diff: &SubscriptionDiff) -> Result<(), ProviderError> {
+    for m in &diff.only_in_profile {
+        store.set_modio_subscribed(m.modio_id, true).await?;
+    }
+    for m in &diff.only_subscribed {
+        store.set_modio_subscribed(m.modio_id, false).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::providers::{ApprovalStatus, ModResolution, ModioTags, RequiredStatus};
+
+    fn spec(url: &str) -> ModSpecification {
+        ModSpecification::new(url.to_string())
+    }
+
+    fn subscription(modio_id: u32, name: &str) -> ModioSearchResult {
+        ModioSearchResult {
+            modio_id,
+            spec: spec(&format!("https://mod.io/g/drg/m/{name}#{modio_id}")),
+            name: name.to_string(),
+            summary: String::new(),
+            modio_tags: ModioTags {
+                qol: false,
+                gameplay: false,
+                audio: false,
+                visual: false,
+                framework: false,
+                versions: Default::default(),
+                required_status: RequiredStatus::Optional,
+                approval_status: ApprovalStatus::Approved,
+            },
+        }
+    }
+
+    fn profile_mod(url: &str, enabled: bool) -> ProfileMod {
+        ProfileMod {
+            spec: spec(url),
+            enabled,
+        }
+    }
+
+    fn info(url: &str, modio_id: u32, name: &str) -> (ModSpecification, ModInfo) {
+        let info = ModInfo {
+            provider: "modio",
+            name: name.to_string(),
+            spec: spec(url),
+            versions: vec![],
+            resolution: ModResolution::resolvable(url.to_string().into()),
+            suggested_require: false,
+            suggested_dependencies: vec![],
+            modio_tags: None,
+            modio_id: Some(modio_id),
+        };
+        (spec(url), info)
+    }
+
+    fn names(mods: &[SyncMod]) -> Vec<&str> {
+        mods.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_diff_only_subscribed_and_only_in_profile() {
+        let profile = [
+            profile_mod("https://mod.io/g/drg/m/both#1", true),
+            profile_mod("https://mod.io/g/drg/m/local#2", true),
+        ];
+        let resolved = HashMap::from([
+            info("https://mod.io/g/drg/m/both#1", 1, "both"),
+            info("https://mod.io/g/drg/m/local#2", 2, "local"),
+        ]);
+        let diff = diff(
+            vec![subscription(1, "both"), subscription(3, "remote")],
+            &profile,
+            &resolved,
+        );
+
+        assert_eq!(vec!["remote"], names(&diff.only_subscribed));
+        assert_eq!(vec!["local"], names(&diff.only_in_profile));
+        assert_eq!(
+            vec![spec("https://mod.io/g/drg/m/remote#3")],
+            diff.specs_to_add()
+        );
+        assert!(diff.specs_to_enable().is_empty());
+        assert!(!diff.is_empty(SyncDirection::Pull));
+    }
+
+    #[test]
+    fn test_diff_pinned_matches_unpinned() {
+        let profile = [profile_mod("https://mod.io/g/drg/m/pinned#1/10", true)];
+        let resolved = HashMap::from([info("https://mod.io/g/drg/m/pinned#1/10", 1, "pinned")]);
+        let diff = diff(vec![subscription(1, "pinned")], &profile, &resolved);
+
+        assert!(diff.is_empty(SyncDirection::Pull));
+        assert!(diff.is_empty(SyncDirection::Push));
+    }
+
+    #[test]
+    fn test_diff_enables_disabled_mods() {
+        let profile = [
+            profile_mod("https://mod.io/g/drg/m/disabled#1/10", false),
+            profile_mod("https://mod.io/g/drg/m/not-subscribed#2", false),
+        ];
+        let resolved = HashMap::from([
+            info("https://mod.io/g/drg/m/disabled#1/10", 1, "disabled"),
+            info(
+                "https://mod.io/g/drg/m/not-subscribed#2",
+                2,
+                "not-subscribed",
+            ),
+        ]);
+        let diff = diff(vec![subscription(1, "disabled")], &profile, &resolved);
+
+        // disabled mods are kept as they are when pushing
+        assert!(diff.only_in_profile.is_empty());
+        assert_eq!(vec!["disabled"], names(&diff.only_subscribed));
+        assert!(diff.specs_to_add().is_empty());
+        assert_eq!(
+            vec![spec("https://mod.io/g/drg/m/disabled#1/10")],
+            diff.specs_to_enable()
+        );
+        assert_eq!(
+            vec![
+                "enable in \"default\": disabled https://mod.io/g/drg/m/disabled#1/10".to_string()
+            ],
+            diff.describe(SyncDirection::Pull, "default")
+        );
+    }
+
+    #[test]
+    fn test_diff_dedup() {
+        // the same mod pinned and unpinned, one of them enabled
+        let profile = [
+            profile_mod("https://mod.io/g/drg/m/twice#1", true),
+            profile_mod("https://mod.io/g/drg/m/twice#1/10", false),
+            profile_mod("https://mod.io/g/drg/m/local#2", true),
+            profile_mod("https://mod.io/g/drg/m/local#2/20", true),
+        ];
+        let resolved = HashMap::from([
+            info("https://mod.io/g/drg/m/twice#1", 1, "twice"),
+            info("https://mod.io/g/drg/m/twice#1/10", 1, "twice"),
+            info("https://mod.io/g/drg/m/local#2", 2, "local"),
+            info("https://mod.io/g/drg/m/local#2/20", 2, "local"),
+        ]);
+        let diff = diff(
+            vec![subscription(1, "twice"), subscription(1, "twice")],
+            &profile,
+            &resolved,
+        );
+
+        assert!(diff.only_subscribed.is_empty());
+        assert_eq!(vec!["local"], names(&diff.only_in_profile));
+
+        let diff = super::diff(
+            vec![subscription(3, "remote"), subscription(3, "remote")],
+            &[],
+            &HashMap::new(),
+        );
+        assert_eq!(vec!["remote"], names(&diff.only_subscribed));
+    }
+}
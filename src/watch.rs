@@ -0,0 +1,264 @@
+//! Change detection for mods backed by local files and directories, used by `mint watch` and the
+//! watch toggle of the GUI.
+//!
+//! Files are polled rather than watched through OS notifications: there are usually only a few
+//! local mods and cooking writes many files in bursts, which have to be debounced either way.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use fs_err as fs;
+
+use crate::state::State;
+
+/// How often [`LocalModWatcher::poll`] checks the files for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Paths of the enabled mods of `profile` which are local files or directories.
+pub fn local_mod_paths(state: &State, profile: &str) -> Vec<PathBuf> {
+    let mut paths = vec![];
+    state.mod_data.for_each_enabled_mod(profile, |mc| {
+        let path = Path::new(&mc.spec.url);
+        if path.exists() {
+            paths.push(path.to_path_buf());
+        }
+    });
+    paths
+}
+
+/// Modification time and size of every file below the watched paths. Missing paths are recorded
+/// too so that deleting and recreating a mod is noticed.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Snapshot(BTreeMap<PathBuf, Option<(SystemTime, u64)>>);
+
+impl Snapshot {
+    fn take(paths: &[PathBuf]) -> Self {
+        let mut snapshot = BTreeMap::new();
+        for path in paths {
+            snapshot_path(path, &mut snapshot);
+        }
+        Self(snapshot)
+    }
+}
+
+fn snapshot_path(path: &Path, snapshot: &mut BTreeMap<PathBuf, Option<(SystemTime, u64)>>) {
+    let Ok(metadata) = fs::metadata(path) else {
+        snapshot.insert(path.to_path_buf(), None);
+        return;
+    };
+    if metadata.is_dir() {
+        snapshot_dir(path, snapshot);
+    } else {
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        snapshot.insert(path.to_path_buf(), Some((modified, metadata.len())));
+    }
+}
+
+/// Symlinked directories below a watched directory are skipped like when building a pak from it,
+/// they may link back up the tree.
+fn snapshot_dir(dir: &Path, snapshot: &mut BTreeMap<PathBuf, Option<(SystemTime, u64)>>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => snapshot_dir(&path, snapshot),
+            Ok(file_type) if file_type.is_symlink() && path.is_dir() => {}
+            _ => snapshot_path(&path, snapshot),
+        }
+    }
+}
+
+/// Polls local mods for changes and reports them once the files have stopped changing for the
+/// debounce duration.
+#[derive(Debug)]
+pub struct LocalModWatcher {
+    paths: Vec<PathBuf>,
+    debounce: Duration,
+    snapshot: Snapshot,
+    last_poll: Instant,
+    changed_at: Option<Instant>,
+}
+
+impl LocalModWatcher {
+    pub fn new(paths: Vec<PathBuf>, debounce: Duration) -> Self {
+        Self {
+            snapshot: Snapshot::take(&paths),
+            paths,
+            debounce,
+            last_poll: Instant::now(),
+            changed_at: None,
+        }
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Replace the watched paths. Does not count as a change, mods that were added or removed
+    /// have to be applied explicitly.
+    pub fn set_paths(&mut self, paths: Vec<PathBuf>) {
+        if paths != self.paths {
+            self.snapshot = Snapshot::take(&paths);
+            self.paths = paths;
+            self.changed_at = None;
+        }
+    }
+
+    /// Returns true once after the files changed and then stayed unchanged for the debounce
+    /// duration. Checks the files at most once per [`POLL_INTERVAL`] so it can be called often.
+    pub fn poll(&mut self) -> bool {
+        self.poll_at(Instant::now())
+    }
+
+    fn poll_at(&mut self, now: Instant) -> bool {
+        if now.saturating_duration_since(self.last_poll) < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = now;
+
+        let snapshot = Snapshot::take(&self.paths);
+        if snapshot != self.snapshot {
+            self.snapshot = snapshot;
+            self.changed_at = Some(now);
+            return false;
+        }
+        match self.changed_at {
+            Some(changed_at) if now.saturating_duration_since(changed_at) >= self.debounce => {
+                self.changed_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_nested() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("FSD/Content/Mod");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("a.uasset"), "a").unwrap();
+        fs::write(dir.path().join("b.uasset"), "b").unwrap();
+
+        let paths = vec![dir.path().to_path_buf()];
+        let before = Snapshot::take(&paths);
+        assert_eq!(
+            before.0.keys().collect::<Vec<_>>(),
+            [
+                &dir.path().join("FSD/Content/Mod/a.uasset"),
+                &dir.path().join("b.uasset")
+            ]
+        );
+
+        fs::write(nested.join("a.uasset"), "changed").unwrap();
+        assert_ne!(before, Snapshot::take(&paths));
+
+        fs::create_dir(nested.join("New")).unwrap();
+        fs::write(nested.join("New/c.uasset"), "c").unwrap();
+        assert_eq!(Snapshot::take(&paths).0.len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_snapshot_skips_symlinked_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("FSD/Content");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("a.uasset"), "a").unwrap();
+        std::os::unix::fs::symlink("..", nested.join("up")).unwrap();
+        std::os::unix::fs::symlink("../..", nested.join("root")).unwrap();
+        std::os::unix::fs::symlink("a.uasset", nested.join("b.uasset")).unwrap();
+
+        let snapshot = Snapshot::take(&[dir.path().to_path_buf()]);
+        assert_eq!(
+            snapshot.0.keys().collect::<Vec<_>>(),
+            [&nested.join("a.uasset"), &nested.join("b.uasset")]
+        );
+    }
+
+    #[test]
+    fn test_snapshot_deleted_and_recreated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mod.pak");
+        fs::write(&path, "pak").unwrap();
+
+        let paths = vec![path.clone()];
+        let before = Snapshot::take(&paths);
+        assert!(before.0[&path].is_some());
+
+        fs::remove_file(&path).unwrap();
+        let deleted = Snapshot::take(&paths);
+        assert_eq!(deleted.0[&path], None);
+
+        fs::write(&path, "new pak").unwrap();
+        let recreated = Snapshot::take(&paths);
+        assert!(recreated.0[&path].is_some());
+        assert_ne!(deleted, recreated);
+        assert_ne!(before, recreated);
+    }
+
+    #[test]
+    fn test_poll_debounce() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mod.pak");
+        fs::write(&path, "pak").unwrap();
+
+        let debounce = Duration::from_secs(2);
+        let mut watcher = LocalModWatcher::new(vec![path.clone()], debounce);
+        let start = Instant::now();
+
+        assert!(!watcher.poll_at(start + POLL_INTERVAL), "nothing changed");
+
+        fs::write(&path, "pak v2").unwrap();
+        let changed = start + POLL_INTERVAL * 2;
+        assert!(!watcher.poll_at(changed), "change is debounced");
+
+        // a further change restarts the debounce
+        fs::write(&path, "pak v3!").unwrap();
+        let changed_again = changed + debounce / 2;
+        assert!(!watcher.poll_at(changed_again));
+        assert!(!watcher.poll_at(changed + debounce));
+        let settled = changed_again + debounce;
+        assert!(!watcher.poll_at(settled - POLL_INTERVAL / 4));
+
+        // polls closer together than the interval are ignored
+        assert!(!watcher.poll_at(settled));
+
+        assert!(watcher.poll_at(settled + POLL_INTERVAL), "settled");
+        assert!(
+            !watcher.poll_at(settled + POLL_INTERVAL * 2),
+            "reported once"
+        );
+    }
+
+    #[test]
+    fn test_poll_deleted_and_recreated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mod.pak");
+        fs::write(&path, "pak").unwrap();
+
+        let mut watcher = LocalModWatcher::new(vec![path.clone()], Duration::ZERO);
+        let start = Instant::now();
+
+        fs::remove_file(&path).unwrap();
+        assert!(!watcher.poll_at(start + POLL_INTERVAL));
+        assert!(
+            watcher.poll_at(start + POLL_INTERVAL * 2),
+            "deletion reported"
+        );
+
+        fs::write(&path, "pak").unwrap();
+        assert!(!watcher.poll_at(start + POLL_INTERVAL * 3));
+        assert!(
+            watcher.poll_at(start + POLL_INTERVAL * 4),
+            "re-creation reported"
+        );
+    }
+}
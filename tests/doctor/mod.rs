@@ -0,0 +1,34 @@
+use mint::doctor::{diagnose, Severity};
+
+#[test]
+pub fn test_doctor_fake_installation() {
+    let dir = tempfile::tempdir().unwrap();
+    let paks = dir.path().join("FSD/Content/Paks");
+    std::fs::create_dir_all(&paks).unwrap();
+    let pak_path = paks.join("FSD-WindowsNoEditor.pak");
+    std::fs::write(&pak_path, b"not a pak").unwrap();
+    std::fs::write(paks.join("SomeMod_P.pak"), b"").unwrap();
+
+    let findings = diagnose(Some(&pak_path));
+    let find = |check: &str| {
+        findings
+            .iter()
+            .filter(|f| f.check == check)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(Severity::Error, find("game pak")[0].severity);
+    assert_eq!(Severity::Ok, find("installation")[0].severity);
+    assert_eq!(Severity::Warning, find("hook")[0].severity);
+    assert_eq!(Severity::Warning, find("mod bundle")[0].severity);
+
+    let manual = find("manual paks");
+    assert_eq!(1, manual.len());
+    assert_eq!(Severity::Warning, manual[0].severity);
+    assert!(manual[0].message.contains("SomeMod_P.pak"));
+    assert!(manual[0].fix.is_some());
+
+    assert!(diagnose(None)
+        .iter()
+        .any(|f| f.check == "game pak" && f.severity == Severity::Error));
+}
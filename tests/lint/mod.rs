@@ -23,7 +23,13 @@ pub fn test_lint_conflicting_files() {
 
     let LintReport {
         conflicting_mods, ..
-    } = mint::mod_lints::run_lints(&[LintId::CONFLICTING].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::CONFLICTING].into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
 
     println!("{:#?}", conflicting_mods);
 
@@ -51,7 +57,13 @@ pub fn test_lint_shader() {
 
     let LintReport {
         shader_file_mods, ..
-    } = mint::mod_lints::run_lints(&[LintId::SHADER_FILES].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::SHADER_FILES].into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
 
     println!("{:#?}", shader_file_mods);
 
@@ -80,8 +92,13 @@ pub fn test_lint_asset_registry_bin() {
     let LintReport {
         asset_register_bin_mods,
         ..
-    } = mint::mod_lints::run_lints(&[LintId::ASSET_REGISTRY_BIN].into(), mods.into(), None)
-        .unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::ASSET_REGISTRY_BIN].into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
 
     println!("{:#?}", asset_register_bin_mods);
 
@@ -105,8 +122,13 @@ pub fn test_lint_outdated_pak_version() {
     let LintReport {
         outdated_pak_version_mods,
         ..
-    } = mint::mod_lints::run_lints(&[LintId::OUTDATED_PAK_VERSION].into(), mods.into(), None)
-        .unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::OUTDATED_PAK_VERSION].into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
 
     println!("{:#?}", outdated_pak_version_mods);
 
@@ -129,7 +151,13 @@ pub fn test_lint_empty_archive() {
 
     let LintReport {
         empty_archive_mods, ..
-    } = mint::mod_lints::run_lints(&[LintId::EMPTY_ARCHIVE].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::EMPTY_ARCHIVE].into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
 
     println!("{:#?}", empty_archive_mods);
 
@@ -161,6 +189,7 @@ pub fn test_lint_only_non_pak_files() {
     } = mint::mod_lints::run_lints(
         &[LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES].into(),
         mods.into(),
+        Default::default(),
         None,
     )
     .unwrap();
@@ -189,6 +218,7 @@ pub fn test_lint_multi_pak_archive() {
     } = mint::mod_lints::run_lints(
         &[LintId::ARCHIVE_WITH_MULTIPLE_PAKS].into(),
         mods.into(),
+        Default::default(),
         None,
     )
     .unwrap();
@@ -200,6 +230,41 @@ pub fn test_lint_multi_pak_archive() {
         .contains(&multiple_paks_spec));
 }
 
+#[test]
+pub fn test_lint_skips_disabled_paks() {
+    let base_path = PathBuf::from_str("test_assets/lints/").unwrap();
+    assert!(base_path.exists());
+    let multiple_paks_archive_path = base_path.clone().join("multiple_paks.zip");
+    assert!(multiple_paks_archive_path.exists());
+    let multiple_paks_spec = ModSpecification {
+        url: "multiple_paks".to_string(),
+    };
+    let mods = [(multiple_paks_spec.clone(), multiple_paks_archive_path)];
+    let lints = [LintId::ARCHIVE_WITH_MULTIPLE_PAKS, LintId::SHADER_FILES].into();
+
+    let LintReport {
+        archive_with_multiple_paks_mods,
+        shader_file_mods,
+        ..
+    } = mint::mod_lints::run_lints(&lints, mods.clone().into(), Default::default(), None).unwrap();
+    assert!(archive_with_multiple_paks_mods
+        .unwrap()
+        .contains(&multiple_paks_spec));
+    assert!(shader_file_mods.unwrap().contains_key(&multiple_paks_spec));
+
+    // A.pak contains the shader file
+    let disabled_paks = [(multiple_paks_spec.clone(), ["A.pak".to_string()].into())].into();
+    let LintReport {
+        archive_with_multiple_paks_mods,
+        shader_file_mods,
+        ..
+    } = mint::mod_lints::run_lints(&lints, mods.into(), disabled_paks, None).unwrap();
+    assert!(!archive_with_multiple_paks_mods
+        .unwrap()
+        .contains(&multiple_paks_spec));
+    assert!(!shader_file_mods.unwrap().contains_key(&multiple_paks_spec));
+}
+
 #[test]
 pub fn test_lint_non_asset_files() {
     let base_path = PathBuf::from_str("test_assets/lints/").unwrap();
@@ -214,7 +279,13 @@ pub fn test_lint_non_asset_files() {
     let LintReport {
         non_asset_file_mods,
         ..
-    } = mint::mod_lints::run_lints(&[LintId::NON_ASSET_FILES].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::NON_ASSET_FILES].into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
 
     println!("{:#?}", non_asset_file_mods);
 
@@ -238,7 +309,13 @@ pub fn test_lint_split_asset_pairs() {
     let LintReport {
         split_asset_pairs_mods,
         ..
-    } = mint::mod_lints::run_lints(&[LintId::SPLIT_ASSET_PAIRS].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::SPLIT_ASSET_PAIRS].into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
 
     println!("{:#?}", split_asset_pairs_mods);
 
@@ -282,6 +359,7 @@ pub fn test_lint_unmodified_game_assets() {
     } = mint::mod_lints::run_lints(
         &[LintId::UNMODIFIED_GAME_ASSETS].into(),
         mods.into(),
+        Default::default(),
         Some(reference_pak_path),
     )
     .unwrap();
@@ -295,3 +373,232 @@ pub fn test_lint_unmodified_game_assets() {
         Some(&["a.uexp".to_string(), "a.uasset".to_string()].into())
     );
 }
+
+#[test]
+pub fn test_lint_id_from_str() {
+    for id in LintId::ALL {
+        assert_eq!(id.name().parse::<LintId>(), Ok(id));
+    }
+    assert_eq!("Shader_Files".parse::<LintId>(), Ok(LintId::SHADER_FILES));
+    assert!("not_a_lint".parse::<LintId>().is_err());
+}
+
+#[test]
+pub fn test_lint_report_serialize() {
+    let base_path = PathBuf::from_str("test_assets/lints/").unwrap();
+    assert!(base_path.exists());
+    let a_path = base_path.clone().join("A.pak");
+    assert!(a_path.exists());
+    let b_path = base_path.clone().join("B.pak");
+    assert!(b_path.exists());
+    let a_spec = ModSpecification {
+        url: "A".to_string(),
+    };
+    let b_spec = ModSpecification {
+        url: "B".to_string(),
+    };
+    let mods = [(a_spec.clone(), a_path), (b_spec.clone(), b_path)];
+
+    let report = mint::mod_lints::run_lints(
+        &[LintId::CONFLICTING, LintId::SHADER_FILES].into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
+
+    let json = serde_json::to_value(&report).unwrap();
+    println!("{:#}", json);
+
+    assert_eq!(
+        json["shader_file_mods"]["A"],
+        serde_json::json!(["fsd/content/c.ushaderbytecode"])
+    );
+    assert_eq!(
+        json["conflicting_mods"]["fsd/content/a.uexp"],
+        serde_json::json!([{ "url": "A" }, { "url": "B" }])
+    );
+    assert!(json["empty_archive_mods"].is_null());
+
+    let findings = report.findings();
+    assert!(findings
+        .iter()
+        .any(|f| f.lint == LintId::CONFLICTING && f.mods == [a_spec.clone(), b_spec.clone()]));
+    assert!(findings
+        .iter()
+        .any(|f| f.lint == LintId::SHADER_FILES && f.mods == [a_spec.clone()]));
+
+    let sarif = report.to_sarif();
+    assert_eq!(sarif["version"], "2.1.0");
+    assert_eq!(
+        sarif["runs"][0]["results"].as_array().unwrap().len(),
+        findings.len()
+    );
+}
+
+#[test]
+pub fn test_lint_tar_gz_archive() {
+    let base_path = PathBuf::from_str("test_assets/lints/").unwrap();
+    assert!(base_path.exists());
+    let dir = tempfile::tempdir().unwrap();
+
+    let multiple_paks_archive_path = dir.path().join("multiple_paks.tar.gz");
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        std::fs::File::create(&multiple_paks_archive_path).unwrap(),
+        flate2::Compression::default(),
+    ));
+    builder
+        .append_path_with_name(base_path.join("A.pak"), "A.pak")
+        .unwrap();
+    builder
+        .append_path_with_name(base_path.join("B.pak"), "B.pak")
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let only_non_pak_path = dir.path().join("only_non_pak_files.tar");
+    let mut builder = tar::Builder::new(std::fs::File::create(&only_non_pak_path).unwrap());
+    builder
+        .append_path_with_name(base_path.join("reference"), "reference")
+        .unwrap();
+    builder.finish().unwrap();
+
+    let multiple_paks_spec = ModSpecification {
+        url: "multiple_paks".to_string(),
+    };
+    let only_non_pak_spec = ModSpecification {
+        url: "only_non_pak".to_string(),
+    };
+    let mods = [
+        (multiple_paks_spec.clone(), multiple_paks_archive_path),
+        (only_non_pak_spec.clone(), only_non_pak_path),
+    ];
+
+    let LintReport {
+        archive_with_multiple_paks_mods,
+        archive_with_only_non_pak_files_mods,
+        ..
+    } = mint::mod_lints::run_lints(
+        &[
+            LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
+            LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
+        ]
+        .into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
+
+    assert!(archive_with_multiple_paks_mods
+        .unwrap()
+        .contains(&multiple_paks_spec));
+    assert!(archive_with_only_non_pak_files_mods
+        .unwrap()
+        .contains(&only_non_pak_spec));
+}
+
+#[test]
+pub fn test_lint_7z_and_tar_xz_archives() {
+    let base_path = PathBuf::from_str("test_assets/lints/").unwrap();
+    assert!(base_path.exists());
+    let dir = tempfile::tempdir().unwrap();
+
+    let seven_z_path = dir.path().join("multiple_paks.7z");
+    let mut writer = sevenz_rust::SevenZWriter::create(&seven_z_path).unwrap();
+    for name in ["A.pak", "B.pak"] {
+        writer
+            .push_archive_entry(
+                sevenz_rust::SevenZArchiveEntry::from_path(base_path.join(name), name.to_string()),
+                Some(std::fs::File::open(base_path.join(name)).unwrap()),
+            )
+            .unwrap();
+    }
+    writer.finish().unwrap();
+
+    let mut builder = tar::Builder::new(vec![]);
+    builder
+        .append_path_with_name(base_path.join("A.pak"), "A.pak")
+        .unwrap();
+    builder
+        .append_path_with_name(base_path.join("B.pak"), "B.pak")
+        .unwrap();
+    let tar = builder.into_inner().unwrap();
+    let tar_xz_path = dir.path().join("multiple_paks.tar.xz");
+    let mut tar_xz = vec![];
+    lzma_rs::xz_compress(&mut tar.as_slice(), &mut tar_xz).unwrap();
+    std::fs::write(&tar_xz_path, tar_xz).unwrap();
+
+    for path in [&seven_z_path, &tar_xz_path] {
+        let mint::archive::ModContents::Archive { paks, .. } =
+            mint::archive::read_mod_contents(Box::new(std::fs::File::open(path).unwrap())).unwrap()
+        else {
+            panic!("{} not read as an archive", path.display());
+        };
+        assert_eq!(
+            vec![
+                (
+                    PathBuf::from("A.pak"),
+                    std::fs::read(base_path.join("A.pak")).unwrap()
+                ),
+                (
+                    PathBuf::from("B.pak"),
+                    std::fs::read(base_path.join("B.pak")).unwrap()
+                ),
+            ],
+            paks
+        );
+    }
+
+    let seven_z_spec = ModSpecification {
+        url: "multiple_paks_7z".to_string(),
+    };
+    let tar_xz_spec = ModSpecification {
+        url: "multiple_paks_tar_xz".to_string(),
+    };
+    let mods = [
+        (seven_z_spec.clone(), seven_z_path),
+        (tar_xz_spec.clone(), tar_xz_path),
+    ];
+
+    let LintReport {
+        archive_with_multiple_paks_mods,
+        ..
+    } = mint::mod_lints::run_lints(
+        &[LintId::ARCHIVE_WITH_MULTIPLE_PAKS].into(),
+        mods.into(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
+
+    let archive_with_multiple_paks_mods = archive_with_multiple_paks_mods.unwrap();
+    assert!(archive_with_multiple_paks_mods.contains(&seven_z_spec));
+    assert!(archive_with_multiple_paks_mods.contains(&tar_xz_spec));
+}
+
+#[test]
+pub fn test_gzip_compressed_pak_is_not_tar_gz() {
+    use std::io::Write;
+
+    let base_path = PathBuf::from_str("test_assets/lints/").unwrap();
+    let pak = std::fs::read(base_path.join("A.pak")).unwrap();
+
+    let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    gz.write_all(&pak).unwrap();
+    let gz = gz.finish().unwrap();
+    assert!(mint::archive::detect_format(&gz).is_none());
+
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        vec![],
+        flate2::Compression::default(),
+    ));
+    builder
+        .append_path_with_name(base_path.join("A.pak"), "A.pak")
+        .unwrap();
+    let tar_gz = builder.into_inner().unwrap().finish().unwrap();
+    assert_eq!(
+        Some("tar.gz"),
+        mint::archive::detect_format(&tar_gz[..tar_gz.len().min(mint::archive::HEADER_LEN)])
+            .map(|f| f.name)
+    );
+}
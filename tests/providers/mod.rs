@@ -0,0 +1,601 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+use mint::providers::{FetchProgress, ModSpecification, ModStore};
+
+#[tokio::test]
+pub async fn test_dir_provider_builds_pak() {
+    let dir = tempfile::tempdir().unwrap();
+    let mod_dir = dir.path().join("TestMod");
+    std::fs::create_dir_all(mod_dir.join("FSD/Content/Sub")).unwrap();
+    std::fs::write(mod_dir.join("FSD/Content/A.uasset"), b"a").unwrap();
+    std::fs::write(mod_dir.join("FSD/Content/Sub/B.uexp"), b"b").unwrap();
+
+    let cache_dir = dir.path().join("cache");
+    std::fs::create_dir(&cache_dir).unwrap();
+    let store = ModStore::new(&cache_dir, &HashMap::new()).unwrap();
+    let spec = ModSpecification::new(mod_dir.to_string_lossy().to_string());
+    let info = store
+        .resolve_mods(&[spec.clone()], false)
+        .await
+        .unwrap()
+        .remove(&spec)
+        .unwrap();
+    assert_eq!("dir", info.provider);
+    assert_eq!("TestMod", info.name);
+
+    let path = store
+        .fetch_mod(&info.resolution, false, None)
+        .await
+        .unwrap();
+    let mut reader = BufReader::new(std::fs::File::open(&path).unwrap());
+    let pak = repak::PakBuilder::new().reader(&mut reader).unwrap();
+    assert_eq!("../../../", pak.mount_point());
+    let mut files = pak.files();
+    files.sort();
+    assert_eq!(
+        vec!["FSD/Content/A.uasset", "FSD/Content/Sub/B.uexp"],
+        files
+    );
+    assert_eq!(
+        b"b".to_vec(),
+        pak.get("FSD/Content/Sub/B.uexp", &mut reader).unwrap()
+    );
+
+    // same files result in the same pak
+    let again = store
+        .fetch_mod(&info.resolution, false, None)
+        .await
+        .unwrap();
+    assert_eq!(path, again);
+
+    let not_a_mod = dir.path().join("NotAMod");
+    std::fs::create_dir_all(&not_a_mod).unwrap();
+    let spec = ModSpecification::new(not_a_mod.to_string_lossy().to_string());
+    let info = store
+        .resolve_mods(&[spec.clone()], false)
+        .await
+        .unwrap()
+        .remove(&spec)
+        .unwrap();
+    assert!(store
+        .fetch_mod(&info.resolution, false, None)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+pub async fn test_dir_provider_only_packs_fsd() {
+    let dir = tempfile::tempdir().unwrap();
+    let mod_dir = dir.path().join("TestMod");
+    std::fs::create_dir_all(mod_dir.join("FSD/Content")).unwrap();
+    std::fs::write(mod_dir.join("FSD/Content/A.uasset"), b"a").unwrap();
+    std::fs::write(mod_dir.join("README.md"), b"readme").unwrap();
+    std::fs::write(mod_dir.join("FSD.uproject"), b"{}").unwrap();
+    std::fs::create_dir_all(mod_dir.join(".git")).unwrap();
+    std::fs::write(mod_dir.join(".git/HEAD"), b"ref: refs/heads/main").unwrap();
+    std::fs::create_dir_all(mod_dir.join("Saved/Logs")).unwrap();
+    std::fs::write(mod_dir.join("Saved/Logs/FSD.log"), b"log").unwrap();
+
+    let cache_dir = dir.path().join("cache");
+    std::fs::create_dir(&cache_dir).unwrap();
+    let store = ModStore::new(&cache_dir, &HashMap::new()).unwrap();
+    let spec = ModSpecification::new(mod_dir.to_string_lossy().to_string());
+    let info = store
+        .resolve_mods(&[spec.clone()], false)
+        .await
+        .unwrap()
+        .remove(&spec)
+        .unwrap();
+    let path = store
+        .fetch_mod(&info.resolution, false, None)
+        .await
+        .unwrap();
+    let mut reader = BufReader::new(std::fs::File::open(&path).unwrap());
+    let pak = repak::PakBuilder::new().reader(&mut reader).unwrap();
+    assert_eq!(vec!["FSD/Content/A.uasset"], pak.files());
+}
+
+struct Request {
+    path: String,
+    /// Headers with lowercase names.
+    headers: HashMap<String, String>,
+    port: u16,
+}
+
+struct Response {
+    status: &'static str,
+    headers: Vec<String>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: "200 OK",
+            headers: vec![],
+            body: body.into(),
+        }
+    }
+
+    fn not_found() -> Self {
+        Self {
+            status: "404 Not Found",
+            headers: vec![],
+            body: vec![],
+        }
+    }
+}
+
+/// Serve `routes` over HTTP on a local port, standing in for the GitHub API and mod hosts.
+fn serve(routes: impl Fn(&Request) -> Response + Send + 'static) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut headers = HashMap::new();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                let Some((name, value)) = header.trim().split_once(':') else {
+                    break;
+                };
+                headers.insert(name.to_ascii_lowercase(), value.trim().to_string());
+            }
+            let request = Request {
+                path: request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string(),
+                headers,
+                port,
+            };
+            let response = routes(&request);
+            write!(stream, "HTTP/1.1 {}\r\n", response.status).unwrap();
+            for header in &response.headers {
+                write!(stream, "{header}\r\n").unwrap();
+            }
+            // a larger Content-Length can be set to simulate a dropped connection
+            if !response
+                .headers
+                .iter()
+                .any(|h| h.to_ascii_lowercase().starts_with("content-length:"))
+            {
+                write!(stream, "Content-Length: {}\r\n", response.body.len()).unwrap();
+            }
+            write!(stream, "Connection: close\r\n\r\n").unwrap();
+            stream.write_all(&response.body).ok();
+        }
+    });
+    port
+}
+
+#[tokio::test]
+pub async fn test_github_provider_resolves_release_assets() {
+    let port = serve(|request| {
+        let port = request.port;
+        match request.path.as_str() {
+            "/repos/owner/repo/releases?per_page=100" => Response {
+                headers: vec![format!(
+                    r#"Link: <http://127.0.0.1:{port}/repos/owner/repo/releases?per_page=100&page=2>; rel="next", <http://127.0.0.1:{port}/repos/owner/repo/releases?per_page=100&page=2>; rel="last""#
+                )],
+                ..Response::ok(format!(
+                    r#"[
+                    {{"tag_name": "v3", "draft": false, "prerelease": true, "assets": [
+                        {{"name": "Mod.pak", "browser_download_url": "http://127.0.0.1:{port}/dl/v3/Mod.pak"}}
+                    ]}}
+                ]"#
+                ))
+            },
+            "/repos/owner/repo/releases?per_page=100&page=2" => Response::ok(format!(
+                r#"[
+                    {{"tag_name": "v2", "draft": false, "prerelease": false, "assets": [
+                        {{"name": "Mod.pak", "browser_download_url": "http://127.0.0.1:{port}/dl/v2/Mod.pak"}},
+                        {{"name": "Mod-debug.pak", "browser_download_url": "http://127.0.0.1:{port}/dl/v2/Mod-debug.pak"}},
+                        {{"name": "README.md", "browser_download_url": "http://127.0.0.1:{port}/dl/v2/README.md"}}
+                    ]}},
+                    {{"tag_name": "v1", "draft": false, "prerelease": false, "assets": [
+                        {{"name": "Mod.zip", "browser_download_url": "http://127.0.0.1:{port}/dl/v1/Mod.zip"}}
+                    ]}}
+                ]"#
+            )),
+            "/dl/v2/Mod.pak" => Response::ok(b"v2 pak"),
+            "/dl/v1/Mod.zip" => Response::ok(b"v1 zip"),
+            _ => Response::not_found(),
+        }
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let parameters = HashMap::from([(
+        "github".to_string(),
+        HashMap::from([("api_url".to_string(), format!("http://127.0.0.1:{port}"))]),
+    )]);
+    let store = ModStore::new(dir.path(), &parameters).unwrap();
+
+    // latest stable release, asset selected by pattern
+    let spec = ModSpecification::new("https://github.com/owner/repo?asset=mod.pak".to_string());
+    let (_, info) = store.resolve_mod(spec.clone(), false).await.unwrap();
+    assert_eq!("github", info.provider);
+    assert_eq!("repo", info.name);
+    assert_eq!(
+        "https://github.com/owner/repo/releases/download/v2/Mod.pak",
+        info.resolution.url.0
+    );
+    assert_eq!(
+        vec![
+            "https://github.com/owner/repo@v3?asset=mod.pak",
+            "https://github.com/owner/repo@v2?asset=mod.pak",
+            "https://github.com/owner/repo@v1?asset=mod.pak",
+        ],
+        info.versions
+            .iter()
+            .map(|v| v.url.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert!(!store.is_pinned(&spec));
+    assert_eq!(
+        b"v2 pak".to_vec(),
+        std::fs::read(
+            store
+                .fetch_mod(&info.resolution, false, None)
+                .await
+                .unwrap()
+        )
+        .unwrap()
+    );
+
+    // multiple mod assets without a pattern
+    let spec = ModSpecification::new("https://github.com/owner/repo".to_string());
+    assert!(store.resolve_mod(spec, false).await.is_err());
+
+    // pinned release
+    let spec = ModSpecification::new("https://github.com/owner/repo@v1".to_string());
+    assert!(store.is_pinned(&spec));
+    let (_, info) = store.resolve_mod(spec.clone(), false).await.unwrap();
+    assert_eq!(Some("v1".to_string()), store.get_version_name(&spec));
+    assert_eq!(
+        b"v1 zip".to_vec(),
+        std::fs::read(
+            store
+                .fetch_mod(&info.resolution, false, None)
+                .await
+                .unwrap()
+        )
+        .unwrap()
+    );
+
+    let spec = ModSpecification::new("https://github.com/owner/repo@v0".to_string());
+    assert!(store.resolve_mod(spec, false).await.is_err());
+}
+
+#[tokio::test]
+pub async fn test_http_provider_conditional_update() {
+    use std::sync::{Arc, Mutex};
+
+    let content = Arc::new(Mutex::new(("\"1\"", b"PK\x03\x04first".to_vec())));
+    let requests = Arc::new(Mutex::new(vec![]));
+    let port = serve({
+        let content = content.clone();
+        let requests = requests.clone();
+        move |request| {
+            let (etag, body) = content.lock().unwrap().clone();
+            let if_none_match = request.headers.get("if-none-match").cloned();
+            requests.lock().unwrap().push(if_none_match.clone());
+            let mut response = if if_none_match.as_deref() == Some(etag) {
+                Response {
+                    status: "304 Not Modified",
+                    headers: vec![],
+                    body: vec![],
+                }
+            } else {
+                Response::ok(body)
+            };
+            response.headers = vec![
+                format!("ETag: {etag}"),
+                "Last-Modified: Wed, 21 Oct 2015 07:28:00 GMT".to_string(),
+                "Content-Type: application/octet-stream".to_string(),
+            ];
+            response
+        }
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let store = ModStore::new(dir.path(), &HashMap::new()).unwrap();
+    let spec = ModSpecification::new(format!("http://127.0.0.1:{port}/Mod.pak"));
+    let (_, info) = store.resolve_mod(spec.clone(), false).await.unwrap();
+    assert_eq!(Some("latest".to_string()), store.get_version_name(&spec));
+
+    let path = store
+        .fetch_mod(&info.resolution, false, None)
+        .await
+        .unwrap();
+    assert_eq!(b"PK\x03\x04first".to_vec(), std::fs::read(&path).unwrap());
+    let version = store.get_version_name(&spec).unwrap();
+    assert!(version.starts_with("2015-10-21 ("), "{version}");
+
+    // cached files are not requested again unless updating
+    store
+        .fetch_mod(&info.resolution, false, None)
+        .await
+        .unwrap();
+    assert_eq!(vec![None], *requests.lock().unwrap());
+
+    // unchanged files are not downloaded again
+    let again = store.fetch_mod(&info.resolution, true, None).await.unwrap();
+    assert_eq!(path, again);
+    assert_eq!(Some("\"1\"".to_string()), requests.lock().unwrap()[1]);
+    assert_eq!(version, store.get_version_name(&spec).unwrap());
+
+    *content.lock().unwrap() = ("\"2\"", b"PK\x03\x04second".to_vec());
+    let updated = store.fetch_mod(&info.resolution, true, None).await.unwrap();
+    assert_eq!(
+        b"PK\x03\x04second".to_vec(),
+        std::fs::read(&updated).unwrap()
+    );
+    assert_ne!(version, store.get_version_name(&spec).unwrap());
+}
+
+#[tokio::test]
+pub async fn test_http_provider_resumes_interrupted_download() {
+    use std::sync::{Arc, Mutex};
+
+    const CONTENT: &[u8] = b"PK\x03\x04abcdef";
+    let ranges = Arc::new(Mutex::new(vec![]));
+    let port = serve({
+        let ranges = ranges.clone();
+        move |request| {
+            let range = request.headers.get("range").cloned();
+            ranges.lock().unwrap().push(range.clone());
+            match range.as_deref().and_then(|r| r.strip_prefix("bytes=")) {
+                Some(range) => {
+                    assert_eq!(
+                        Some("\"v1\""),
+                        request.headers.get("if-range").map(|s| s.as_str())
+                    );
+                    let start = range.trim_end_matches('-').parse::<usize>().unwrap();
+                    Response {
+                        status: "206 Partial Content",
+                        headers: vec![
+                            "ETag: \"v1\"".to_string(),
+                            format!("Content-Range: bytes {start}-9/10"),
+                        ],
+                        body: CONTENT[start..].to_vec(),
+                    }
+                }
+                // drop the connection half way through
+                None => Response {
+                    status: "200 OK",
+                    headers: vec!["ETag: \"v1\"".to_string(), "Content-Length: 10".to_string()],
+                    body: CONTENT[..4].to_vec(),
+                },
+            }
+        }
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let store = ModStore::new(dir.path(), &HashMap::new()).unwrap();
+    let spec = ModSpecification::new(format!("http://127.0.0.1:{port}/Mod.pak"));
+    let (_, info) = store.resolve_mod(spec, false).await.unwrap();
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+    let path = store
+        .fetch_mod(&info.resolution, false, Some(tx))
+        .await
+        .unwrap();
+    assert_eq!(CONTENT.to_vec(), std::fs::read(&path).unwrap());
+    assert_eq!(
+        vec![None, Some("bytes=4-".to_string())],
+        *ranges.lock().unwrap()
+    );
+
+    let mut progress = vec![];
+    while let Ok(p) = rx.try_recv() {
+        if let FetchProgress::Progress {
+            progress: p, size, ..
+        } = p
+        {
+            progress.push((p, size));
+        }
+    }
+    assert_eq!(Some(&(4, 10)), progress.first());
+    assert_eq!(Some(&(10, 10)), progress.last());
+}
+
+#[tokio::test]
+pub async fn test_sha256_pinned_mods() {
+    use mint::providers::ProviderError;
+    use sha2::{Digest, Sha256};
+
+    let port = serve(|request| match request.path.as_str() {
+        "/Mod.pak" => Response::ok(b"PK\x03\x04hosted"),
+        _ => Response::not_found(),
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache_dir = dir.path().join("cache");
+    std::fs::create_dir(&cache_dir).unwrap();
+    let store = ModStore::new(&cache_dir, &HashMap::new()).unwrap();
+
+    let fetch = |url: String| {
+        let store = &store;
+        async move {
+            let (_, info) = store.resolve_mod(ModSpecification::new(url), false).await?;
+            store.fetch_mod(&info.resolution, false, None).await
+        }
+    };
+
+    let file = dir.path().join("Local.pak");
+    std::fs::write(&file, b"local").unwrap();
+    let file_hash = hex::encode(Sha256::digest(b"local"));
+    let file_url = format!("{}#sha256={}", file.display(), file_hash);
+    let path = fetch(file_url.clone()).await.unwrap();
+    assert_eq!(b"local".to_vec(), std::fs::read(&path).unwrap());
+    assert_ne!(file, path);
+
+    // the checked copy is used even if the file changes afterwards
+    std::fs::write(&file, b"tampered").unwrap();
+    assert_eq!(path, fetch(file_url).await.unwrap());
+    assert!(matches!(
+        fetch(format!("{}#sha256={}", file.display(), "0".repeat(64))).await,
+        Err(ProviderError::HashMismatch { .. })
+    ));
+
+    let url = format!("http://127.0.0.1:{port}/Mod.pak");
+    assert!(matches!(
+        fetch(format!("{url}#sha256={}", "0".repeat(64))).await,
+        Err(ProviderError::HashMismatch { .. })
+    ));
+    assert!(matches!(
+        fetch(format!("{url}#sha256=1234")).await,
+        Err(ProviderError::InvalidUrl { .. })
+    ));
+    let hash = hex::encode(Sha256::digest(b"PK\x03\x04hosted"));
+    let path = fetch(format!("{url}#sha256={}", hash.to_ascii_uppercase()))
+        .await
+        .unwrap();
+    assert_eq!(b"PK\x03\x04hosted".to_vec(), std::fs::read(path).unwrap());
+}
+
+#[tokio::test]
+pub async fn test_http_provider_checks_content() {
+    use mint::providers::ProviderError;
+
+    let port = serve(|request| {
+        let (content_type, body): (_, &[u8]) = match request.path.as_str() {
+            "/page" => (
+                "text/html; charset=utf-8",
+                b"<!DOCTYPE html><html><head><TITLE>\n  Download  Mod.zip\n</TITLE></head></html>",
+            ),
+            "/text" => ("text/plain", b"not a mod"),
+            "/odd" => ("text/plain", b"not a mod either"),
+            "/zip" => ("application/x-zip-compressed", b"PK\x05\x06 empty zip"),
+            "/pak" => ("binary/octet-stream", b"data\xE1\x12\x6F\x5Afooter"),
+            _ => return Response::not_found(),
+        };
+        let mut response = Response::ok(body);
+        response.headers = vec![format!("Content-Type: {content_type}")];
+        response
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let parameters = HashMap::from([(
+        "http".to_string(),
+        HashMap::from([(
+            "content_types".to_string(),
+            // invalid entries are ignored
+            format!("text/plain http://127.0.0.1:{port}/odd=text/plain,text/csv =text/plain"),
+        )]),
+    )]);
+    let store = ModStore::new(dir.path(), &parameters).unwrap();
+    let fetch = |path: &str| {
+        let store = &store;
+        let spec = ModSpecification::new(format!("http://127.0.0.1:{port}{path}"));
+        async move {
+            let (_, info) = store.resolve_mod(spec, false).await?;
+            store.fetch_mod(&info.resolution, false, None).await
+        }
+    };
+
+    match fetch("/page").await {
+        Err(ProviderError::HtmlPage { title, .. }) => {
+            assert_eq!(Some("Download Mod.zip".to_string()), title)
+        }
+        res => panic!("unexpected result {res:?}"),
+    }
+    match fetch("/text").await {
+        Err(ProviderError::UnrecognizedContent { content_type, .. }) => {
+            assert_eq!(Some("text/plain".to_string()), content_type)
+        }
+        res => panic!("unexpected result {res:?}"),
+    }
+    assert!(fetch("/odd").await.is_ok());
+    assert!(fetch("/zip").await.is_ok());
+    assert!(fetch("/pak").await.is_ok());
+}
+
+#[tokio::test]
+pub async fn test_http_provider_rewrites_share_links() {
+    let port = serve(|request| match request.path.as_str() {
+        "/files/Mod.pak" => Response::ok(b"PK\x03\x04shared"),
+        _ => Response::not_found(),
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let parameters = HashMap::from([(
+        "http".to_string(),
+        HashMap::from([(
+            "link_rules".to_string(),
+            // invalid rules are ignored
+            r"^(unclosed http://example.org ^http://127\.0\.0\.1:(?P<port>\d+)/share/(?P<name>[^/]+)$ http://127.0.0.1:${port}/files/${name} ^dangling".to_string(),
+        )]),
+    )]);
+    let store = ModStore::new(dir.path(), &parameters).unwrap();
+    let resolve = |url: &str| {
+        let store = &store;
+        let spec = ModSpecification::new(url.to_string());
+        async move {
+            let (_, info) = store.resolve_mod(spec.clone(), false).await.unwrap();
+            assert_eq!(spec, info.spec);
+            info
+        }
+    };
+
+    for (url, expected) in [
+        (
+            "https://drive.google.com/file/d/abc-DEF_1/view?usp=sharing",
+            "https://drive.google.com/uc?export=download&confirm=t&id=abc-DEF_1",
+        ),
+        (
+            "https://drive.google.com/open?id=abc-DEF_1",
+            "https://drive.google.com/uc?export=download&confirm=t&id=abc-DEF_1",
+        ),
+        (
+            "https://www.dropbox.com/scl/fi/xyz/Mod.zip?rlkey=key&dl=0",
+            "https://dl.dropboxusercontent.com/scl/fi/xyz/Mod.zip?rlkey=key&dl=0",
+        ),
+        (
+            "https://1drv.ms/u/s!AbC",
+            "https://api.onedrive.com/v1.0/shares/u!aHR0cHM6Ly8xZHJ2Lm1zL3UvcyFBYkM/root/content",
+        ),
+        (
+            "https://onedrive.live.com/redir?cid=1&resid=2&authkey=3",
+            "https://onedrive.live.com/download?cid=1&resid=2&authkey=3",
+        ),
+        (
+            "https://media.discordapp.net/attachments/1/2/Mod.zip?ex=a&is=b&hm=c",
+            "https://cdn.discordapp.com/attachments/1/2/Mod.zip?ex=a&is=b&hm=c",
+        ),
+        ("https://example.org/Mod.zip", "https://example.org/Mod.zip"),
+    ] {
+        assert_eq!(expected, resolve(url).await.resolution.url.0);
+    }
+
+    // pins are kept
+    let pin = format!("#sha256={}", "0".repeat(64));
+    assert_eq!(
+        format!("https://drive.google.com/uc?export=download&confirm=t&id=abc{pin}"),
+        resolve(&format!("https://drive.google.com/file/d/abc/view{pin}"))
+            .await
+            .resolution
+            .url
+            .0
+    );
+
+    // user rules
+    let info = resolve(&format!("http://127.0.0.1:{port}/share/Mod.pak")).await;
+    assert_eq!(
+        format!("http://127.0.0.1:{port}/files/Mod.pak"),
+        info.resolution.url.0
+    );
+    let path = store
+        .fetch_mod(&info.resolution, false, None)
+        .await
+        .unwrap();
+    assert_eq!(b"PK\x03\x04shared".to_vec(), std::fs::read(path).unwrap());
+}
@@ -1 +1,3 @@
+mod doctor;
 mod lint;
+mod providers;